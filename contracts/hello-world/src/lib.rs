@@ -1,5 +1,9 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, String};
+#![allow(clippy::too_many_arguments)]
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, xdr::ToXdr, Address, Bytes, BytesN, Env, IntoVal, Map, String,
+    Symbol, TryFromVal, Val, Vec,
+};
 
 extern crate alloc;
 use alloc::string::ToString;
@@ -16,26 +20,242 @@ pub struct Campaign {
     pub title: String,
     pub description: String,
     pub beneficiary: Address,
-    pub goal_amount: u64,
-    pub current_amount: u64,
+    pub goal_amount: i128,
+    pub current_amount: i128,
+    pub disbursed_amount: i128,
     pub start_time: u64,
     pub end_time: u64,
     pub verified: bool,
     pub trust_score: u32,
-    pub category: String,
+    pub category: Category,
     pub location: String,
+    pub at_risk: bool,
+    pub at_risk_acknowledged_at: Option<u64>,
+    pub perpetual: bool,
+    pub active: bool,
+    pub funding_mode: FundingMode,
+    /// Another campaign already used the same title
+    pub possible_duplicate: bool,
+    /// The endgame disbursement lock has been announced
+    pub endgame_locked: bool,
+    /// Whether the goal was met when the campaign was closed or finalized
+    pub goal_reached: bool,
+    /// Free-form category of a campaign created before categories were an
+    /// enum
+    pub legacy_category: Option<String>,
+    /// Smallest accepted donation, 0 for no minimum
+    pub min_donation: i128,
+    /// Most a single donor may give in total, 0 for no cap
+    pub max_donation_per_donor: i128,
+    /// Most the campaign may raise, in basis points of its goal (12000 is
+    /// 120%); 0 for no cap
+    pub overfunding_limit_bps: u32,
+    /// Taken down by the admin: disbursements are blocked and every
+    /// donation can be refunded
+    pub suspended: bool,
+    /// When donations first reached the goal
+    pub goal_reached_at: Option<u64>,
+    /// Stop accepting donations once the goal is reached
+    pub close_on_goal: bool,
+    /// Off-chain metadata (images, long-form story), if any
+    pub metadata_uri: Option<String>,
+    /// Token contract the campaign is denominated in; every amount on it is
+    /// in this asset
+    pub token: Address,
+    /// How the campaign ended, `Ongoing` while it is active
+    pub outcome: CampaignOutcome,
 }
 
-#[derive(Clone)]
+impl Campaign {
+    /// Whether the campaign's end time has passed; perpetual campaigns never end
+    pub fn has_ended(&self, now: u64) -> bool {
+        !self.perpetual && now > self.end_time
+    }
+
+    /// Most the campaign may raise, if it has an overfunding limit
+    pub fn funding_cap(&self) -> Option<i128> {
+        if self.overfunding_limit_bps == 0 {
+            return None;
+        }
+        let bps = self.overfunding_limit_bps as i128;
+        Some((self.goal_amount / 10000).saturating_mul(bps).saturating_add(self.goal_amount % 10000 * bps / 10000))
+    }
+}
+
+/// What happens to donations if a campaign ends below its goal
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum FundingMode {
+    /// The beneficiary keeps whatever was raised
+    KeepItAll,
+    /// Donors can reclaim their donations if the goal is missed
+    AllOrNothing,
+}
+
+/// How a campaign ended, recorded when it is closed, finalized or suspended
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum CampaignOutcome {
+    /// Still accepting donations
+    Ongoing,
+    /// Ended with its goal met
+    Successful,
+    /// Reached its end time below its goal
+    Unsuccessful,
+    /// Closed by the beneficiary before its end time, below its goal
+    ClosedEarly,
+    /// Taken down by the admin
+    Suspended,
+}
+
+impl CampaignOutcome {
+    /// Whether funds may still be disbursed. An all-or-nothing campaign
+    /// that missed its goal holds its funds for donor refunds.
+    pub fn allows_disbursement(&self, funding_mode: FundingMode) -> bool {
+        match self {
+            CampaignOutcome::Ongoing | CampaignOutcome::Successful => true,
+            CampaignOutcome::Unsuccessful | CampaignOutcome::ClosedEarly => funding_mode == FundingMode::KeepItAll,
+            CampaignOutcome::Suspended => false,
+        }
+    }
+}
+
+/// What a campaign raises money for
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum Category {
+    Health,
+    Education,
+    Environment,
+    Emergency,
+    Community,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 6] = [
+        Category::Health,
+        Category::Education,
+        Category::Environment,
+        Category::Emergency,
+        Category::Community,
+        Category::Other,
+    ];
+
+    /// English name, used where no localized label is set
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::Health => "Health",
+            Category::Education => "Education",
+            Category::Environment => "Environment",
+            Category::Emergency => "Emergency",
+            Category::Community => "Community",
+            Category::Other => "Other",
+        }
+    }
+
+    /// Category whose name matches a free-form category string exactly, or
+    /// `Other`
+    pub fn from_name(name: &String) -> Category {
+        Category::ALL.into_iter()
+            .find(|category| *name == String::from_str(name.env(), category.name()))
+            .unwrap_or(Category::Other)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 #[contracttype]
 pub struct Donation {
     pub id: BytesN<32>,
     pub campaign_id: BytesN<32>,
     pub donor: Address,
-    pub amount: u64,
+    pub amount: i128,
     pub timestamp: u64,
     pub nft_minted: bool,
-    pub anonymous: bool,
+    pub privacy: Privacy,
+    pub refunded: bool,
+    /// Public message of support, empty if none; shown even when the donor
+    /// is hidden
+    pub message: String,
+    /// Who the badge went to when given in someone's honor; shown even when
+    /// the donor is hidden
+    pub badge_recipient: Option<Address>,
+    /// Who referred the donor, if anyone
+    pub referrer: Option<Address>,
+    /// Platform fee taken out of the gift; `amount` is what the campaign
+    /// received
+    pub platform_fee: i128,
+}
+
+/// Tax receipt for a donation. `receipt_hash` commits to every other
+/// field, so a copy can be checked against the contract with
+/// `verify_receipt`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Receipt {
+    pub donation_id: BytesN<32>,
+    pub campaign_id: BytesN<32>,
+    pub campaign_title: String,
+    pub beneficiary: Address,
+    pub donor: Address,
+    pub gross_amount: i128,
+    pub fee: i128,
+    pub net_amount: i128,
+    pub timestamp: u64,
+    /// A refunded donation's receipt no longer verifies against one issued
+    /// before the refund
+    pub refunded: bool,
+    pub receipt_hash: BytesN<32>,
+}
+
+/// What a donor allows to be shown publicly about a donation.
+///
+/// A gift that hides its donor still counts toward the donor's running
+/// total for the campaign, which sets their badge tier there, and toward
+/// their trust score; both only ever show aggregates. It is left out of the
+/// leaderboard, donor listings and every event, and mints no badge in the
+/// donor's name. The donor sees it through `get_my_donations`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum Privacy {
+    Public,
+    HideAmount,
+    HideDonor,
+    HideBoth,
+}
+
+impl Privacy {
+    /// Map the legacy `anonymous` flag onto a privacy mode
+    pub fn from_anonymous(anonymous: bool) -> Self {
+        if anonymous { Privacy::HideDonor } else { Privacy::Public }
+    }
+
+    pub fn hides_donor(&self) -> bool {
+        matches!(self, Privacy::HideDonor | Privacy::HideBoth)
+    }
+
+    pub fn hides_amount(&self) -> bool {
+        matches!(self, Privacy::HideAmount | Privacy::HideBoth)
+    }
+}
+
+/// Why a trust score was written, carried by `trust` `updated` events
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum TrustChangeReason {
+    /// A record was initialized or imported
+    Created,
+    Donation,
+    CampaignCreated,
+    /// A finalized campaign counted towards its beneficiary
+    CampaignOutcome,
+    /// An admin adjustment, including suspension penalties
+    AdminAdjustment,
+    Referral,
+    Verification,
+    Decay,
+    /// A donation was raised by the entity as a campaign's fundraiser
+    Fundraising,
 }
 
 #[derive(Clone)]
@@ -45,9 +265,113 @@ pub struct TrustScore {
     pub score: u32,
     pub verification_level: u32,
     pub donation_count: u32,
-    pub total_donated: u64,
+    pub total_donated: i128,
     pub campaigns_created: u32,
     pub last_updated: u64,
+    /// Net admin adjustment, kept on top of the score computed from activity
+    pub adjustment: i32,
+    /// Hash of the KYC evidence behind `verification_level`, all zeros if
+    /// none was recorded
+    pub evidence_hash: BytesN<32>,
+    /// Months in a row with a donation, 0 before the first one or once more
+    /// than 35 days have passed since the last
+    pub current_streak_months: u32,
+    pub longest_streak: u32,
+    pub streak_started_at: u64,
+    pub last_donation_at: u64,
+    /// Points lost to inactivity since the score was last recalculated
+    pub decay_applied: u32,
+    /// Finalized campaigns that reached their goal
+    pub campaigns_completed: u32,
+    /// Finalized campaigns that ended below a quarter of their goal
+    pub campaigns_failed: u32,
+}
+
+/// Public self-description of a beneficiary or donor
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Profile {
+    pub display_name: String,
+    pub bio_uri: String,
+    pub website: String,
+    /// The admin checked `display_name` against identity evidence; cleared
+    /// whenever the profile changes
+    pub name_verified: bool,
+    pub updated_at: u64,
+}
+
+/// The components behind a trust score. For scores the contract computed
+/// they sum, clamped to 0-100, to `score`; imported scores are kept as
+/// given until the entity's next donation recalculates them.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ScoreBreakdown {
+    pub score: u32,
+    pub base: u32,
+    /// Up to 25 points for the number of donations
+    pub donation_points: u32,
+    /// Up to 20 points for the total donated
+    pub amount_points: u32,
+    /// Extra amount points from the donation streak
+    pub streak_bonus: u32,
+    /// Bonus for completed campaigns less the penalty for failed ones
+    pub campaign_points: i32,
+    pub verification_bonus: u32,
+    /// Net admin adjustment, including referral bonuses
+    pub adjustment: i32,
+    /// Points lost to inactivity
+    pub decay: u32,
+}
+
+impl ScoreBreakdown {
+    /// The components summed and clamped to 0-100
+    fn total(&self) -> u32 {
+        let earned = (self.base + self.donation_points + self.amount_points + self.streak_bonus + self.verification_bonus) as i64;
+        (earned + self.campaign_points as i64 + self.adjustment as i64 - self.decay as i64).clamp(0, 100) as u32
+    }
+}
+
+/// Supporter tier a badge is awarded at, by net amount given
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum BadgeTier {
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+    Diamond,
+    /// Raised enough for a campaign as a registered fundraiser; never
+    /// reached by donating
+    Ambassador,
+}
+
+impl BadgeTier {
+    /// Tier reached by a net amount of `amount`
+    pub fn for_amount(amount: i128) -> BadgeTier {
+        if amount < 1000 {
+            BadgeTier::Bronze
+        } else if amount < 5000 {
+            BadgeTier::Silver
+        } else if amount < 10000 {
+            BadgeTier::Gold
+        } else if amount < 50000 {
+            BadgeTier::Platinum
+        } else {
+            BadgeTier::Diamond
+        }
+    }
+
+    /// Display name, also the badge's `badge_type`
+    pub fn name(&self) -> &'static str {
+        match self {
+            BadgeTier::Bronze => "Bronze Supporter",
+            BadgeTier::Silver => "Silver Supporter",
+            BadgeTier::Gold => "Gold Supporter",
+            BadgeTier::Platinum => "Platinum Supporter",
+            BadgeTier::Diamond => "Diamond Supporter",
+            BadgeTier::Ambassador => "Ambassador",
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -55,10 +379,19 @@ pub struct TrustScore {
 pub struct NFTBadge {
     pub id: BytesN<32>,
     pub owner: Address,
+    /// Name of `tier`, kept for clients that predate it
     pub badge_type: String,
-    pub campaign_id: Option<BytesN<32>>,
+    /// Campaign the badge was earned on, or the bundle for a bundle badge
+    pub campaign_id: BytesN<32>,
     pub minted_at: u64,
     pub metadata_uri: String,
+    /// The badge cannot be transferred
+    pub soulbound: bool,
+    pub tier: BadgeTier,
+    /// Net amount the owner has given the campaign, as of the last upgrade
+    pub donation_amount: i128,
+    /// Minting order, from 1
+    pub serial: u64,
 }
 
 #[derive(Clone)]
@@ -67,512 +400,12095 @@ pub struct Disbursement {
     pub id: BytesN<32>,
     pub campaign_id: BytesN<32>,
     pub recipient: Address,
-    pub amount: u64,
+    pub amount: i128,
+    /// Title of the milestone being paid out
     pub milestone: String,
     pub status: DisbursementStatus,
     pub created_at: u64,
     pub executed_at: Option<u64>,
+    pub execution_nonce: u32,
+    pub rejection_reason: Option<String>,
+    /// Position of the milestone in the campaign's plan
+    pub milestone_index: u32,
+    /// Time after which the request can no longer be executed
+    pub expires_at: u64,
 }
 
-#[derive(Clone, PartialEq)]
+/// One step of a campaign's spending plan
+#[derive(Clone, Debug, PartialEq)]
 #[contracttype]
-pub enum DisbursementStatus {
-    Pending,
-    Approved,
-    Executed,
-    Rejected,
+pub struct Milestone {
+    pub title: String,
+    /// Share of the goal allocated to this milestone
+    pub percentage: u32,
 }
 
-// ========== STORAGE KEYS ==========
-
+/// A pledge that becomes a donation once the counterpart gives enough
 #[derive(Clone)]
 #[contracttype]
-pub enum DataKey {
-    Campaign(BytesN<32>),
-    Donation(BytesN<32>),
-    TrustScore(Address),
-    NFTBadge(BytesN<32>),
-    Disbursement(BytesN<32>),
-    PlatformFee,
-    CampaignCounter,
-    DonationCounter,
-    NFTCounter,
-    DisbursementCounter,
+pub struct Challenge {
+    pub id: BytesN<32>,
+    pub challenger: Address,
+    pub campaign_id: BytesN<32>,
+    pub amount: i128,
+    pub counterpart: Address,
+    pub min_counterpart_amount: i128,
+    pub deadline: u64,
+    pub status: ChallengeStatus,
 }
 
-// ========== ERROR CODES ==========
+/// Campaigns the platform groups under one theme, each taking a share of
+/// every donation to the bundle
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Bundle {
+    pub id: BytesN<32>,
+    pub name: String,
+    /// Campaigns and their shares in basis points, summing to 10000
+    pub allocations: Vec<(BytesN<32>, u32)>,
+    pub created_at: u64,
+}
 
+/// Where a donor's dispute against a campaign stands
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[contracttype]
-pub enum SaviaError {
-    InvalidFee = 1,
-    InvalidGoal = 2,
-    InvalidDuration = 3,
-    CampaignNotFound = 4,
-    CampaignEnded = 5,
-    InvalidAmount = 6,
-    ScoreExists = 7,
-    InsufficientFunds = 8,
-    DisbursementNotFound = 9,
-    NotApproved = 10,
+pub enum DisputeStatus {
+    Open,
+    /// The admin agreed and suspended the campaign
+    Upheld,
+    Dismissed,
 }
 
-// ========== MAIN CONTRACT ==========
+/// A donor's concern about a campaign, awaiting or past admin review
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Dispute {
+    pub id: BytesN<32>,
+    pub campaign_id: BytesN<32>,
+    pub reporter: Address,
+    /// Off-chain statement of the concern and any evidence
+    pub reason_uri: String,
+    pub status: DisputeStatus,
+    pub filed_at: u64,
+    pub resolved_at: Option<u64>,
+}
 
-#[contract]
-pub struct SaviaContract;
+/// How many open disputes put a campaign's donations on hold until the
+/// admin reviews them; 0 disables a limit
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DisputeThreshold {
+    /// Open disputes a campaign may have before it is held
+    pub max_open: u32,
+    /// Open disputes a campaign may have before it is held, in basis
+    /// points of its unique donors
+    pub max_open_bps: u32,
+}
 
-#[contractimpl]
-impl SaviaContract {
-    
-    /// Initialize the contract with platform fee
-    pub fn initialize(env: Env, platform_fee: u64) -> Result<(), soroban_sdk::Error> {
-        if platform_fee > 1000 {
-            return Err(soroban_sdk::Error::from_contract_error(SaviaError::InvalidFee as u32));
-        }
-        
-        env.storage().instance().set(&DataKey::PlatformFee, &platform_fee);
-        env.storage().instance().set(&DataKey::CampaignCounter, &0u64);
-        env.storage().instance().set(&DataKey::DonationCounter, &0u64);
-        env.storage().instance().set(&DataKey::NFTCounter, &0u64);
-        env.storage().instance().set(&DataKey::DisbursementCounter, &0u64);
-        
-        Ok(())
-    }
+/// A donation repeated every `interval` seconds, paid from an allowance
+/// the donor grants the contract
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct RecurringDonation {
+    pub id: BytesN<32>,
+    pub campaign_id: BytesN<32>,
+    pub donor: Address,
+    pub amount: i128,
+    pub interval: u64,
+    pub next_execution: u64,
+    /// Runs left; 0 once finished or cancelled
+    pub remaining_executions: u32,
+}
 
-    /// Create a new campaign
-    pub fn create_campaign(
-        env: Env,
-        beneficiary: Address,
-        title: String,
-        description: String,
-        goal_amount: u64,
-        duration_days: u64,
-        category: String,
-        location: String,
-    ) -> Result<BytesN<32>, soroban_sdk::Error> {
-        // Validate inputs
-        if goal_amount == 0 {
-            return Err(soroban_sdk::Error::from_contract_error(SaviaError::InvalidGoal as u32));
-        }
-        
-        if duration_days == 0 || duration_days > 365 {
-            return Err(soroban_sdk::Error::from_contract_error(SaviaError::InvalidDuration as u32));
-        }
+/// Progress report a beneficiary posts for donors to audit
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CampaignUpdate {
+    /// Position in the campaign's update log, from 0
+    pub id: u32,
+    pub campaign_id: BytesN<32>,
+    pub title: String,
+    pub content_uri: String,
+    pub posted_at: u64,
+}
 
-        // Get and increment campaign counter
-        let counter: u64 = env.storage().instance().get(&DataKey::CampaignCounter).unwrap_or(0);
-        let new_counter = counter + 1;
-        env.storage().instance().set(&DataKey::CampaignCounter, &new_counter);
+/// A sponsor's pool that matches donations to a campaign
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct MatchPool {
+    pub sponsor: Address,
+    /// Matched amount per donated amount, in basis points (10000 is 1:1)
+    pub ratio_bps: u32,
+    pub cap: i128,
+    /// Funds left in escrow to match with
+    pub remaining: i128,
+}
 
-        // Generate campaign ID using existing data
-        let current_time = env.ledger().timestamp();
-        let mut hash_input = Bytes::new(&env);
-        
-        // Convert to bytes properly
-        let beneficiary_bytes = beneficiary.clone().to_xdr(&env);
-        let title_bytes = title.to_string().into_bytes();
-               
-        hash_input.append(&beneficiary_bytes); //&Bytes::from_slice(&env, &beneficiary_bytes));
-        hash_input.append(&Bytes::from_slice(&env, &title_bytes));
-        hash_input.append(&Bytes::from_slice(&env, &goal_amount.to_be_bytes()));
-        hash_input.append(&Bytes::from_slice(&env, &current_time.to_be_bytes()));
-        hash_input.append(&Bytes::from_slice(&env, &new_counter.to_be_bytes()));
-        
-        let campaign_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum ChallengeStatus {
+    Open,
+    Met,
+    Expired,
+}
 
-        let end_time = current_time + (duration_days * 24 * 60 * 60); // Convert to seconds
+/// How much detail donation, disbursement and trust events carry
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[contracttype]
+pub enum EventVerbosity {
+    /// Only record ids; trust score events are skipped
+    Minimal,
+    /// Ids plus the key amounts
+    Standard,
+    /// Full structured records
+    Verbose,
+}
 
-        let campaign = Campaign {
-            id: campaign_id.clone(),
-            title,
-            description,
-            beneficiary,
-            goal_amount,
-            current_amount: 0,
-            start_time: current_time,
-            end_time,
-            verified: false,
-            trust_score: 0,
-            category,
-            location,
-        };
+/// A time window during which donations use a reduced platform fee
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct FeeHoliday {
+    pub start: u64,
+    pub end: u64,
+    pub fee_bps: u64,
+    pub used: bool,
+}
 
-        env.storage().persistent().set(&DataKey::Campaign(campaign_id.clone()), &campaign);
-        
-        Ok(campaign_id)
-    }
+/// Fee breakdown for a prospective donation
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DonationQuote {
+    pub gross: i128,
+    pub fee_bps: u64,
+    pub fee: i128,
+    pub net: i128,
+    /// The donor must stay identifiable, so the donation cannot hide the donor
+    pub disclosure_required: bool,
+}
 
-    /// Get campaign details
-    pub fn get_campaign(env: Env, campaign_id: BytesN<32>) -> Option<Campaign> {
-        env.storage().persistent().get(&DataKey::Campaign(campaign_id))
-    }
+/// What a donation to a specific campaign would do, without making it
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DonationPreview {
+    pub gross: i128,
+    pub fee: i128,
+    pub net: i128,
+    /// Badge the net amount alone would earn
+    pub badge_type: String,
+    /// The campaign would reach its goal with this donation
+    pub completes_goal: bool,
+    /// Net amount the campaign may still raise before it must be verified,
+    /// `None` if it is verified or the cap is disabled
+    pub unverified_headroom: Option<i128>,
+}
 
-    /// Verify a campaign (admin function)
-    pub fn verify_campaign(
-        env: Env,
-        campaign_id: BytesN<32>,
-        trust_score: u32,
-    ) -> Result<(), soroban_sdk::Error> {
-        let mut campaign: Campaign = env.storage().persistent().get(&DataKey::Campaign(campaign_id.clone()))
-            .ok_or(soroban_sdk::Error::from_contract_error(SaviaError::CampaignNotFound as u32))?;
+/// How trust scores of inactive entities decay toward neutral
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct TrustDecay {
+    /// Inactivity, in seconds since `last_updated`, before decay applies
+    pub grace: u64,
+    /// Length in seconds of each decay period, counted from `last_updated`
+    pub period: u64,
+    /// Points lost per elapsed period
+    pub points: u32,
+}
 
-        campaign.verified = true;
-        campaign.trust_score = trust_score;
+/// How many campaigns a beneficiary may run and start; 0 disables a limit
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CreationLimits {
+    /// Campaigns a beneficiary may have active at once
+    pub max_active: u32,
+    /// Campaigns a beneficiary may create in any 24 hours
+    pub max_per_day: u32,
+}
 
-        env.storage().persistent().set(&DataKey::Campaign(campaign_id), &campaign);
-        Ok(())
-    }
+/// Points each risk signal adds to a campaign's risk score at full strength
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct RiskWeights {
+    /// Beneficiary first seen less than `NEW_ACCOUNT_PERIOD` ago
+    pub new_beneficiary: u32,
+    /// Beneficiary below verification level 2
+    pub unverified: u32,
+    /// Goal above the beneficiary's disbursement limit
+    pub goal_over_cap: u32,
+    /// Title already used by another campaign
+    pub duplicate_title: u32,
+    /// Share of the campaign raised from the beneficiary itself
+    pub self_donations: u32,
+    /// Reports filed against the campaign
+    pub reports: u32,
+}
 
-    /// Process a donation
-    pub fn donate(
-        env: Env,
-        campaign_id: BytesN<32>,
-        donor: Address,
-        amount: u64,
-        anonymous: bool,
-        mint_nft: bool,
-    ) -> Result<BytesN<32>, soroban_sdk::Error> {
-        // Validate campaign exists and is active
-        let mut campaign: Campaign = env.storage().persistent().get(&DataKey::Campaign(campaign_id.clone()))
-            .ok_or(soroban_sdk::Error::from_contract_error(SaviaError::CampaignNotFound as u32))?;
+/// Platform-wide running totals, also used for the per-category and
+/// per-period breakdowns. Periods do not track `active_campaigns`, and
+/// neither breakdown tracks `total_nfts`. Amounts only count donations in
+/// the platform token; `TokenStats` has the totals for every token.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct PlatformStats {
+    pub total_campaigns: u64,
+    pub active_campaigns: u64,
+    pub total_donations: u64,
+    pub total_fees_collected: i128,
+    /// Badges minted and not burned
+    pub total_nfts: u64,
+    /// Donations net of fees
+    pub total_raised: i128,
+}
 
-        let current_time = env.ledger().timestamp();
-        if current_time > campaign.end_time {
-            return Err(soroban_sdk::Error::from_contract_error(SaviaError::CampaignEnded as u32));
-        }
+/// Running totals for one campaign
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct CampaignStats {
+    pub donation_count: u32,
+    pub unique_donors: u32,
+    /// Largest single donation, net of fees
+    pub largest_donation: i128,
+    pub total_fees: i128,
+}
 
-        if amount == 0 {
-            return Err(soroban_sdk::Error::from_contract_error(SaviaError::InvalidAmount as u32));
-        }
+/// A campaign's funding progress at the current ledger time
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CampaignProgress {
+    /// Raised over goal in basis points, saturating at 50000
+    pub percent_funded: u32,
+    /// 0 once ended; at `end_time` itself donations are still accepted with
+    /// 0 remaining. `u64::MAX` for perpetual campaigns.
+    pub seconds_remaining: u64,
+    pub is_ended: bool,
+    pub is_goal_met: bool,
+    pub donor_count: u32,
+}
 
-        // Get platform fee
-        let platform_fee_rate: u64 = env.storage().instance().get(&DataKey::PlatformFee).unwrap_or(200);
-        let platform_fee = (amount * platform_fee_rate) / 10000;
-        let net_amount = amount - platform_fee;
+/// Core contract configuration and record counters
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Config {
+    pub admin: Address,
+    pub token: Address,
+    pub platform_fee: u64,
+    pub campaign_count: u64,
+    pub donation_count: u64,
+    pub nft_count: u64,
+    pub disbursement_count: u64,
+}
 
-        // Get and increment donation counter
-        let counter: u64 = env.storage().instance().get(&DataKey::DonationCounter).unwrap_or(0);
-        let new_counter = counter + 1;
-        env.storage().instance().set(&DataKey::DonationCounter, &new_counter);
+/// Resume point for a listing over a chunked index. `generation` must match
+/// the index's current generation, which changes whenever it is compacted.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Cursor {
+    pub page: u32,
+    pub offset: u32,
+    pub generation: u32,
+}
 
-        // Generate donation ID
-        let mut hash_input = Bytes::new(&env);
-        
-        // Convert to bytes properly
-        let campaign_bytes = Bytes::from_slice(&env, campaign_id.to_array().as_slice());
-        let donor_bytes = donor.clone().to_xdr(&env);
+/// Id indexes stored as fixed-size chunks
+#[derive(Clone)]
+#[contracttype]
+pub enum IndexKey {
+    ActiveCampaigns,
+    AllCampaigns,
+    CampaignDonations(BytesN<32>),
+    OwnerNfts(Address),
+    DonorRecurring(Address),
+    DonorDonations(Address),
+    AllNfts,
+    /// Donations made on a day, `timestamp / DAY`
+    DonationsByDay(u64),
+    /// Campaigns ending on a day, `end_time / DAY`
+    CampaignsEndingOn(u64),
+}
+
+/// Contract-wide settings and counters, plus per-address referral stats,
+/// blacklist entries, donation refs, the token allowlist and the stats
+/// breakdowns kept in persistent storage.
+/// Nested so `DataKey` stays within the contracttype
+/// limit of 50 variants.
+#[derive(Clone)]
+#[contracttype]
+pub enum ConfigKey {
+    Ttl,
+    Paused,
+    Version,
+    SuspensionPenalty,
+    TrustDecay,
+    MinCreatorLevel,
+    RecurringCounter,
+    BurnedNfts,
+    VerifiedFeeWaiver,
+    FeeRecipients,
+    FeeShare(Address),
+    UnclaimedFeeShares,
+    ReferralStats(Address),
+    Blacklist(Address),
+    Approvers,
+    ApprovalThreshold,
+    DisbursementValidity,
+    TotalRaised,
+    CategoryStats(Category),
+    PeriodStats(u64),
+    DonationRef(Address, BytesN<32>),
+    AllowedToken(Address),
+    TokenStats(Address),
+    TokenFees(Address),
+    CreationLimits,
+    CreationExempt(Address),
+    /// Creation times within the last 24 hours
+    RecentCreations(Address),
+    /// Campaigns that most recently met their goal, newest first
+    RecentlyFunded,
+    Profile(Address),
+    UnverifiedRaiseCap,
+    ArchiveGracePeriod,
+    MetadataBaseUri,
+    DisputeThreshold,
+    DisputeCounter,
+    Bundle(BytesN<32>),
+    BundleCounter,
+    AmbassadorThresholds,
+}
+
+/// Running totals for donations made in one token
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct TokenStats {
+    pub total_donations: u64,
+    /// Donations net of fees
+    pub total_raised: i128,
+    pub total_fees_collected: i128,
+}
+
+/// Donations an address has referred
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct ReferralStats {
+    pub referred_count: u32,
+    /// Net amount of the referred donations
+    pub referred_volume: i128,
+    /// Trust score points granted for referrals so far
+    pub trust_bonus: u32,
+}
+
+/// What a registered fundraiser has raised for a campaign
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct FundraiserStats {
+    pub registered_at: u64,
+    pub donation_count: u32,
+    /// Net amount of the donations attributed to the fundraiser
+    pub raised: i128,
+    /// Trust score points granted for fundraising so far
+    pub trust_bonus: u32,
+    /// The Ambassador badge for the campaign has been minted
+    pub ambassador: bool,
+}
+
+/// What a fundraiser must raise for a campaign to earn its Ambassador
+/// badge; both must be met
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct AmbassadorThresholds {
+    /// Net amount raised
+    pub min_raised: i128,
+    /// Donations attributed
+    pub min_donations: u32,
+}
+
+/// Per-campaign records kept alongside the campaign, the records of its
+/// disbursement votes and approvals, its recurring donations, its match pool, its
+/// leaderboard, its progress updates, its fee override and its donor
+/// whitelist. Nested so `DataKey` stays within the
+/// contracttype limit of 50 variants.
+#[derive(Clone)]
+#[contracttype]
+pub enum CampaignKey {
+    Stats(BytesN<32>),
+    Milestones(BytesN<32>),
+    MilestoneCommitted(BytesN<32>, u32),
+    VotingWindow(BytesN<32>),
+    VoteTally(BytesN<32>),
+    Voted(BytesN<32>, Address),
+    Recurring(BytesN<32>),
+    Match(BytesN<32>),
+    Leaderboard(BytesN<32>),
+    PublicDonorTotal(BytesN<32>, Address),
+    Updates(BytesN<32>),
+    Fee(BytesN<32>),
+    Whitelist(BytesN<32>),
+    WhitelistEnabled(BytesN<32>),
+    Approvals(BytesN<32>),
+    Pledged(BytesN<32>),
+    /// `(closed_at, active index generation)` of an early close
+    Closure(BytesN<32>),
+    ReopenRequested(BytesN<32>),
+    /// When the campaign was closed, finalized or suspended
+    EndedAt(BytesN<32>),
+    /// Digest of the donations deleted when the campaign was archived
+    Archived(BytesN<32>),
+    Dispute(BytesN<32>),
+    /// Ids of the campaign's open disputes
+    OpenDisputes(BytesN<32>),
+    /// The reporter's open dispute against the campaign
+    OpenDispute(BytesN<32>, Address),
+    /// Donations and payouts are on hold pending dispute review
+    DisputeHold(BytesN<32>),
+    /// Present once the fundraiser is registered for the campaign
+    FundraiserStats(BytesN<32>, Address),
+    /// Donations and new disbursement requests are blocked by the admin
+    Frozen(BytesN<32>),
+}
+
+/// Running donor vote on a disbursement of a campaign with donor voting
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct VoteTally {
+    pub deadline: u64,
+    pub approve_weight: i128,
+    pub reject_weight: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct IndexMeta {
+    pub chunks: u32,
+    pub generation: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum DisbursementStatus {
+    Pending,
+    Approved,
+    Executed,
+    Rejected,
+    Cancelled,
+    /// Not executed before `expires_at`
+    Expired,
+}
+
+// ========== LEGACY LAYOUTS ==========
+
+// Records as stored while amounts were u64; `migrate_amounts` rewrites
+// them in the current layout.
+
+#[contracttype(export = false)]
+pub struct LegacyCampaign {
+    pub id: BytesN<32>,
+    pub title: String,
+    pub description: String,
+    pub beneficiary: Address,
+    pub goal_amount: u64,
+    pub current_amount: u64,
+    pub disbursed_amount: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub verified: bool,
+    pub trust_score: u32,
+    pub category: String,
+    pub location: String,
+    pub at_risk: bool,
+    pub at_risk_acknowledged_at: Option<u64>,
+    pub perpetual: bool,
+    pub active: bool,
+    pub funding_mode: FundingMode,
+    pub possible_duplicate: bool,
+    pub endgame_locked: bool,
+}
+
+impl LegacyCampaign {
+    fn upgrade(self, token: Address) -> Campaign {
+        Campaign {
+            id: self.id,
+            title: self.title,
+            description: self.description,
+            beneficiary: self.beneficiary,
+            goal_amount: self.goal_amount as i128,
+            current_amount: self.current_amount as i128,
+            disbursed_amount: self.disbursed_amount as i128,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            verified: self.verified,
+            trust_score: self.trust_score,
+            category: Category::from_name(&self.category),
+            location: self.location,
+            at_risk: self.at_risk,
+            at_risk_acknowledged_at: self.at_risk_acknowledged_at,
+            perpetual: self.perpetual,
+            active: self.active,
+            funding_mode: self.funding_mode,
+            possible_duplicate: self.possible_duplicate,
+            endgame_locked: self.endgame_locked,
+            goal_reached: false,
+            legacy_category: Some(self.category),
+            min_donation: 0,
+            max_donation_per_donor: 0,
+            overfunding_limit_bps: 0,
+            suspended: false,
+            goal_reached_at: None,
+            close_on_goal: false,
+            metadata_uri: None,
+            token,
+            outcome: match (self.active, self.current_amount >= self.goal_amount) {
+                (_, true) => CampaignOutcome::Successful,
+                (true, false) => CampaignOutcome::Ongoing,
+                (false, false) => CampaignOutcome::Unsuccessful,
+            },
+        }
+    }
+}
+
+#[contracttype(export = false)]
+pub struct LegacyDonation {
+    pub id: BytesN<32>,
+    pub campaign_id: BytesN<32>,
+    pub donor: Address,
+    pub amount: u64,
+    pub timestamp: u64,
+    pub nft_minted: bool,
+    pub privacy: Privacy,
+    pub refunded: bool,
+}
+
+impl LegacyDonation {
+    fn upgrade(self) -> Donation {
+        let message = String::from_str(self.id.env(), "");
+        Donation {
+            id: self.id,
+            campaign_id: self.campaign_id,
+            donor: self.donor,
+            amount: self.amount as i128,
+            timestamp: self.timestamp,
+            nft_minted: self.nft_minted,
+            privacy: self.privacy,
+            refunded: self.refunded,
+            message,
+            badge_recipient: None,
+            referrer: None,
+            platform_fee: 0,
+        }
+    }
+}
+
+#[contracttype(export = false)]
+pub struct LegacyTrustScore {
+    pub entity: Address,
+    pub score: u32,
+    pub verification_level: u32,
+    pub donation_count: u32,
+    pub total_donated: u64,
+    pub campaigns_created: u32,
+    pub last_updated: u64,
+}
+
+impl LegacyTrustScore {
+    fn upgrade(self) -> TrustScore {
+        let evidence_hash = BytesN::from_array(self.entity.env(), &[0; 32]);
+        TrustScore {
+            entity: self.entity,
+            score: self.score,
+            verification_level: self.verification_level,
+            donation_count: self.donation_count,
+            total_donated: self.total_donated as i128,
+            campaigns_created: self.campaigns_created,
+            last_updated: self.last_updated,
+            adjustment: 0,
+            evidence_hash,
+            current_streak_months: 0,
+            longest_streak: 0,
+            streak_started_at: 0,
+            last_donation_at: 0,
+            decay_applied: 0,
+            campaigns_completed: 0,
+            campaigns_failed: 0,
+        }
+    }
+}
+
+#[contracttype(export = false)]
+pub struct LegacyDisbursement {
+    pub id: BytesN<32>,
+    pub campaign_id: BytesN<32>,
+    pub recipient: Address,
+    pub amount: u64,
+    pub milestone: String,
+    pub status: DisbursementStatus,
+    pub created_at: u64,
+    pub executed_at: Option<u64>,
+    pub execution_nonce: u32,
+    pub rejection_reason: Option<String>,
+}
+
+impl LegacyDisbursement {
+    fn upgrade(self) -> Disbursement {
+        Disbursement {
+            id: self.id,
+            campaign_id: self.campaign_id,
+            recipient: self.recipient,
+            amount: self.amount as i128,
+            milestone: self.milestone,
+            status: self.status,
+            created_at: self.created_at,
+            executed_at: self.executed_at,
+            execution_nonce: self.execution_nonce,
+            rejection_reason: self.rejection_reason,
+            milestone_index: 0,
+            expires_at: self.created_at.saturating_add(DEFAULT_DISBURSEMENT_VALIDITY),
+        }
+    }
+}
+
+#[contracttype(export = false)]
+pub struct LegacyChallenge {
+    pub id: BytesN<32>,
+    pub challenger: Address,
+    pub campaign_id: BytesN<32>,
+    pub amount: u64,
+    pub counterpart: Address,
+    pub min_counterpart_amount: u64,
+    pub deadline: u64,
+    pub status: ChallengeStatus,
+}
+
+impl LegacyChallenge {
+    fn upgrade(self) -> Challenge {
+        Challenge {
+            id: self.id,
+            challenger: self.challenger,
+            campaign_id: self.campaign_id,
+            amount: self.amount as i128,
+            counterpart: self.counterpart,
+            min_counterpart_amount: self.min_counterpart_amount as i128,
+            deadline: self.deadline,
+            status: self.status,
+        }
+    }
+}
+
+// ========== STORAGE KEYS ==========
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Campaign(BytesN<32>),
+    Donation(BytesN<32>),
+    TrustScore(Address),
+    NFTBadge(BytesN<32>),
+    Disbursement(BytesN<32>),
+    Admin,
+    PendingAdmin,
+    Token,
+    PlatformFee,
+    AccruedFees,
+    RiskWeights,
+    RiskThreshold,
+    ReviewQueue,
+    FirstSeen(Address),
+    TitleHash(BytesN<32>),
+    CampaignReports(BytesN<32>),
+    Reported(BytesN<32>, Address),
+    TotalFeesCollected,
+    FeeHolidays,
+    DisbursementLimits,
+    PreMigrationDigest,
+    Challenge(BytesN<32>),
+    OpenChallenges(BytesN<32>, Address),
+    ChallengeCounter,
+    EventVerbosity,
+    CampaignsByBeneficiary(Address),
+    CampaignHook(BytesN<32>),
+    IndexMeta(IndexKey),
+    IndexChunk(IndexKey, u32),
+    OwnerNftCount(Address),
+    TransferableBadge(String),
+    BadgeMetadata(String),
+    DonorNetTotal(BytesN<32>, Address),
+    DonorBadge(BytesN<32>, Address),
+    KycDisclosureThreshold,
+    DonorCampaignTotal(BytesN<32>, Address),
+    InitializedAt,
+    BootstrapWindow,
+    EndgameWindow,
+    Config(ConfigKey),
+    Categories,
+    CategoryLabel(Category, Symbol),
+    CategoryLangs(Category),
+    CampaignsByCategory(Category),
+    CampaignData(CampaignKey),
+    CampaignCounter,
+    ActiveCampaignCounter,
+    DonationCounter,
+    NFTCounter,
+    DisbursementCounter,
+}
+
+// ========== ERROR CODES ==========
+
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum SaviaError {
+    InvalidFee = 1,
+    InvalidGoal = 2,
+    InvalidDuration = 3,
+    CampaignNotFound = 4,
+    CampaignEnded = 5,
+    InvalidAmount = 6,
+    ScoreExists = 7,
+    InsufficientFunds = 8,
+    DisbursementNotFound = 9,
+    NotApproved = 10,
+    Unauthorized = 11,
+    HolidayOverlap = 12,
+    DonationNotFound = 14,
+    DisbursementLimitExceeded = 15,
+    DisbursementNotPending = 16,
+    InvalidInput = 17,
+    NotInitialized = 18,
+    StaleNonce = 19,
+    CampaignInactive = 20,
+    RefundNotAllowed = 21,
+    AlreadyRefunded = 22,
+    ChallengeNotFound = 23,
+    ChallengeNotOpen = 24,
+    BootstrapClosed = 25,
+    NoPendingAdmin = 26,
+    AnonymityNotAllowed = 27,
+    StaleCursor = 28,
+    AlreadyReported = 29,
+    EndgameLocked = 30,
+    Overflow = 32,
+    CampaignNotEnded = 33,
+    NonTransferable = 34,
+    MilestoneNotFound = 35,
+    MilestoneAllocationExceeded = 36,
+    AlreadyVoted = 37,
+    ContractPaused = 40,
+    DonationCapExceeded = 41,
+    GoalExceeded = 42,
+    GoalNotReached = 43,
+    CampaignSuspended = 44,
+    VerificationRequired = 45,
+    RecurringNotFound = 46,
+    CampaignLocked = 49,
+    NFTNotFound = 50,
+    WrongToken = 51,
+    AddressBlocked = 52,
+    NotWhitelisted = 53,
+    RateLimited = 54,
+    DisbursementExpired = 55,
+    CampaignFrozen = 56,
+}
+
+/// Campaign trust score from which verified campaigns pay no fee, when the
+/// waiver is enabled
+const FEE_WAIVER_MIN_TRUST_SCORE: u32 = 90;
+
+/// Most addresses platform fees may be split between
+const MAX_FEE_RECIPIENTS: u32 = 5;
+
+/// Most disbursement approvers that may be configured
+const MAX_APPROVERS: u32 = 10;
+
+/// Most disbursements a single `approve_disbursements` call may sign
+const MAX_APPROVAL_BATCH: u32 = 25;
+
+/// Maximum number of fee holidays that may be scheduled at once
+const MAX_FEE_HOLIDAYS: u32 = 4;
+
+/// Default time after initialize during which trust scores can be imported
+const DEFAULT_BOOTSTRAP_WINDOW: u64 = 30 * 24 * 60 * 60;
+
+/// Length of a stats period, in seconds
+const STATS_PERIOD: u64 = 30 * 24 * 60 * 60;
+
+/// Width of a donation time index bucket, in seconds
+const DAY: u64 = 24 * 60 * 60;
+
+/// Longest window `get_donations_in_range` and
+/// `get_campaigns_ending_between` cover in one call
+const MAX_RANGE_SECONDS: u64 = 31 * DAY;
+
+/// Campaigns kept in the recently funded list
+const RECENTLY_FUNDED_SIZE: u32 = 20;
+
+/// Most `CampaignProgress::percent_funded` reports, in basis points
+const MAX_PROGRESS_BPS: i128 = 50_000;
+
+/// Default campaigns a beneficiary may have active at once
+const DEFAULT_MAX_ACTIVE_CAMPAIGNS: u32 = 5;
+
+/// Default campaigns a beneficiary may create per 24 hours
+const DEFAULT_MAX_CREATIONS_PER_DAY: u32 = 3;
+
+/// How long after an early close a campaign can still be reopened
+const REOPEN_WINDOW: u64 = 7 * 24 * 60 * 60;
+
+/// Default time a disbursement request stays executable after creation
+const DEFAULT_DISBURSEMENT_VALIDITY: u64 = 30 * 24 * 60 * 60;
+
+/// Default time before `end_time` during which an underfunded campaign
+/// cannot request disbursements
+const DEFAULT_ENDGAME_WINDOW: u64 = 48 * 60 * 60;
+
+/// Default trust decay: 5 points per 30 days once inactive for 90 days
+const DEFAULT_TRUST_DECAY_GRACE: u64 = 90 * 24 * 60 * 60;
+const DEFAULT_TRUST_DECAY_PERIOD: u64 = 30 * 24 * 60 * 60;
+const DEFAULT_TRUST_DECAY_POINTS: u32 = 5;
+
+/// Score that trust decay never goes below
+const NEUTRAL_TRUST_SCORE: u32 = 50;
+
+/// Verification levels: unverified, email, identity, organization
+const MAX_VERIFICATION_LEVEL: u32 = 3;
+
+/// Trust score points a referrer gains per referred donation
+const REFERRAL_TRUST_BONUS: u32 = 1;
+
+/// Most trust score points referrals can earn an address
+const MAX_REFERRAL_TRUST_BONUS: u32 = 10;
+
+/// Trust score points a fundraiser gains per donation they raise
+const FUNDRAISER_TRUST_BONUS: u32 = 1;
+
+/// Most trust score points fundraising for one campaign can earn an address
+const MAX_FUNDRAISER_TRUST_BONUS: u32 = 10;
+
+/// Default net amount a fundraiser must raise for an Ambassador badge
+const DEFAULT_AMBASSADOR_MIN_RAISED: i128 = 10_000;
+
+/// Default donations a fundraiser must raise for an Ambassador badge
+const DEFAULT_AMBASSADOR_MIN_DONATIONS: u32 = 5;
+
+/// Verification level, identity, at which a profile's display name is
+/// marked verified
+const NAME_VERIFICATION_LEVEL: u32 = 2;
+
+/// Default most an unverified campaign may raise
+const DEFAULT_UNVERIFIED_RAISE_CAP: i128 = 100_000;
+
+/// Default time after a campaign ends before it can be archived
+const DEFAULT_ARCHIVE_GRACE_PERIOD: u64 = 90 * DAY;
+
+/// Longest profile display name, in bytes
+const MAX_DISPLAY_NAME_LEN: u32 = 64;
+
+/// Longest profile bio URI or website, in bytes
+const MAX_PROFILE_URI_LEN: u32 = 256;
+
+/// Trust score points added per verification level
+const VERIFICATION_LEVEL_BONUS: u32 = 5;
+
+/// Trust points per finalized campaign that reached its goal
+const CAMPAIGN_COMPLETED_POINTS: i32 = 3;
+
+/// Cap on the points from completed campaigns
+const MAX_CAMPAIGN_COMPLETED_POINTS: i32 = 15;
+
+/// Trust points lost per finalized campaign that ended below
+/// `CAMPAIGN_FAILED_BPS` of its goal
+const CAMPAIGN_FAILED_POINTS: i32 = 2;
+
+/// Share of the goal, in basis points, under which a campaign counts as failed
+const CAMPAIGN_FAILED_BPS: i128 = 2500;
+
+/// A donation streak breaks when donations are further apart than this
+const STREAK_MAX_GAP: u64 = 35 * 24 * 60 * 60;
+
+/// Length of a streak month, in seconds
+const STREAK_MONTH: u64 = 30 * 24 * 60 * 60;
+
+/// Percentage added to the amount component per streak month after the first
+const STREAK_BONUS_PER_MONTH: u64 = 5;
+
+/// Cap on the streak percentage bonus
+const MAX_STREAK_BONUS: u64 = 20;
+
+/// Default trust score points a beneficiary loses when their campaign is
+/// suspended
+const DEFAULT_SUSPENSION_PENALTY: u32 = 20;
+
+/// Ledgers per day at a five second close time
+const DAY_IN_LEDGERS: u32 = 17280;
+
+/// Default remaining TTL, in ledgers, below which a touched record is extended
+const DEFAULT_TTL_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
+
+/// Default TTL, in ledgers, a touched record is extended to
+const DEFAULT_TTL_EXTEND_TO: u32 = 30 * DAY_IN_LEDGERS;
+
+/// Base of the metadata URI badges are minted with, followed by
+/// `/<nft id in hex>`, for tiers the admin has not given their own URI
+const DEFAULT_METADATA_BASE_URI: &str = "https://savia.org/nft/metadata";
+
+/// Longest metadata base URI, in bytes
+const MAX_METADATA_BASE_URI_LEN: u32 = 192;
+
+/// Longest dispute reason URI, in bytes
+const MAX_DISPUTE_URI_LEN: u32 = 256;
+
+/// Open disputes a campaign may have by default before it is held
+const DEFAULT_MAX_OPEN_DISPUTES: u32 = 3;
+
+/// Longest localized category label, in bytes
+const MAX_LABEL_LEN: u32 = 48;
+
+/// Most records accepted by one `migrate_amounts` call
+const MAX_MIGRATION_BATCH: u32 = 25;
+
+/// Most trust score records accepted by one import call
+const MAX_IMPORT_BATCH: u32 = 25;
+
+/// Highest score an imported trust record may carry
+const MAX_IMPORTED_SCORE: u32 = 90;
+
+/// Largest page any listing query returns
+const MAX_PAGE_SIZE: u32 = 50;
+
+/// How long after first being seen an address counts as new for risk scoring
+const NEW_ACCOUNT_PERIOD: u64 = 30 * 24 * 60 * 60;
+
+/// Reports that make the report signal count in full
+const MAX_COUNTED_REPORTS: u32 = 5;
+
+/// Default risk score at or above which new campaigns are queued for review
+const DEFAULT_RISK_THRESHOLD: u32 = 60;
+
+/// Ids stored per chunk of a chunked index
+const INDEX_CHUNK_SIZE: u32 = 25;
+
+/// Version of this contract code, recorded on initialize. A new wasm
+/// installed through `upgrade` bumps it and writes it back on migration.
+const CONTRACT_VERSION: u32 = 1;
+
+/// Donors shown on a campaign's leaderboard
+const LEADERBOARD_SIZE: u32 = 10;
+
+/// Most addresses a campaign's donor whitelist may hold
+const MAX_WHITELIST_SIZE: u32 = 500;
+
+/// Most campaigns a single `donate_batch` call may give to
+const MAX_DONATION_BATCH: u32 = 10;
+
+/// Most campaigns a bundle may split donations between
+const MAX_BUNDLE_SIZE: u32 = 10;
+
+/// Longest bundle name, in bytes
+const MAX_BUNDLE_NAME_LEN: u32 = 64;
+
+/// Most progress updates a campaign may post
+const MAX_CAMPAIGN_UPDATES: u32 = 50;
+
+/// Most runs a recurring donation may be scheduled for
+const MAX_RECURRING_EXECUTIONS: u32 = 120;
+
+/// Longest donation message accepted, in bytes
+const MAX_DONATION_MESSAGE_LEN: u32 = 280;
+
+/// Version byte leading every donation attestation payload
+const ATTESTATION_VERSION: u8 = 2;
+
+/// Version byte leading every hashed donation receipt
+const RECEIPT_VERSION: u8 = 1;
+
+/// Version byte leading the canonical platform state serialization
+const STATE_DIGEST_VERSION: u8 = 3;
+
+// ========== EVENT TOPICS ==========
+
+/// Event topic names. Indexers filter on these strings, so they are frozen by
+/// `test_event_topics_frozen`; renaming one is a breaking change.
+pub mod topics {
+    // Subjects (first topic)
+    pub const CAMPAIGN: &str = "campaign";
+    pub const DONATION: &str = "donation";
+    pub const DISBURSEMENT: &str = "disbursement";
+    pub const NFT: &str = "nft";
+    pub const TRUST: &str = "trust";
+    pub const PROFILE: &str = "profile";
+    pub const CONFIG: &str = "config";
+    pub const ADMIN: &str = "admin";
+    pub const CHALLENGE: &str = "challenge";
+    pub const FEE: &str = "fee";
+    pub const REFERRAL: &str = "referral";
+    pub const DISPUTE: &str = "dispute";
+    pub const BUNDLE: &str = "bundle";
+    pub const FUNDRAISER: &str = "fundraiser";
+
+    // Actions (second topic)
+    pub const INITIALIZED: &str = "initialized";
+    pub const CREATED: &str = "created";
+    pub const VERIFIED: &str = "verified";
+    pub const QUEUED: &str = "queued";
+    pub const REPORTED: &str = "reported";
+    pub const RISK: &str = "risk";
+    pub const MADE: &str = "made";
+    pub const MINTED: &str = "minted";
+    pub const TRANSFERRED: &str = "transferred";
+    pub const UPGRADED: &str = "upgraded";
+    pub const MILESTONES: &str = "milestones";
+    pub const VOTING: &str = "voting";
+    pub const VOTED: &str = "voted";
+    pub const TRANSFERABLE: &str = "transferable";
+    pub const METADATA: &str = "metadata";
+    pub const EXECUTED: &str = "executed";
+    pub const UPDATED: &str = "updated";
+    pub const SCHEDULED: &str = "scheduled";
+    pub const HOLIDAY: &str = "holiday";
+    pub const LIMITS: &str = "limits";
+    pub const MATCH: &str = "match";
+    pub const MATCHED: &str = "matched";
+    pub const GOAL_MET: &str = "goal_met";
+    pub const FUNDED: &str = "funded";
+    pub const CLOSE_ON_GOAL: &str = "close_on_goal";
+    pub const OVERFUNDING: &str = "overfunding";
+    pub const APPROVED: &str = "approved";
+    pub const REJECTED: &str = "rejected";
+    pub const MIGRATED: &str = "migrated";
+    pub const WITHDRAWN: &str = "withdrawn";
+    pub const VERBOSITY: &str = "verbosity";
+    pub const KYC_THRESHOLD: &str = "kyc_threshold";
+    pub const IMPORTED: &str = "imported";
+    pub const ADJUSTED: &str = "adjusted";
+    pub const PENALTY: &str = "penalty";
+    pub const DECAY: &str = "decay";
+    pub const DECAYED: &str = "decayed";
+    pub const MIN_LEVEL: &str = "min_level";
+    pub const BOOTSTRAP: &str = "bootstrap";
+    pub const PROPOSED: &str = "proposed";
+    pub const PAUSED: &str = "paused";
+    pub const UNPAUSED: &str = "unpaused";
+    pub const ACCEPTED: &str = "accepted";
+    pub const CANCELLED: &str = "cancelled";
+    pub const CLOSED: &str = "closed";
+    pub const SUSPENDED: &str = "suspended";
+    pub const FINALIZED: &str = "finalized";
+    pub const COMPACTED: &str = "compacted";
+    pub const REFUNDED: &str = "refunded";
+    pub const TRIGGERED: &str = "triggered";
+    pub const EXPIRED: &str = "expired";
+    pub const AT_RISK: &str = "at_risk";
+    pub const ENDGAME_LOCKED: &str = "endgame_locked";
+    pub const ENDGAME: &str = "endgame";
+    pub const TTL: &str = "ttl";
+    pub const CATEGORY_ADDED: &str = "category_added";
+    pub const CATEGORY_REMOVED: &str = "category_removed";
+    pub const LABEL: &str = "label";
+    pub const HOOK_REGISTERED: &str = "hook_registered";
+    pub const HOOK_REMOVED: &str = "hook_removed";
+    pub const HOOK_FAILED: &str = "hook_failed";
+    pub const ACKNOWLEDGED: &str = "acknowledged";
+    pub const POSTED: &str = "posted";
+    pub const BURNED: &str = "burned";
+    pub const OVERRIDE: &str = "override";
+    pub const WAIVER: &str = "waiver";
+    pub const RECIPIENTS: &str = "recipients";
+    pub const CLAIMED: &str = "claimed";
+    pub const CREDITED: &str = "credited";
+    pub const WHITELIST: &str = "whitelist";
+    pub const BLACKLIST: &str = "blacklist";
+    pub const APPROVERS: &str = "approvers";
+    pub const SIGNED: &str = "signed";
+    pub const VALIDITY: &str = "validity";
+    pub const TOKEN: &str = "token";
+    pub const REOPEN_REQUESTED: &str = "reopen_requested";
+    pub const REOPENED: &str = "reopened";
+    pub const CREATION_LIMITS: &str = "creation_limits";
+    pub const EXEMPT: &str = "exempt";
+    pub const UNVERIFIED_CAP: &str = "unverified_cap";
+    pub const ARCHIVED: &str = "archived";
+    pub const ARCHIVE_GRACE: &str = "archive_grace";
+    pub const METADATA_BASE: &str = "metadata_base";
+    pub const FILED: &str = "filed";
+    pub const RESOLVED: &str = "resolved";
+    pub const HELD: &str = "held";
+    pub const RELEASED: &str = "released";
+    pub const DISPUTE_THRESHOLD: &str = "dispute_threshold";
+    pub const DONATED: &str = "donated";
+    pub const REGISTERED: &str = "registered";
+    pub const AMBASSADOR: &str = "ambassador";
+    pub const FROZEN: &str = "frozen";
+    pub const UNFROZEN: &str = "unfrozen";
+}
+
+/// Lowercase hex encoding of a 32-byte id
+fn hex_encode(id: &BytesN<32>) -> [u8; 64] {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = [0u8; 64];
+    for (i, byte) in id.to_array().iter().enumerate() {
+        out[2 * i] = DIGITS[(byte >> 4) as usize];
+        out[2 * i + 1] = DIGITS[(byte & 0x0f) as usize];
+    }
+    out
+}
+
+/// Publish an event under a `(subject, action)` topic pair
+fn publish<D: IntoVal<Env, Val>>(env: &Env, subject: &str, action: &str, data: D) {
+    env.events()
+        .publish((Symbol::new(env, subject), Symbol::new(env, action)), data);
+}
+
+/// Publish an event under `(subject, action, about)` so indexers can filter
+/// by the campaign or party it concerns
+fn publish_about<A, D>(env: &Env, subject: &str, action: &str, about: A, data: D)
+where
+    A: IntoVal<Env, Val>,
+    D: IntoVal<Env, Val>,
+{
+    env.events()
+        .publish((Symbol::new(env, subject), Symbol::new(env, action), about), data);
+}
+
+/// Publish an event under `(subject, action, about, party)`, typically a
+/// campaign id followed by the donor, owner or disbursement it concerns
+fn publish_between<A, P, D>(env: &Env, subject: &str, action: &str, about: A, party: P, data: D)
+where
+    A: IntoVal<Env, Val>,
+    P: IntoVal<Env, Val>,
+    D: IntoVal<Env, Val>,
+{
+    env.events()
+        .publish((Symbol::new(env, subject), Symbol::new(env, action), about, party), data);
+}
+
+// ========== MAIN CONTRACT ==========
+
+#[contract]
+pub struct SaviaContract;
+
+#[contractimpl]
+impl SaviaContract {
+    
+    /// Initialize the contract with an admin, the token donations are made in
+    /// and the platform fee. Only allowed once.
+    pub fn initialize(env: Env, admin: Address, token: Address, platform_fee: u64) -> Result<(), SaviaError> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(SaviaError::Unauthorized);
+        }
+        if platform_fee > 1000 {
+            return Err(SaviaError::InvalidFee);
+        }
         
-        hash_input.append(&campaign_bytes);
-        hash_input.append(&donor_bytes);
-        hash_input.append(&Bytes::from_slice(&env, &amount.to_be_bytes()));
-        hash_input.append(&Bytes::from_slice(&env, &current_time.to_be_bytes()));
-        hash_input.append(&Bytes::from_slice(&env, &new_counter.to_be_bytes()));
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Token, &token);
+        env.storage().instance().set(&DataKey::PlatformFee, &platform_fee);
+        env.storage().instance().set(&DataKey::AccruedFees, &0i128);
+        env.storage().instance().set(&DataKey::CampaignCounter, &0u64);
+        env.storage().instance().set(&DataKey::DonationCounter, &0u64);
+        env.storage().instance().set(&DataKey::NFTCounter, &0u64);
+        env.storage().instance().set(&DataKey::DisbursementCounter, &0u64);
+        env.storage().instance().set(&DataKey::InitializedAt, &env.ledger().timestamp());
+        env.storage().instance().set(&DataKey::Config(ConfigKey::Version), &CONTRACT_VERSION);
+        let (threshold, extend_to) = Self::get_ttl_config(env.clone());
+        env.storage().instance().extend_ttl(threshold, extend_to);
+
+        publish(&env, topics::CONFIG, topics::INITIALIZED, platform_fee);
         
-        let donation_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+        Ok(())
+    }
+
+    /// Replace the contract code, keeping all storage (admin function).
+    /// The wasm must already be uploaded to the network.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+
+        publish(&env, topics::ADMIN, topics::UPGRADED, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Whether `initialize` has run. Every state-changing entrypoint fails
+    /// with `NotInitialized` until it has.
+    pub fn is_initialized(env: Env) -> bool {
+        env.storage().instance().has(&DataKey::Admin)
+    }
+
+    /// Version of the code the stored state was last written by, or 0
+    /// before initialize
+    pub fn version(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::Version)).unwrap_or(0)
+    }
+
+    /// Get the current admin, if the contract is initialized
+    pub fn get_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Admin)
+    }
+
+    /// Get the core configuration and record counters
+    pub fn get_config(env: Env) -> Result<Config, SaviaError> {
+        let storage = env.storage().instance();
+        Ok(Config {
+            admin: storage.get(&DataKey::Admin).ok_or(SaviaError::NotInitialized)?,
+            token: storage.get(&DataKey::Token).ok_or(SaviaError::NotInitialized)?,
+            platform_fee: Self::get_platform_fee(env.clone())?,
+            campaign_count: storage.get(&DataKey::CampaignCounter).unwrap_or(0),
+            donation_count: storage.get(&DataKey::DonationCounter).unwrap_or(0),
+            nft_count: storage.get(&DataKey::NFTCounter).unwrap_or(0),
+            disbursement_count: storage.get(&DataKey::DisbursementCounter).unwrap_or(0),
+        })
+    }
+
+    /// Stop new campaigns, donations, challenges and disbursements in an
+    /// emergency (admin function). Refunds and reads keep working.
+    pub fn pause(env: Env) -> Result<(), SaviaError> {
+        let admin = Self::require_admin(&env)?;
+
+        env.storage().instance().set(&DataKey::Config(ConfigKey::Paused), &true);
+
+        publish(&env, topics::ADMIN, topics::PAUSED, admin);
+        Ok(())
+    }
+
+    /// Lift a pause (admin function)
+    pub fn unpause(env: Env) -> Result<(), SaviaError> {
+        let admin = Self::require_admin(&env)?;
+
+        env.storage().instance().remove(&DataKey::Config(ConfigKey::Paused));
+
+        publish(&env, topics::ADMIN, topics::UNPAUSED, admin);
+        Ok(())
+    }
+
+    /// Whether the contract is paused
+    pub fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::Paused)).unwrap_or(false)
+    }
+
+    /// Block or unblock an address from creating campaigns, donating and
+    /// receiving disbursements, including on campaigns it already runs
+    /// (admin function). Blocked calls fail with `AddressBlocked`.
+    pub fn set_blacklist(env: Env, addr: Address, blocked: bool) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let key = DataKey::Config(ConfigKey::Blacklist(addr.clone()));
+        if blocked {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        publish(&env, topics::ADMIN, topics::BLACKLIST, (addr, blocked));
+        Ok(())
+    }
+
+    /// Whether `addr` is blacklisted
+    pub fn is_blacklisted(env: Env, addr: Address) -> bool {
+        env.storage().persistent().get(&DataKey::Config(ConfigKey::Blacklist(addr))).unwrap_or(false)
+    }
+
+    /// Propose a new admin (admin function). The current admin keeps its
+    /// rights until the new admin accepts.
+    pub fn transfer_admin(env: Env, new_admin: Address) -> Result<(), SaviaError> {
+        let admin = Self::require_admin(&env)?;
+
+        env.storage().instance().set(&DataKey::PendingAdmin, &new_admin);
+
+        publish(&env, topics::ADMIN, topics::PROPOSED, (admin, new_admin));
+        Ok(())
+    }
+
+    /// Accept a pending admin transfer (pending admin function)
+    pub fn accept_admin(env: Env) -> Result<(), SaviaError> {
+        Self::require_initialized(&env)?;
+        let pending: Address = env.storage().instance().get(&DataKey::PendingAdmin)
+            .ok_or(SaviaError::NoPendingAdmin)?;
+        pending.require_auth();
+
+        let previous: Option<Address> = env.storage().instance().get(&DataKey::Admin);
+        env.storage().instance().set(&DataKey::Admin, &pending);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        publish(&env, topics::ADMIN, topics::ACCEPTED, (previous, pending));
+        Ok(())
+    }
+
+    /// Withdraw a pending admin transfer (admin function)
+    pub fn cancel_admin_transfer(env: Env) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let pending: Address = env.storage().instance().get(&DataKey::PendingAdmin)
+            .ok_or(SaviaError::NoPendingAdmin)?;
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        publish(&env, topics::ADMIN, topics::CANCELLED, pending);
+        Ok(())
+    }
+
+    /// Run storage migrations after a contract upgrade (admin function).
+    /// The state digest is recorded first so audits can compare before and after.
+    pub fn migrate(env: Env) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let digest = Self::export_state_digest(env.clone());
+        env.storage().instance().set(&DataKey::PreMigrationDigest, &digest);
+
+        // Rewrite amounts stored before the move to i128
+        for key in [DataKey::AccruedFees, DataKey::TotalFeesCollected, DataKey::KycDisclosureThreshold] {
+            if let Some(amount) = Self::stored_amount(&env, &key) {
+                env.storage().instance().set(&key, &amount);
+            }
+        }
+        if let Some(limits) = Self::stored_disbursement_limits(&env) {
+            env.storage().instance().set(&DataKey::DisbursementLimits, &limits);
+        }
+
+        publish(&env, topics::CONFIG, topics::MIGRATED, digest);
+        Ok(())
+    }
+
+    /// Rewrite records stored with u64 amounts in their i128 layout (admin
+    /// function). Accepts campaign, donation, disbursement, challenge, trust
+    /// score and donor total keys; records already migrated are skipped.
+    /// Returns the number of records rewritten.
+    pub fn migrate_amounts(env: Env, keys: Vec<DataKey>) -> Result<u32, SaviaError> {
+        Self::require_admin(&env)?;
+
+        if keys.len() > MAX_MIGRATION_BATCH {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let token: Address = env.storage().instance().get(&DataKey::Token).ok_or(SaviaError::NotInitialized)?;
+        let mut migrated = 0;
+        for key in keys.iter() {
+            let upgraded = match key {
+                DataKey::Campaign(_) => Self::upgrade_record(&env, &key, "goal_amount", |legacy: LegacyCampaign| legacy.upgrade(token.clone())),
+                DataKey::Donation(_) => Self::upgrade_record(&env, &key, "amount", LegacyDonation::upgrade),
+                DataKey::Disbursement(_) => Self::upgrade_record(&env, &key, "amount", LegacyDisbursement::upgrade),
+                DataKey::Challenge(_) => Self::upgrade_record(&env, &key, "amount", LegacyChallenge::upgrade),
+                DataKey::TrustScore(_) => Self::upgrade_record(&env, &key, "total_donated", LegacyTrustScore::upgrade),
+                DataKey::DonorCampaignTotal(_, _) => Self::upgrade_record(&env, &key, "", |total: u64| total as i128),
+                _ => return Err(SaviaError::InvalidInput),
+            };
+            if upgraded {
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    }
+
+    /// sha256 over a canonical serialization of the platform configuration
+    /// and counters. Layout (version 3), integers big-endian with amounts as
+    /// i128, optional values prefixed with a presence byte: version byte,
+    /// admin XDR, token XDR, platform fee, accrued fees, fee holidays (count,
+    /// then start, end, fee_bps, used byte each), disbursement limits
+    /// (presence, count, then level, amount each), event verbosity byte, KYC
+    /// disclosure threshold (presence, amount), then the campaign, donation,
+    /// NFT and disbursement counters.
+    pub fn export_state_digest(env: Env) -> BytesN<32> {
+        let storage = env.storage().instance();
+        let mut data = Bytes::new(&env);
+
+        data.push_back(STATE_DIGEST_VERSION);
+
+        for key in [DataKey::Admin, DataKey::Token] {
+            match storage.get::<_, Address>(&key) {
+                Some(address) => {
+                    data.push_back(1);
+                    data.append(&address.to_xdr(&env));
+                }
+                None => data.push_back(0),
+            }
+        }
+
+        let platform_fee: u64 = storage.get(&DataKey::PlatformFee).unwrap_or(0);
+        data.append(&Bytes::from_slice(&env, &platform_fee.to_be_bytes()));
+        let accrued_fees = Self::stored_amount(&env, &DataKey::AccruedFees).unwrap_or(0);
+        data.append(&Bytes::from_slice(&env, &accrued_fees.to_be_bytes()));
+
+        let holidays = Self::get_fee_holidays(env.clone());
+        data.append(&Bytes::from_slice(&env, &holidays.len().to_be_bytes()));
+        for holiday in holidays.iter() {
+            data.append(&Bytes::from_slice(&env, &holiday.start.to_be_bytes()));
+            data.append(&Bytes::from_slice(&env, &holiday.end.to_be_bytes()));
+            data.append(&Bytes::from_slice(&env, &holiday.fee_bps.to_be_bytes()));
+            data.push_back(holiday.used as u8);
+        }
+
+        match Self::stored_disbursement_limits(&env) {
+            Some(limits) => {
+                data.push_back(1);
+                data.append(&Bytes::from_slice(&env, &limits.len().to_be_bytes()));
+                for (level, amount) in limits.iter() {
+                    data.append(&Bytes::from_slice(&env, &level.to_be_bytes()));
+                    data.append(&Bytes::from_slice(&env, &amount.to_be_bytes()));
+                }
+            }
+            None => data.push_back(0),
+        }
+
+        data.push_back(Self::get_event_verbosity(env.clone()) as u8);
+
+        match Self::get_kyc_disclosure_threshold(env.clone()) {
+            Some(threshold) => {
+                data.push_back(1);
+                data.append(&Bytes::from_slice(&env, &threshold.to_be_bytes()));
+            }
+            None => data.push_back(0),
+        }
+
+        for key in [
+            DataKey::CampaignCounter,
+            DataKey::DonationCounter,
+            DataKey::NFTCounter,
+            DataKey::DisbursementCounter,
+        ] {
+            let value: u64 = storage.get(&key).unwrap_or(0);
+            data.append(&Bytes::from_slice(&env, &value.to_be_bytes()));
+        }
+
+        env.crypto().sha256(&data).into()
+    }
+
+    /// Check a digest against the current platform state
+    pub fn verify_state_digest(env: Env, digest: BytesN<32>) -> bool {
+        Self::export_state_digest(env) == digest
+    }
+
+    /// Get the state digest recorded by the last migration
+    pub fn get_pre_migration_digest(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&DataKey::PreMigrationDigest)
+    }
+
+    /// Create a new campaign (beneficiary function)
+    pub fn create_campaign(
+        env: Env,
+        beneficiary: Address,
+        title: String,
+        description: String,
+        goal_amount: i128,
+        duration_days: u64,
+        category: Category,
+        location: String,
+        perpetual: bool,
+        funding_mode: FundingMode,
+    ) -> Result<BytesN<32>, SaviaError> {
+        beneficiary.require_auth();
+        Self::require_not_paused(&env)?;
+        Self::require_not_blacklisted(&env, &beneficiary)?;
+
+        // Validate inputs
+        if goal_amount <= 0 {
+            return Err(SaviaError::InvalidGoal);
+        }
+        
+        // Perpetual campaigns have no duration; the goal is only a soft target
+        let valid_duration = if perpetual {
+            duration_days == 0
+        } else {
+            duration_days > 0 && duration_days <= 365
+        };
+        if !valid_duration {
+            return Err(SaviaError::InvalidDuration);
+        }
+
+        // A perpetual campaign never ends, so it can never miss its goal
+        if perpetual && funding_mode == FundingMode::AllOrNothing {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let min_level = Self::get_min_creator_level(env.clone());
+        let level = Self::get_trust_score(env.clone(), beneficiary.clone())
+            .map(|trust| trust.verification_level)
+            .unwrap_or(0);
+        if level < min_level {
+            return Err(SaviaError::VerificationRequired);
+        }
+        let recent_creations = Self::check_creation_limits(&env, &beneficiary)?;
+
+        // An empty allowlist accepts any category
+        let categories = Self::get_categories(env.clone());
+        if !categories.is_empty() && !categories.contains(category) {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        // Get and increment campaign counter
+        let counter: u64 = env.storage().instance().get(&DataKey::CampaignCounter).unwrap_or(0);
+        let new_counter = counter + 1;
+        env.storage().instance().set(&DataKey::CampaignCounter, &new_counter);
+        let active: u64 = env.storage().instance().get(&DataKey::ActiveCampaignCounter).unwrap_or(0);
+        env.storage().instance().set(&DataKey::ActiveCampaignCounter, &(active + 1));
+        Self::update_breakdowns(&env, category, |stats, is_period| {
+            stats.total_campaigns += 1;
+            if !is_period {
+                stats.active_campaigns += 1;
+            }
+        });
+
+        // Generate campaign ID using existing data
+        let current_time = env.ledger().timestamp();
+        let mut hash_input = Bytes::new(&env);
+        
+        // Convert to bytes properly
+        let beneficiary_bytes = beneficiary.clone().to_xdr(&env);
+        let title_bytes = title.to_string().into_bytes();
+               
+        hash_input.append(&beneficiary_bytes); //&Bytes::from_slice(&env, &beneficiary_bytes));
+        hash_input.append(&Bytes::from_slice(&env, &title_bytes));
+        hash_input.append(&Bytes::from_slice(&env, &goal_amount.to_be_bytes()));
+        hash_input.append(&Bytes::from_slice(&env, &current_time.to_be_bytes()));
+        hash_input.append(&Bytes::from_slice(&env, &new_counter.to_be_bytes()));
+        
+        let campaign_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+
+        let end_time = current_time + (duration_days * 24 * 60 * 60); // Convert to seconds, 0 days if perpetual
+
+        let title_key = Self::title_key(&env, &title);
+        let possible_duplicate = env.storage().persistent().has(&title_key);
+        if !possible_duplicate {
+            env.storage().persistent().set(&title_key, &campaign_id);
+        }
+        Self::touch_first_seen(&env, &beneficiary);
+        let token: Address = env.storage().instance().get(&DataKey::Token).ok_or(SaviaError::NotInitialized)?;
+
+        let campaign = Campaign {
+            id: campaign_id.clone(),
+            title,
+            description,
+            beneficiary: beneficiary.clone(),
+            goal_amount,
+            current_amount: 0,
+            disbursed_amount: 0,
+            start_time: current_time,
+            end_time,
+            verified: false,
+            trust_score: 0,
+            category,
+            location,
+            at_risk: false,
+            at_risk_acknowledged_at: None,
+            perpetual,
+            active: true,
+            funding_mode,
+            possible_duplicate,
+            endgame_locked: false,
+            goal_reached: false,
+            legacy_category: None,
+            min_donation: 0,
+            max_donation_per_donor: 0,
+            overfunding_limit_bps: 0,
+            suspended: false,
+            goal_reached_at: None,
+            close_on_goal: false,
+            metadata_uri: None,
+            token,
+            outcome: CampaignOutcome::Ongoing,
+        };
+
+        Self::store(&env, &DataKey::Campaign(campaign_id.clone()), &campaign);
+
+        let index_key = DataKey::CampaignsByBeneficiary(beneficiary.clone());
+        let mut beneficiary_campaigns: Vec<BytesN<32>> = env.storage().persistent().get(&index_key).unwrap_or(Vec::new(&env));
+        beneficiary_campaigns.push_back(campaign_id.clone());
+        env.storage().persistent().set(&index_key, &beneficiary_campaigns);
+        let category_key = DataKey::CampaignsByCategory(category);
+        let mut category_campaigns: Vec<BytesN<32>> = env.storage().persistent().get(&category_key).unwrap_or(Vec::new(&env));
+        category_campaigns.push_back(campaign_id.clone());
+        env.storage().persistent().set(&category_key, &category_campaigns);
+        Self::index_push(&env, IndexKey::ActiveCampaigns, campaign_id.clone());
+        Self::index_push(&env, IndexKey::AllCampaigns, campaign_id.clone());
+        if !perpetual {
+            Self::index_push(&env, IndexKey::CampaignsEndingOn(end_time / DAY), campaign_id.clone());
+        }
+        Self::record_campaign_created(&env, &beneficiary);
+        if let Some(mut recent) = recent_creations {
+            recent.push_back(current_time);
+            env.storage().persistent().set(&DataKey::Config(ConfigKey::RecentCreations(beneficiary.clone())), &recent);
+        }
+
+        publish_about(&env, topics::CAMPAIGN, topics::CREATED, campaign_id.clone(), (campaign_id.clone(), beneficiary, goal_amount));
+
+        let risk_score = Self::risk_score(&env, &campaign);
+        if risk_score >= Self::get_risk_threshold(env.clone()) {
+            let mut queue = Self::get_review_queue(env.clone());
+            queue.push_back(campaign_id.clone());
+            env.storage().persistent().set(&DataKey::ReviewQueue, &queue);
+
+            publish_about(&env, topics::CAMPAIGN, topics::QUEUED, campaign_id.clone(), (campaign_id.clone(), risk_score));
+        }
+        
+        Ok(campaign_id)
+    }
+
+    /// A campaign's top public donors by net amount given, largest first
+    pub fn get_leaderboard(env: Env, campaign_id: BytesN<32>) -> Vec<(Address, i128)> {
+        env.storage().persistent()
+            .get(&DataKey::CampaignData(CampaignKey::Leaderboard(campaign_id)))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get campaign details
+    pub fn get_campaign(env: Env, campaign_id: BytesN<32>) -> Option<Campaign> {
+        Self::load(&env, &DataKey::Campaign(campaign_id))
+    }
+
+    /// Extend a campaign's storage TTL. Anyone may call this to keep a
+    /// campaign from being archived.
+    pub fn bump_campaign(env: Env, campaign_id: BytesN<32>) -> Result<(), SaviaError> {
+        let key = DataKey::Campaign(campaign_id);
+        if !env.storage().persistent().has(&key) {
+            return Err(SaviaError::CampaignNotFound);
+        }
+        Self::extend_record(&env, &key);
+        Ok(())
+    }
+
+    /// Get a page of a beneficiary's campaigns in creation order
+    pub fn get_campaigns_by_beneficiary(env: Env, beneficiary: Address, offset: u32, limit: u32) -> Vec<Campaign> {
+        let ids: Vec<BytesN<32>> = env.storage().persistent()
+            .get(&DataKey::CampaignsByBeneficiary(beneficiary))
+            .unwrap_or(Vec::new(&env));
+
+        let mut campaigns = Vec::new(&env);
+        for campaign_id in Self::page(&env, &ids, offset, limit).iter() {
+            if let Some(campaign) = Self::get_campaign(env.clone(), campaign_id) {
+                campaigns.push_back(campaign);
+            }
+        }
+        campaigns
+    }
+
+    /// Get a campaign's progress towards its goal and its time left
+    pub fn get_campaign_progress(env: Env, campaign_id: BytesN<32>) -> Result<CampaignProgress, SaviaError> {
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        let now = env.ledger().timestamp();
+
+        let percent_funded = (campaign.current_amount.max(0).saturating_mul(10000) / campaign.goal_amount).min(MAX_PROGRESS_BPS);
+        let seconds_remaining = if campaign.perpetual { u64::MAX } else { campaign.end_time.saturating_sub(now) };
+        Ok(CampaignProgress {
+            percent_funded: percent_funded as u32,
+            seconds_remaining,
+            is_ended: campaign.has_ended(now),
+            is_goal_met: campaign.current_amount >= campaign.goal_amount,
+            donor_count: Self::get_campaign_stats(env, campaign_id).unique_donors,
+        })
+    }
+
+    /// Get a campaign's donation statistics
+    pub fn get_campaign_stats(env: Env, campaign_id: BytesN<32>) -> CampaignStats {
+        env.storage().persistent().get(&DataKey::CampaignData(CampaignKey::Stats(campaign_id))).unwrap_or_default()
+    }
+
+    /// Get a page of a category's campaigns in creation order
+    pub fn get_campaigns_by_category(env: Env, category: Category, offset: u32, limit: u32) -> Vec<Campaign> {
+        let ids: Vec<BytesN<32>> = env.storage().persistent()
+            .get(&DataKey::CampaignsByCategory(category))
+            .unwrap_or(Vec::new(&env));
+
+        let mut campaigns = Vec::new(&env);
+        for campaign_id in Self::page(&env, &ids, offset, limit).iter() {
+            if let Some(campaign) = Self::get_campaign(env.clone(), campaign_id) {
+                campaigns.push_back(campaign);
+            }
+        }
+        campaigns
+    }
+
+    /// List campaigns still accepting donations, starting at `cursor` (or the
+    /// beginning). Returns the page and the cursor for the next one, if any.
+    pub fn list_active_campaigns(
+        env: Env,
+        cursor: Option<Cursor>,
+        limit: u32,
+    ) -> Result<(Vec<Campaign>, Option<Cursor>), SaviaError> {
+        let (ids, next) = Self::index_page(&env, &IndexKey::ActiveCampaigns, cursor, limit)?;
+
+        let current_time = env.ledger().timestamp();
+        let mut campaigns = Vec::new(&env);
+        for campaign_id in ids.iter() {
+            if let Some(campaign) = Self::get_campaign(env.clone(), campaign_id) {
+                if campaign.active && !campaign.has_ended(current_time) {
+                    campaigns.push_back(campaign);
+                }
+            }
+        }
+        Ok((campaigns, next))
+    }
+
+    /// List campaigns in creation order, skipping the first `offset` that
+    /// pass the filters. `limit` is capped at `MAX_PAGE_SIZE`.
+    pub fn list_campaigns(env: Env, offset: u32, limit: u32, only_active: bool, only_verified: bool) -> Vec<Campaign> {
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let mut campaigns = Vec::new(&env);
+        let mut skipped = 0;
+        let mut cursor = None;
+        while campaigns.len() < limit {
+            let (ids, next) = match Self::index_page(&env, &IndexKey::AllCampaigns, cursor, MAX_PAGE_SIZE) {
+                Ok(page) => page,
+                Err(_) => break,
+            };
+            for campaign_id in ids.iter() {
+                let campaign: Campaign = match Self::load(&env, &DataKey::Campaign(campaign_id)) {
+                    Some(campaign) => campaign,
+                    None => continue,
+                };
+                if (only_active && !campaign.active) || (only_verified && !campaign.verified) {
+                    continue;
+                }
+                if skipped < offset {
+                    skipped += 1;
+                } else if campaigns.len() < limit {
+                    campaigns.push_back(campaign);
+                }
+            }
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        campaigns
+    }
+
+    /// Total number of campaigns ever created
+    pub fn get_campaign_count(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::CampaignCounter).unwrap_or(0)
+    }
+
+    /// Offset-based form of `list_active_campaigns`
+    pub fn list_active_campaigns_at(env: Env, offset: u32, limit: u32) -> Vec<Campaign> {
+        let cursor = Self::cursor_at(&env, &IndexKey::ActiveCampaigns, offset);
+        Self::list_active_campaigns(env.clone(), Some(cursor), limit)
+            .map(|(campaigns, _)| campaigns)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// List active campaigns ending between `from_ts` and `to_ts` inclusive,
+    /// soonest first. The window may span at most 31 days.
+    pub fn get_campaigns_ending_between(env: Env, from_ts: u64, to_ts: u64, limit: u32) -> Result<Vec<Campaign>, SaviaError> {
+        if from_ts > to_ts || to_ts - from_ts > MAX_RANGE_SECONDS {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let mut campaigns = Vec::new(&env);
+        for day in from_ts / DAY..=to_ts / DAY {
+            // Buckets are in creation order; sort each day by end time
+            let mut ending: Vec<Campaign> = Vec::new(&env);
+            let index = IndexKey::CampaignsEndingOn(day);
+            let mut cursor = None;
+            loop {
+                let (ids, next) = Self::index_page(&env, &index, cursor, MAX_PAGE_SIZE)?;
+                for campaign_id in ids.iter() {
+                    let Some(campaign) = Self::load::<Campaign>(&env, &DataKey::Campaign(campaign_id)) else {
+                        continue;
+                    };
+                    if !campaign.active || campaign.suspended || campaign.end_time < from_ts || campaign.end_time > to_ts {
+                        continue;
+                    }
+                    let position = ending.iter().position(|other| other.end_time > campaign.end_time)
+                        .unwrap_or(ending.len() as usize) as u32;
+                    ending.insert(position, campaign);
+                }
+                match next {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+            for campaign in ending.iter() {
+                if campaigns.len() >= limit {
+                    return Ok(campaigns);
+                }
+                campaigns.push_back(campaign);
+            }
+        }
+        Ok(campaigns)
+    }
+
+    /// Up to `limit` campaigns that most recently met their goal and are
+    /// still funded, newest first
+    pub fn get_recently_funded(env: Env, limit: u32) -> Vec<Campaign> {
+        let ids: Vec<BytesN<32>> = env.storage().persistent()
+            .get(&DataKey::Config(ConfigKey::RecentlyFunded))
+            .unwrap_or(Vec::new(&env));
+
+        let mut campaigns = Vec::new(&env);
+        for campaign_id in ids.iter() {
+            if campaigns.len() >= limit.min(RECENTLY_FUNDED_SIZE) {
+                break;
+            }
+            if let Some(campaign) = Self::load::<Campaign>(&env, &DataKey::Campaign(campaign_id)) {
+                if !campaign.suspended && campaign.current_amount >= campaign.goal_amount {
+                    campaigns.push_back(campaign);
+                }
+            }
+        }
+        campaigns
+    }
+
+    /// Drop closed and ended campaigns from the active index. Anyone can run
+    /// this; outstanding cursors over the index become stale if anything is
+    /// removed. Returns the number of campaigns removed.
+    pub fn compact_active_campaigns(env: Env) -> u32 {
+        let current_time = env.ledger().timestamp();
+        let removed = Self::index_retain(&env, &IndexKey::ActiveCampaigns, |campaign_id| {
+            env.storage().persistent()
+                .get::<_, Campaign>(&DataKey::Campaign(campaign_id.clone()))
+                .is_some_and(|campaign| campaign.active && !campaign.has_ended(current_time))
+        });
+
+        if removed > 0 {
+            publish(&env, topics::CAMPAIGN, topics::COMPACTED, removed);
+        }
+        removed
+    }
+
+    /// Close a campaign to new donations (beneficiary function)
+    pub fn close_campaign(env: Env, campaign_id: BytesN<32>) -> Result<(), SaviaError> {
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if !campaign.active {
+            return Err(SaviaError::CampaignInactive);
+        }
+
+        Self::deactivate(&env, &mut campaign);
+        if campaign.outcome == CampaignOutcome::ClosedEarly {
+            let generation = Self::index_meta(&env, &IndexKey::ActiveCampaigns).generation;
+            env.storage().persistent().set(
+                &DataKey::CampaignData(CampaignKey::Closure(campaign_id.clone())),
+                &(env.ledger().timestamp(), generation),
+            );
+        }
+
+        publish_about(&env, topics::CAMPAIGN, topics::CLOSED, campaign_id.clone(), campaign_id);
+        Ok(())
+    }
+
+    /// Ask the admin to reopen a campaign closed early by mistake
+    /// (beneficiary function). Only campaigns closed before their end time
+    /// and below their goal qualify, within 7 days of closing.
+    pub fn request_reopen(env: Env, campaign_id: BytesN<32>) -> Result<(), SaviaError> {
+        let campaign = Self::require_beneficiary(&env, &campaign_id)?;
+        Self::check_reopenable(&env, &campaign)?;
+
+        env.storage().persistent().set(
+            &DataKey::CampaignData(CampaignKey::ReopenRequested(campaign_id.clone())),
+            &env.ledger().timestamp(),
+        );
+
+        publish_about(&env, topics::CAMPAIGN, topics::REOPEN_REQUESTED, campaign_id.clone(), campaign_id);
+        Ok(())
+    }
+
+    /// Confirm a beneficiary's reopen request (admin function). The
+    /// campaign takes donations again until its original end time.
+    pub fn reopen_campaign(env: Env, campaign_id: BytesN<32>) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        let request_key = DataKey::CampaignData(CampaignKey::ReopenRequested(campaign_id.clone()));
+        if !env.storage().persistent().has(&request_key) {
+            return Err(SaviaError::InvalidInput);
+        }
+        let generation = Self::check_reopenable(&env, &campaign)?;
+
+        campaign.active = true;
+        campaign.goal_reached = false;
+        campaign.outcome = CampaignOutcome::Ongoing;
+        Self::store(&env, &DataKey::Campaign(campaign_id.clone()), &campaign);
+        env.storage().persistent().remove(&request_key);
+        env.storage().persistent().remove(&DataKey::CampaignData(CampaignKey::Closure(campaign_id.clone())));
+        env.storage().persistent().remove(&DataKey::CampaignData(CampaignKey::EndedAt(campaign_id.clone())));
+
+        let active: u64 = env.storage().instance().get(&DataKey::ActiveCampaignCounter).unwrap_or(0);
+        env.storage().instance().set(&DataKey::ActiveCampaignCounter, &(active + 1));
+        Self::update_breakdowns(&env, campaign.category, |stats, is_period| {
+            if !is_period {
+                stats.active_campaigns += 1;
+            }
+        });
+        // Compacting the active index since the close dropped the campaign
+        if Self::index_meta(&env, &IndexKey::ActiveCampaigns).generation != generation {
+            Self::index_push(&env, IndexKey::ActiveCampaigns, campaign_id.clone());
+        }
+
+        publish_about(&env, topics::CAMPAIGN, topics::REOPENED, campaign_id.clone(), campaign_id);
+        Ok(())
+    }
+
+    /// Fail with `RateLimited` if `beneficiary` already runs or recently
+    /// created as many campaigns as allowed. Returns their creation times
+    /// within the last 24 hours, or `None` if they are exempt.
+    fn check_creation_limits(env: &Env, beneficiary: &Address) -> Result<Option<Vec<u64>>, SaviaError> {
+        if Self::is_creation_exempt(env.clone(), beneficiary.clone()) {
+            return Ok(None);
+        }
+        let limits = Self::get_creation_limits(env.clone());
+        let now = env.ledger().timestamp();
+
+        if limits.max_active > 0 {
+            let campaign_ids: Vec<BytesN<32>> = env.storage().persistent()
+                .get(&DataKey::CampaignsByBeneficiary(beneficiary.clone()))
+                .unwrap_or(Vec::new(env));
+            let mut active = 0;
+            for campaign_id in campaign_ids.iter() {
+                let campaign: Option<Campaign> = env.storage().persistent().get(&DataKey::Campaign(campaign_id));
+                if campaign.is_some_and(|campaign| campaign.active && !campaign.has_ended(now)) {
+                    active += 1;
+                }
+            }
+            if active >= limits.max_active {
+                return Err(SaviaError::RateLimited);
+            }
+        }
+
+        let stored: Vec<u64> = env.storage().persistent()
+            .get(&DataKey::Config(ConfigKey::RecentCreations(beneficiary.clone())))
+            .unwrap_or(Vec::new(env));
+        let mut recent = Vec::new(env);
+        for created_at in stored.iter() {
+            if now - created_at < DAY {
+                recent.push_back(created_at);
+            }
+        }
+        if limits.max_per_day > 0 && recent.len() >= limits.max_per_day {
+            return Err(SaviaError::RateLimited);
+        }
+        Ok(Some(recent))
+    }
+
+    /// Move a campaign to the front of the recently funded list, dropping
+    /// the oldest entry once it is full
+    fn push_recently_funded(env: &Env, campaign_id: &BytesN<32>) {
+        let key = DataKey::Config(ConfigKey::RecentlyFunded);
+        let mut ids: Vec<BytesN<32>> = env.storage().persistent().get(&key).unwrap_or(Vec::new(env));
+        if let Some(position) = ids.first_index_of(campaign_id) {
+            ids.remove(position);
+        }
+        ids.push_front(campaign_id.clone());
+        if ids.len() > RECENTLY_FUNDED_SIZE {
+            ids.pop_back();
+        }
+        env.storage().persistent().set(&key, &ids);
+    }
+
+    /// Check a campaign was closed early within the reopen window and has
+    /// time left, returning the active index generation at its close
+    fn check_reopenable(env: &Env, campaign: &Campaign) -> Result<u32, SaviaError> {
+        if campaign.suspended {
+            return Err(SaviaError::CampaignSuspended);
+        }
+        if campaign.active {
+            return Err(SaviaError::InvalidInput);
+        }
+        let now = env.ledger().timestamp();
+        if campaign.outcome != CampaignOutcome::ClosedEarly || campaign.has_ended(now) {
+            return Err(SaviaError::CampaignEnded);
+        }
+        let (closed_at, generation): (u64, u32) = env.storage().persistent()
+            .get(&DataKey::CampaignData(CampaignKey::Closure(campaign.id.clone())))
+            .ok_or(SaviaError::CampaignEnded)?;
+        if now > closed_at + REOPEN_WINDOW
+            || env.storage().persistent().has(&DataKey::CampaignData(CampaignKey::Archived(campaign.id.clone())))
+        {
+            return Err(SaviaError::CampaignLocked);
+        }
+        Ok(generation)
+    }
+
+    /// Take down a fraudulent campaign (admin function). The campaign stops
+    /// taking donations, its disbursements are blocked, approved ones
+    /// included, and every donation becomes refundable.
+    pub fn suspend_campaign(env: Env, campaign_id: BytesN<32>, reason: String) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        if campaign.suspended {
+            return Err(SaviaError::CampaignSuspended);
+        }
+
+        Self::suspend(&env, &mut campaign, reason);
+        Ok(())
+    }
+
+    /// Block donations and new disbursement requests to a campaign pending
+    /// clarification, or lift the block (admin function). Unlike a
+    /// suspension this leaves the campaign active and unpenalized, already
+    /// approved disbursements can still be executed, and it can still be
+    /// finalized once it ends. Blocked calls fail with `CampaignFrozen`.
+    pub fn freeze_campaign(env: Env, campaign_id: BytesN<32>, frozen: bool) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if !env.storage().persistent().has(&DataKey::Campaign(campaign_id.clone())) {
+            return Err(SaviaError::CampaignNotFound);
+        }
+        let key = DataKey::CampaignData(CampaignKey::Frozen(campaign_id.clone()));
+        if frozen {
+            Self::store(&env, &key, &env.ledger().timestamp());
+            publish_about(&env, topics::CAMPAIGN, topics::FROZEN, campaign_id.clone(), campaign_id);
+        } else {
+            env.storage().persistent().remove(&key);
+            publish_about(&env, topics::CAMPAIGN, topics::UNFROZEN, campaign_id.clone(), campaign_id);
+        }
+        Ok(())
+    }
+
+    /// Whether the admin has frozen a campaign
+    pub fn is_frozen(env: Env, campaign_id: BytesN<32>) -> bool {
+        env.storage().persistent().has(&DataKey::CampaignData(CampaignKey::Frozen(campaign_id)))
+    }
+
+    /// Suspend a campaign and penalize its beneficiary
+    fn suspend(env: &Env, campaign: &mut Campaign, reason: String) {
+        campaign.suspended = true;
+        if campaign.active {
+            Self::deactivate(env, campaign);
+        } else {
+            campaign.outcome = CampaignOutcome::Suspended;
+            Self::store(env, &DataKey::Campaign(campaign.id.clone()), campaign);
+        }
+
+        let penalty = Self::get_suspension_penalty(env.clone());
+        if penalty > 0 {
+            Self::apply_trust_adjustment(env, &campaign.beneficiary, -(penalty as i32), reason.clone(), TrustChangeReason::AdminAdjustment);
+        }
+
+        publish_about(env, topics::CAMPAIGN, topics::SUSPENDED, campaign.id.clone(), (campaign.id.clone(), reason));
+    }
+
+    /// Deactivate a campaign whose end time has passed. Anyone may call this;
+    /// returns whether the goal was reached.
+    pub fn finalize_campaign(env: Env, campaign_id: BytesN<32>) -> Result<bool, SaviaError> {
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        if !campaign.active {
+            return Err(SaviaError::CampaignInactive);
+        }
+        if !campaign.has_ended(env.ledger().timestamp()) {
+            return Err(SaviaError::CampaignNotEnded);
+        }
+
+        Self::deactivate(&env, &mut campaign);
+        Self::update_beneficiary_trust_score(&env, &campaign);
+
+        publish_about(&env, topics::CAMPAIGN, topics::FINALIZED, campaign_id.clone(), (campaign_id, campaign.goal_reached));
+        Ok(campaign.goal_reached)
+    }
+
+    /// Delete a finished campaign's donation records and donation index
+    /// entries to stop paying rent on them (beneficiary or admin function).
+    /// Allowed once the archive grace period has passed since the campaign
+    /// ended, and only if no donation can still be refunded. The campaign,
+    /// its stats and leaderboard, and donors' vote weights are kept.
+    ///
+    /// Returns the sha256 of the XDR of the campaign's donation list as
+    /// `get_donations_by_campaign` returned it, so a copy taken before
+    /// archiving can still be checked against `get_archive_digest`.
+    pub fn archive_campaign(env: Env, campaign_id: BytesN<32>, caller: Address) -> Result<BytesN<32>, SaviaError> {
+        caller.require_auth();
+
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        let admin: Option<Address> = env.storage().instance().get(&DataKey::Admin);
+        if caller != campaign.beneficiary && Some(caller) != admin {
+            return Err(SaviaError::Unauthorized);
+        }
+        let digest_key = DataKey::CampaignData(CampaignKey::Archived(campaign_id.clone()));
+        if env.storage().persistent().has(&digest_key) {
+            return Err(SaviaError::InvalidInput);
+        }
+        if campaign.active {
+            return Err(SaviaError::CampaignNotEnded);
+        }
+        let ended_at: u64 = env.storage().persistent()
+            .get(&DataKey::CampaignData(CampaignKey::EndedAt(campaign_id.clone())))
+            .unwrap_or(campaign.end_time);
+        if env.ledger().timestamp() < ended_at.saturating_add(Self::get_archive_grace_period(env.clone())) {
+            return Err(SaviaError::CampaignNotEnded);
+        }
+
+        // An all-or-nothing campaign closed early becomes refundable at its
+        // end time, so don't wait for it
+        let refundable = campaign.suspended
+            || (campaign.funding_mode == FundingMode::AllOrNothing && campaign.current_amount < campaign.goal_amount);
+        let available = campaign.current_amount - campaign.disbursed_amount;
+
+        let index = IndexKey::CampaignDonations(campaign_id.clone());
+        let mut donations: Vec<Donation> = Vec::new(&env);
+        let mut cursor = None;
+        loop {
+            let (ids, next) = Self::index_page(&env, &index, cursor, MAX_PAGE_SIZE)?;
+            for donation_id in ids.iter() {
+                let Some(donation) = Self::load::<Donation>(&env, &DataKey::Donation(donation_id)) else {
+                    continue;
+                };
+                if refundable && !donation.refunded && donation.amount <= available {
+                    return Err(SaviaError::CampaignLocked);
+                }
+                donations.push_back(donation);
+            }
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        let mut listed = Vec::new(&env);
+        for donation in donations.iter() {
+            listed.push_back(Self::redact_donation(&env, donation));
+        }
+        let digest: BytesN<32> = env.crypto().sha256(&listed.to_xdr(&env)).into();
+        publish_about(&env, topics::CAMPAIGN, topics::ARCHIVED, campaign_id.clone(), (campaign_id.clone(), donations.len(), digest.clone()));
+
+        let mut archived = Map::new(&env);
+        let mut donors = Vec::new(&env);
+        let mut days = Vec::new(&env);
+        for donation in donations.iter() {
+            env.storage().persistent().remove(&DataKey::Donation(donation.id.clone()));
+            archived.set(donation.id, true);
+            if !donors.contains(&donation.donor) {
+                donors.push_back(donation.donor);
+            }
+            if !days.contains(donation.timestamp / DAY) {
+                days.push_back(donation.timestamp / DAY);
+            }
+        }
+        for donor in donors.iter() {
+            env.storage().persistent().remove(&DataKey::DonorCampaignTotal(campaign_id.clone(), donor.clone()));
+            env.storage().persistent().remove(&DataKey::CampaignData(CampaignKey::PublicDonorTotal(campaign_id.clone(), donor.clone())));
+            Self::index_retain(&env, &IndexKey::DonorDonations(donor), |id| !archived.contains_key(id.clone()));
+        }
+        for day in days.iter() {
+            Self::index_retain(&env, &IndexKey::DonationsByDay(day), |id| !archived.contains_key(id.clone()));
+        }
+        Self::index_clear(&env, &index);
+
+        Self::store(&env, &digest_key, &digest);
+        Ok(digest)
+    }
+
+    /// Digest of the donations deleted when a campaign was archived, if it
+    /// was
+    pub fn get_archive_digest(env: Env, campaign_id: BytesN<32>) -> Option<BytesN<32>> {
+        Self::load(&env, &DataKey::CampaignData(CampaignKey::Archived(campaign_id)))
+    }
+
+    /// Pay the undisbursed remainder of a closed or ended campaign to `to`
+    /// (beneficiary function). An all-or-nothing campaign must have met its
+    /// goal; otherwise the funds are held for donor refunds.
+    pub fn withdraw_remaining(env: Env, campaign_id: BytesN<32>, to: Address) -> Result<i128, SaviaError> {
+        Self::require_not_paused(&env)?;
+
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if campaign.suspended {
+            return Err(SaviaError::CampaignSuspended);
+        }
+        if Self::is_dispute_hold(env.clone(), campaign_id.clone()) {
+            return Err(SaviaError::CampaignLocked);
+        }
+        if campaign.active && !campaign.has_ended(env.ledger().timestamp()) {
+            return Err(SaviaError::CampaignNotEnded);
+        }
+        if campaign.funding_mode == FundingMode::AllOrNothing && campaign.current_amount < campaign.goal_amount {
+            return Err(SaviaError::GoalNotReached);
+        }
+        Self::require_not_blacklisted(&env, &campaign.beneficiary)?;
+        Self::require_not_blacklisted(&env, &to)?;
+
+        let remaining = campaign.current_amount - campaign.disbursed_amount;
+        if remaining <= 0 {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        Self::campaign_token(&env, &campaign).transfer(&env.current_contract_address(), &to, &remaining);
+
+        campaign.disbursed_amount = campaign.current_amount;
+        Self::store(&env, &DataKey::Campaign(campaign_id.clone()), &campaign);
+
+        publish_about(&env, topics::CAMPAIGN, topics::WITHDRAWN, campaign_id.clone(), (campaign_id, to, remaining));
+        Ok(remaining)
+    }
+
+    /// Maintenance check anyone can run: flags the campaign and emits an
+    /// `at_risk` event if it is clearly heading for failure, and announces
+    /// the endgame disbursement lock once it engages. Returns whether the
+    /// campaign is flagged at risk.
+    pub fn check_campaign_health(env: Env, campaign_id: BytesN<32>) -> Result<bool, SaviaError> {
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        let flagged = Self::flag_if_at_risk(&env, &mut campaign);
+        let locked = Self::flag_if_endgame_locked(&env, &mut campaign);
+        if flagged || locked {
+            Self::store(&env, &DataKey::Campaign(campaign_id), &campaign);
+        }
+        Ok(campaign.at_risk)
+    }
+
+    /// Beneficiary acknowledges the at-risk warning on their campaign
+    pub fn acknowledge_at_risk(env: Env, campaign_id: BytesN<32>) -> Result<(), SaviaError> {
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if !campaign.at_risk {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let current_time = env.ledger().timestamp();
+        campaign.at_risk_acknowledged_at = Some(current_time);
+        Self::store(&env, &DataKey::Campaign(campaign_id.clone()), &campaign);
+
+        publish_about(&env, topics::CAMPAIGN, topics::ACKNOWLEDGED, campaign_id.clone(), (campaign_id, current_time));
+        Ok(())
+    }
+
+    /// Register a partner contract whose `on_donation(campaign_id, amount)`
+    /// is called after every donation to this campaign. Needs both the
+    /// beneficiary and the admin; replaces any existing hook.
+    pub fn register_hook(env: Env, campaign_id: BytesN<32>, hook_contract: Address) -> Result<(), SaviaError> {
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+        Self::require_admin(&env)?;
+
+        env.storage().persistent().set(&DataKey::CampaignHook(campaign_id.clone()), &hook_contract);
+
+        publish_about(&env, topics::CAMPAIGN, topics::HOOK_REGISTERED, campaign_id.clone(), (campaign_id, hook_contract));
+        Ok(())
+    }
+
+    /// Remove the campaign's donation hook (beneficiary function)
+    pub fn unregister_hook(env: Env, campaign_id: BytesN<32>) -> Result<(), SaviaError> {
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        let key = DataKey::CampaignHook(campaign_id.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().persistent().remove(&key);
+
+        publish_about(&env, topics::CAMPAIGN, topics::HOOK_REMOVED, campaign_id.clone(), campaign_id);
+        Ok(())
+    }
+
+    /// Get the donation hook registered for a campaign
+    pub fn get_hook(env: Env, campaign_id: BytesN<32>) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::CampaignHook(campaign_id))
+    }
+
+    /// Verify a campaign (admin function)
+    pub fn verify_campaign(
+        env: Env,
+        campaign_id: BytesN<32>,
+        trust_score: u32,
+    ) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.verified = true;
+        campaign.trust_score = trust_score;
+
+        Self::store(&env, &DataKey::Campaign(campaign_id.clone()), &campaign);
+
+        // Verification is the outcome of review
+        let queue = Self::get_review_queue(env.clone());
+        if let Some(index) = queue.first_index_of(&campaign_id) {
+            let mut queue = queue;
+            queue.remove(index);
+            env.storage().persistent().set(&DataKey::ReviewQueue, &queue);
+        }
+
+        publish_about(&env, topics::CAMPAIGN, topics::VERIFIED, campaign_id.clone(), (campaign_id, trust_score));
+        Ok(())
+    }
+
+    /// Automated 0-100 triage score from on-chain signals; higher means the
+    /// campaign should be reviewed sooner. Independent of the trust score.
+    pub fn get_risk_score(env: Env, campaign_id: BytesN<32>) -> Result<u32, SaviaError> {
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        Ok(Self::risk_score(&env, &campaign))
+    }
+
+    /// Set the risk signal weights; they may add up to at most 100 (admin function)
+    pub fn set_risk_weights(env: Env, weights: RiskWeights) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let total = weights.new_beneficiary as u64
+            + weights.unverified as u64
+            + weights.goal_over_cap as u64
+            + weights.duplicate_title as u64
+            + weights.self_donations as u64
+            + weights.reports as u64;
+        if total > 100 {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        env.storage().instance().set(&DataKey::RiskWeights, &weights);
+
+        publish(&env, topics::CONFIG, topics::RISK, weights);
+        Ok(())
+    }
+
+    /// Get the risk signal weights
+    pub fn get_risk_weights(env: Env) -> RiskWeights {
+        env.storage().instance().get(&DataKey::RiskWeights).unwrap_or(RiskWeights {
+            new_beneficiary: 20,
+            unverified: 15,
+            goal_over_cap: 25,
+            duplicate_title: 15,
+            self_donations: 15,
+            reports: 10,
+        })
+    }
+
+    /// Set the risk score from which new campaigns join the review queue (admin function)
+    pub fn set_risk_threshold(env: Env, threshold: u32) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if threshold > 100 {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::RiskThreshold, &threshold);
+        Ok(())
+    }
+
+    /// Get the risk score from which new campaigns join the review queue
+    pub fn get_risk_threshold(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::RiskThreshold).unwrap_or(DEFAULT_RISK_THRESHOLD)
+    }
+
+    /// Campaigns awaiting review, oldest first; verifying a campaign removes it
+    pub fn get_review_queue(env: Env) -> Vec<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::ReviewQueue).unwrap_or(Vec::new(&env))
+    }
+
+    /// Report a campaign for review; each address can report a campaign once
+    pub fn report_campaign(env: Env, reporter: Address, campaign_id: BytesN<32>) -> Result<u32, SaviaError> {
+        reporter.require_auth();
+        Self::require_initialized(&env)?;
+
+        if !env.storage().persistent().has(&DataKey::Campaign(campaign_id.clone())) {
+            return Err(SaviaError::CampaignNotFound);
+        }
+
+        let reported_key = DataKey::Reported(campaign_id.clone(), reporter.clone());
+        if env.storage().persistent().has(&reported_key) {
+            return Err(SaviaError::AlreadyReported);
+        }
+        env.storage().persistent().set(&reported_key, &true);
+
+        let count_key = DataKey::CampaignReports(campaign_id.clone());
+        let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0) + 1;
+        env.storage().persistent().set(&count_key, &count);
+
+        publish_about(&env, topics::CAMPAIGN, topics::REPORTED, campaign_id.clone(), (campaign_id, reporter, count));
+        Ok(count)
+    }
+
+    /// Raise a concern about a campaign the reporter donated to; each
+    /// donor may have one open dispute per campaign. Once the campaign's
+    /// open disputes exceed the dispute threshold, its donations and
+    /// undisbursed funds are held until the admin resolves them, whether or
+    /// not the campaign is still active. Returns the dispute id.
+    pub fn file_dispute(
+        env: Env,
+        campaign_id: BytesN<32>,
+        reporter: Address,
+        reason_uri: String,
+    ) -> Result<BytesN<32>, SaviaError> {
+        reporter.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        if campaign.suspended {
+            return Err(SaviaError::CampaignSuspended);
+        }
+        if Self::donor_campaign_total(&env, &campaign_id, &reporter) <= 0 {
+            return Err(SaviaError::Unauthorized);
+        }
+        if reason_uri.is_empty() || reason_uri.len() > MAX_DISPUTE_URI_LEN {
+            return Err(SaviaError::InvalidInput);
+        }
+        let reporter_key = DataKey::CampaignData(CampaignKey::OpenDispute(campaign_id.clone(), reporter.clone()));
+        if env.storage().persistent().has(&reporter_key) {
+            return Err(SaviaError::AlreadyReported);
+        }
+
+        let counter: u64 = env.storage().instance().get(&DataKey::Config(ConfigKey::DisputeCounter)).unwrap_or(0) + 1;
+        env.storage().instance().set(&DataKey::Config(ConfigKey::DisputeCounter), &counter);
+
+        let mut hash_input = Bytes::new(&env);
+        hash_input.append(&Bytes::from_slice(&env, campaign_id.to_array().as_slice()));
+        hash_input.append(&reporter.clone().to_xdr(&env));
+        hash_input.append(&Bytes::from_slice(&env, &counter.to_be_bytes()));
+        let dispute_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+
+        let dispute = Dispute {
+            id: dispute_id.clone(),
+            campaign_id: campaign_id.clone(),
+            reporter: reporter.clone(),
+            reason_uri,
+            status: DisputeStatus::Open,
+            filed_at: env.ledger().timestamp(),
+            resolved_at: None,
+        };
+        Self::store(&env, &DataKey::CampaignData(CampaignKey::Dispute(dispute_id.clone())), &dispute);
+        Self::store(&env, &reporter_key, &dispute_id);
+
+        let mut open = Self::get_open_disputes(env.clone(), campaign_id.clone());
+        open.push_back(dispute_id.clone());
+        Self::store(&env, &DataKey::CampaignData(CampaignKey::OpenDisputes(campaign_id.clone())), &open);
+
+        publish_about(&env, topics::DISPUTE, topics::FILED, campaign_id.clone(), (dispute_id.clone(), reporter, open.len()));
+
+        let hold_key = DataKey::CampaignData(CampaignKey::DisputeHold(campaign_id.clone()));
+        // Hold whatever is still in escrow, even once the campaign has closed
+        if campaign.current_amount > campaign.disbursed_amount
+            && !env.storage().persistent().has(&hold_key)
+            && Self::disputes_exceed_threshold(&env, &campaign_id, open.len())
+        {
+            env.storage().persistent().set(&hold_key, &true);
+            publish_about(&env, topics::CAMPAIGN, topics::HELD, campaign_id.clone(), (campaign_id, open.len()));
+        }
+        Ok(dispute_id)
+    }
+
+    /// Rule on a dispute (admin function). Upholding it suspends the
+    /// campaign, which makes every donation refundable. Dismissing it
+    /// releases held donations once the remaining open disputes are back
+    /// within the threshold.
+    pub fn resolve_dispute(env: Env, dispute_id: BytesN<32>, uphold: bool) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let dispute_key = DataKey::CampaignData(CampaignKey::Dispute(dispute_id.clone()));
+        let mut dispute: Dispute = Self::load(&env, &dispute_key).ok_or(SaviaError::InvalidInput)?;
+        if dispute.status != DisputeStatus::Open {
+            return Err(SaviaError::InvalidInput);
+        }
+        let campaign_id = dispute.campaign_id.clone();
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        dispute.status = if uphold { DisputeStatus::Upheld } else { DisputeStatus::Dismissed };
+        dispute.resolved_at = Some(env.ledger().timestamp());
+        Self::store(&env, &dispute_key, &dispute);
+        env.storage().persistent().remove(&DataKey::CampaignData(CampaignKey::OpenDispute(campaign_id.clone(), dispute.reporter.clone())));
+
+        let mut open = Self::get_open_disputes(env.clone(), campaign_id.clone());
+        if let Some(position) = open.first_index_of(&dispute_id) {
+            open.remove(position);
+        }
+        Self::store(&env, &DataKey::CampaignData(CampaignKey::OpenDisputes(campaign_id.clone())), &open);
+
+        publish_about(&env, topics::DISPUTE, topics::RESOLVED, campaign_id.clone(), (dispute_id, uphold));
+
+        if uphold && !campaign.suspended {
+            Self::suspend(&env, &mut campaign, dispute.reason_uri);
+        }
+        let hold_key = DataKey::CampaignData(CampaignKey::DisputeHold(campaign_id.clone()));
+        if env.storage().persistent().has(&hold_key)
+            && (campaign.suspended || !Self::disputes_exceed_threshold(&env, &campaign_id, open.len()))
+        {
+            env.storage().persistent().remove(&hold_key);
+            publish_about(&env, topics::CAMPAIGN, topics::RELEASED, campaign_id.clone(), campaign_id);
+        }
+        Ok(())
+    }
+
+    /// Get a dispute
+    pub fn get_dispute(env: Env, dispute_id: BytesN<32>) -> Option<Dispute> {
+        Self::load(&env, &DataKey::CampaignData(CampaignKey::Dispute(dispute_id)))
+    }
+
+    /// Ids of a campaign's open disputes, oldest first
+    pub fn get_open_disputes(env: Env, campaign_id: BytesN<32>) -> Vec<BytesN<32>> {
+        Self::load(&env, &DataKey::CampaignData(CampaignKey::OpenDisputes(campaign_id)))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Whether a campaign's donations and payouts are held pending dispute
+    /// review
+    pub fn is_dispute_hold(env: Env, campaign_id: BytesN<32>) -> bool {
+        env.storage().persistent().has(&DataKey::CampaignData(CampaignKey::DisputeHold(campaign_id)))
+    }
+
+    /// Set how many open disputes put a campaign's donations on hold
+    /// (admin function)
+    pub fn set_dispute_threshold(env: Env, threshold: DisputeThreshold) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if threshold.max_open_bps > 10000 {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::Config(ConfigKey::DisputeThreshold), &threshold);
+
+        publish(&env, topics::CONFIG, topics::DISPUTE_THRESHOLD, threshold);
+        Ok(())
+    }
+
+    /// Get how many open disputes put a campaign's donations on hold
+    pub fn get_dispute_threshold(env: Env) -> DisputeThreshold {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::DisputeThreshold))
+            .unwrap_or(DisputeThreshold { max_open: DEFAULT_MAX_OPEN_DISPUTES, max_open_bps: 0 })
+    }
+
+    /// Whether `open` disputes exceed either limit of the dispute threshold
+    fn disputes_exceed_threshold(env: &Env, campaign_id: &BytesN<32>, open: u32) -> bool {
+        let threshold = Self::get_dispute_threshold(env.clone());
+        if threshold.max_open > 0 && open > threshold.max_open {
+            return true;
+        }
+        let donors = Self::get_campaign_stats(env.clone(), campaign_id.clone()).unique_donors;
+        threshold.max_open_bps > 0 && open as u64 * 10000 > donors as u64 * threshold.max_open_bps as u64
+    }
+
+    /// Process a donation
+    pub fn donate(
+        env: Env,
+        campaign_id: BytesN<32>,
+        donor: Address,
+        amount: i128,
+        anonymous: bool,
+        mint_nft: bool,
+        message: String,
+    ) -> Result<BytesN<32>, SaviaError> {
+        Self::donate_with_privacy(env, campaign_id, donor, amount, Privacy::from_anonymous(anonymous), mint_nft, message)
+    }
+
+    /// Process a donation with an explicit privacy mode
+    pub fn donate_with_privacy(
+        env: Env,
+        campaign_id: BytesN<32>,
+        donor: Address,
+        amount: i128,
+        privacy: Privacy,
+        mint_nft: bool,
+        message: String,
+    ) -> Result<BytesN<32>, SaviaError> {
+        donor.require_auth();
+        let mut campaign = Self::check_donation(&env, &campaign_id, &donor, amount, privacy, &message)?;
+
+        // Move the full amount into escrow; the fee share is tracked separately
+        let token_client = Self::campaign_token(&env, &campaign);
+        if token_client.try_transfer(&donor, &env.current_contract_address(), &amount).is_err() {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        Self::complete_donation(&env, &mut campaign, donor, amount, privacy, mint_nft, None, None, message)
+    }
+
+    /// Donate in someone's honor: `donor` pays and earns the trust credit,
+    /// while the badge is minted to `recipient`. An anonymous gift hides the
+    /// donor but not the recipient.
+    pub fn donate_as_gift(
+        env: Env,
+        campaign_id: BytesN<32>,
+        donor: Address,
+        recipient: Address,
+        amount: i128,
+        anonymous: bool,
+        message: String,
+    ) -> Result<BytesN<32>, SaviaError> {
+        donor.require_auth();
+
+        if recipient == donor {
+            return Err(SaviaError::InvalidInput);
+        }
+        let privacy = Privacy::from_anonymous(anonymous);
+        let mut campaign = Self::check_donation(&env, &campaign_id, &donor, amount, privacy, &message)?;
+
+        let token_client = Self::campaign_token(&env, &campaign);
+        if token_client.try_transfer(&donor, &env.current_contract_address(), &amount).is_err() {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        Self::complete_donation(&env, &mut campaign, donor, amount, privacy, true, Some(recipient), None, message)
+    }
+
+    /// Donate crediting `referrer` with bringing the donor in. The referrer
+    /// gains referral stats and a small, capped trust score bonus.
+    pub fn donate_with_referral(
+        env: Env,
+        campaign_id: BytesN<32>,
+        donor: Address,
+        amount: i128,
+        anonymous: bool,
+        mint_nft: bool,
+        message: String,
+        referrer: Address,
+    ) -> Result<BytesN<32>, SaviaError> {
+        donor.require_auth();
+
+        if referrer == donor {
+            return Err(SaviaError::InvalidInput);
+        }
+        let privacy = Privacy::from_anonymous(anonymous);
+        let mut campaign = Self::check_donation(&env, &campaign_id, &donor, amount, privacy, &message)?;
+
+        let token_client = Self::campaign_token(&env, &campaign);
+        if token_client.try_transfer(&donor, &env.current_contract_address(), &amount).is_err() {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        Self::complete_donation(&env, &mut campaign, donor, amount, privacy, mint_nft, None, Some(referrer), message)
+    }
+
+    /// Donate with a client-chosen reference that makes retries safe. A
+    /// second call with the same donor and `client_ref` records nothing and
+    /// returns the id of the donation the first call made.
+    pub fn donate_with_ref(
+        env: Env,
+        campaign_id: BytesN<32>,
+        donor: Address,
+        amount: i128,
+        anonymous: bool,
+        mint_nft: bool,
+        message: String,
+        client_ref: BytesN<32>,
+    ) -> Result<BytesN<32>, SaviaError> {
+        let key = DataKey::Config(ConfigKey::DonationRef(donor.clone(), client_ref));
+        if let Some(donation_id) = Self::load(&env, &key) {
+            donor.require_auth();
+            return Ok(donation_id);
+        }
+
+        let donation_id = Self::donate(env.clone(), campaign_id, donor, amount, anonymous, mint_nft, message)?;
+        Self::store(&env, &key, &donation_id);
+        Ok(donation_id)
+    }
+
+    /// Donate naming the token being given, failing with `WrongToken` unless
+    /// it is the one the campaign is denominated in
+    pub fn donate_with_token(
+        env: Env,
+        campaign_id: BytesN<32>,
+        donor: Address,
+        token: Address,
+        amount: i128,
+        anonymous: bool,
+        mint_nft: bool,
+        message: String,
+    ) -> Result<BytesN<32>, SaviaError> {
+        Self::require_initialized(&env)?;
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        if token != campaign.token {
+            return Err(SaviaError::WrongToken);
+        }
+
+        Self::donate(env, campaign_id, donor, amount, anonymous, mint_nft, message)
+    }
+
+    /// Get the donations `referrer` has referred
+    pub fn get_referral_stats(env: Env, referrer: Address) -> ReferralStats {
+        Self::load(&env, &DataKey::Config(ConfigKey::ReferralStats(referrer))).unwrap_or_default()
+    }
+
+    /// Register `fundraiser` to raise for a campaign (beneficiary function).
+    /// Donations made through `donate_with_fundraiser` are then credited to
+    /// them.
+    pub fn register_fundraiser(env: Env, campaign_id: BytesN<32>, fundraiser: Address) -> Result<(), SaviaError> {
+        Self::require_initialized(&env)?;
+        let campaign = Self::require_beneficiary(&env, &campaign_id)?;
+
+        let key = DataKey::CampaignData(CampaignKey::FundraiserStats(campaign_id.clone(), fundraiser.clone()));
+        if fundraiser == campaign.beneficiary || env.storage().persistent().has(&key) {
+            return Err(SaviaError::InvalidInput);
+        }
+        let stats = FundraiserStats { registered_at: env.ledger().timestamp(), ..Default::default() };
+        Self::store(&env, &key, &stats);
+
+        publish_between(&env, topics::FUNDRAISER, topics::REGISTERED, campaign_id.clone(), fundraiser.clone(), (campaign_id, fundraiser));
+        Ok(())
+    }
+
+    /// What `fundraiser` has raised for a campaign, if they are registered
+    /// for it
+    pub fn get_fundraiser_stats(env: Env, campaign_id: BytesN<32>, fundraiser: Address) -> Option<FundraiserStats> {
+        Self::load(&env, &DataKey::CampaignData(CampaignKey::FundraiserStats(campaign_id, fundraiser)))
+    }
+
+    /// Set what a fundraiser must raise for a campaign to earn an
+    /// Ambassador badge (admin function)
+    pub fn set_ambassador_thresholds(env: Env, thresholds: AmbassadorThresholds) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if thresholds.min_raised <= 0 || thresholds.min_donations == 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::Config(ConfigKey::AmbassadorThresholds), &thresholds);
+
+        publish(&env, topics::CONFIG, topics::AMBASSADOR, thresholds);
+        Ok(())
+    }
+
+    /// Get what a fundraiser must raise for a campaign to earn an
+    /// Ambassador badge
+    pub fn get_ambassador_thresholds(env: Env) -> AmbassadorThresholds {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::AmbassadorThresholds))
+            .unwrap_or(AmbassadorThresholds {
+                min_raised: DEFAULT_AMBASSADOR_MIN_RAISED,
+                min_donations: DEFAULT_AMBASSADOR_MIN_DONATIONS,
+            })
+    }
+
+    /// Donate, crediting the gift to `fundraiser` if they are registered for
+    /// the campaign. Any other fundraiser, the donor included, is ignored.
+    pub fn donate_with_fundraiser(
+        env: Env,
+        campaign_id: BytesN<32>,
+        donor: Address,
+        amount: i128,
+        anonymous: bool,
+        mint_nft: bool,
+        message: String,
+        fundraiser: Option<Address>,
+    ) -> Result<BytesN<32>, SaviaError> {
+        let donation_id = Self::donate(env.clone(), campaign_id.clone(), donor.clone(), amount, anonymous, mint_nft, message)?;
+
+        if let Some(fundraiser) = fundraiser.filter(|fundraiser| *fundraiser != donor) {
+            let donation: Donation = Self::load(&env, &DataKey::Donation(donation_id.clone()))
+                .ok_or(SaviaError::DonationNotFound)?;
+            Self::credit_fundraiser(&env, &campaign_id, fundraiser, &donation_id, donation.amount)?;
+        }
+        Ok(donation_id)
+    }
+
+    /// Give to several campaigns at once. Every donation is checked before
+    /// any funds move, and the first failing one fails the whole batch.
+    /// Returns the donation ids in order.
+    pub fn donate_batch(
+        env: Env,
+        donor: Address,
+        donations: Vec<(BytesN<32>, i128)>,
+        anonymous: bool,
+        mint_nft: bool,
+    ) -> Result<Vec<BytesN<32>>, SaviaError> {
+        donor.require_auth();
+
+        if donations.is_empty() || donations.len() > MAX_DONATION_BATCH {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let privacy = Privacy::from_anonymous(anonymous);
+        let message = String::from_str(&env, "");
+        for (campaign_id, amount) in donations.iter() {
+            Self::check_donation(&env, &campaign_id, &donor, amount, privacy, &message)?;
+        }
+
+        // Campaigns may be in different tokens, so each gift moves on its own;
+        // a failed transfer reverts the whole batch
+        let mut donation_ids = Vec::new(&env);
+        for (campaign_id, amount) in donations.iter() {
+            // Reload, as an earlier entry may have given to the same campaign
+            let mut campaign = Self::check_donation(&env, &campaign_id, &donor, amount, privacy, &message)?;
+            if Self::campaign_token(&env, &campaign).try_transfer(&donor, &env.current_contract_address(), &amount).is_err() {
+                return Err(SaviaError::InsufficientFunds);
+            }
+            let donation_id = Self::complete_donation(&env, &mut campaign, donor.clone(), amount, privacy, mint_nft, None, None, message.clone())?;
+            donation_ids.push_back(donation_id);
+        }
+        Ok(donation_ids)
+    }
+
+    /// Group campaigns under a theme, each taking `share` basis points of
+    /// every donation to the bundle (admin function). Shares must sum to
+    /// 10000 and the campaigns must all be in the same token.
+    pub fn create_bundle(env: Env, name: String, allocations: Vec<(BytesN<32>, u32)>) -> Result<BytesN<32>, SaviaError> {
+        Self::require_admin(&env)?;
+
+        if name.is_empty() || name.len() > MAX_BUNDLE_NAME_LEN {
+            return Err(SaviaError::InvalidInput);
+        }
+        if allocations.is_empty() || allocations.len() > MAX_BUNDLE_SIZE {
+            return Err(SaviaError::InvalidInput);
+        }
+        let mut token: Option<Address> = None;
+        let mut total_share = 0u32;
+        for (index, (campaign_id, share)) in allocations.iter().enumerate() {
+            let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+                .ok_or(SaviaError::CampaignNotFound)?;
+            if share == 0 || allocations.iter().skip(index + 1).any(|(other, _)| other == campaign_id) {
+                return Err(SaviaError::InvalidInput);
+            }
+            if token.get_or_insert(campaign.token.clone()) != &campaign.token {
+                return Err(SaviaError::WrongToken);
+            }
+            total_share = total_share.saturating_add(share);
+        }
+        if total_share != 10000 {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let counter: u64 = env.storage().instance().get(&DataKey::Config(ConfigKey::BundleCounter)).unwrap_or(0) + 1;
+        env.storage().instance().set(&DataKey::Config(ConfigKey::BundleCounter), &counter);
+        let mut hash_input = name.clone().to_xdr(&env);
+        hash_input.append(&Bytes::from_slice(&env, &counter.to_be_bytes()));
+        let bundle_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+
+        let bundle = Bundle {
+            id: bundle_id.clone(),
+            name: name.clone(),
+            allocations,
+            created_at: env.ledger().timestamp(),
+        };
+        Self::store(&env, &DataKey::Config(ConfigKey::Bundle(bundle_id.clone())), &bundle);
+
+        publish_about(&env, topics::BUNDLE, topics::CREATED, bundle_id.clone(), (bundle_id.clone(), name));
+        Ok(bundle_id)
+    }
+
+    /// Get a bundle
+    pub fn get_bundle(env: Env, bundle_id: BytesN<32>) -> Option<Bundle> {
+        Self::load(&env, &DataKey::Config(ConfigKey::Bundle(bundle_id)))
+    }
+
+    /// Give `amount` to a bundle in one payment. It is split between the
+    /// bundle's campaigns by share, with rounding dust going to the first,
+    /// and recorded as one donation to each. If any campaign can't take its
+    /// part, for instance because it has ended, the whole donation fails.
+    /// A single badge for the bundle is minted at the tier of the total,
+    /// unless the donor is anonymous. Returns the donation ids in
+    /// allocation order.
+    pub fn donate_to_bundle(
+        env: Env,
+        bundle_id: BytesN<32>,
+        donor: Address,
+        amount: i128,
+        anonymous: bool,
+        mint_nft: bool,
+    ) -> Result<Vec<BytesN<32>>, SaviaError> {
+        donor.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let bundle = Self::get_bundle(env.clone(), bundle_id.clone()).ok_or(SaviaError::InvalidInput)?;
+        if amount <= 0 {
+            return Err(SaviaError::InvalidAmount);
+        }
+
+        let mut parts = Vec::new(&env);
+        let mut allocated = 0;
+        for (_, share) in bundle.allocations.iter() {
+            let share = share as i128;
+            let part = amount / 10000 * share + amount % 10000 * share / 10000;
+            parts.push_back(part);
+            allocated += part;
+        }
+        parts.set(0, parts.get_unchecked(0) + amount - allocated);
+
+        let privacy = Privacy::from_anonymous(anonymous);
+        let message = String::from_str(&env, "");
+        let mut token: Option<Address> = None;
+        for ((campaign_id, _), part) in bundle.allocations.iter().zip(parts.iter()) {
+            let campaign = Self::check_donation(&env, &campaign_id, &donor, part, privacy, &message)?;
+            if token.get_or_insert(campaign.token.clone()) != &campaign.token {
+                return Err(SaviaError::WrongToken);
+            }
+        }
+
+        // Every campaign is in the same token, so one payment covers them all
+        let token = token.ok_or(SaviaError::InvalidInput)?;
+        if token::Client::new(&env, &token).try_transfer(&donor, &env.current_contract_address(), &amount).is_err() {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        let mut donation_ids = Vec::new(&env);
+        let mut net_total = 0;
+        for ((campaign_id, _), part) in bundle.allocations.iter().zip(parts.iter()) {
+            let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id))
+                .ok_or(SaviaError::CampaignNotFound)?;
+            let donation_id = Self::complete_donation(&env, &mut campaign, donor.clone(), part, privacy, false, None, None, message.clone())?;
+            let donation: Donation = Self::load(&env, &DataKey::Donation(donation_id.clone()))
+                .ok_or(SaviaError::DonationNotFound)?;
+            net_total += donation.amount;
+            donation_ids.push_back(donation_id);
+        }
+
+        // A badge in a hidden donor's own name would show they gave
+        if mint_nft && !privacy.hides_donor() {
+            Self::mint_donation_nft(env.clone(), donor, bundle_id.clone(), donation_ids.get_unchecked(0), net_total)?;
+        }
+
+        publish_about(&env, topics::BUNDLE, topics::DONATED, bundle_id.clone(), (bundle_id, donation_ids.clone()));
+        Ok(donation_ids)
+    }
+
+    /// Schedule `max_executions` donations of `amount`, one every
+    /// `interval_seconds` starting now. The donor's token allowance to the
+    /// contract is raised to cover every run; anyone may then trigger each
+    /// run with `execute_recurring` once it is due.
+    pub fn create_recurring(
+        env: Env,
+        campaign_id: BytesN<32>,
+        donor: Address,
+        amount: i128,
+        interval_seconds: u64,
+        max_executions: u32,
+    ) -> Result<BytesN<32>, SaviaError> {
+        donor.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        Self::require_accepting(&env, &campaign)?;
+
+        if amount <= 0 {
+            return Err(SaviaError::InvalidAmount);
+        }
+        if interval_seconds == 0 || max_executions == 0 || max_executions > MAX_RECURRING_EXECUTIONS {
+            return Err(SaviaError::InvalidInput);
+        }
+        let total = amount.checked_mul(max_executions as i128).ok_or(SaviaError::Overflow)?;
+
+        let token_client = Self::campaign_token(&env, &campaign);
+        let contract = env.current_contract_address();
+        let allowance = Self::checked_add(token_client.allowance(&donor, &contract), total)?;
+        token_client.approve(&donor, &contract, &allowance, &Self::allowance_expiration(&env));
+        Self::mark_pledged(&env, &campaign_id);
+
+        let counter: u64 = env.storage().instance().get(&DataKey::Config(ConfigKey::RecurringCounter)).unwrap_or(0);
+        let new_counter = counter + 1;
+        env.storage().instance().set(&DataKey::Config(ConfigKey::RecurringCounter), &new_counter);
+
+        let mut hash_input = Bytes::new(&env);
+        hash_input.append(&Bytes::from_slice(&env, campaign_id.to_array().as_slice()));
+        hash_input.append(&donor.clone().to_xdr(&env));
+        hash_input.append(&Bytes::from_slice(&env, &new_counter.to_be_bytes()));
+        let recurring_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+
+        let recurring = RecurringDonation {
+            id: recurring_id.clone(),
+            campaign_id: campaign_id.clone(),
+            donor: donor.clone(),
+            amount,
+            interval: interval_seconds,
+            next_execution: env.ledger().timestamp(),
+            remaining_executions: max_executions,
+        };
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::Recurring(recurring_id.clone())), &recurring);
+        Self::index_push(&env, IndexKey::DonorRecurring(donor.clone()), recurring_id.clone());
+
+        publish_between(&env, topics::DONATION, topics::SCHEDULED, campaign_id.clone(), donor.clone(), (recurring_id.clone(), campaign_id, amount, interval_seconds, max_executions));
+        Ok(recurring_id)
+    }
+
+    /// Run a due recurring donation from the donor's allowance. Anyone may
+    /// call this; returns the id of the donation made.
+    pub fn execute_recurring(env: Env, recurring_id: BytesN<32>) -> Result<BytesN<32>, SaviaError> {
+        let key = DataKey::CampaignData(CampaignKey::Recurring(recurring_id.clone()));
+        let mut recurring: RecurringDonation = env.storage().persistent().get(&key)
+            .ok_or(SaviaError::RecurringNotFound)?;
+
+        if recurring.remaining_executions == 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+        if env.ledger().timestamp() < recurring.next_execution {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let privacy = Privacy::Public;
+        let message = String::from_str(&env, "");
+        let mut campaign = Self::check_donation(&env, &recurring.campaign_id, &recurring.donor, recurring.amount, privacy, &message)?;
+
+        let contract = env.current_contract_address();
+        if Self::campaign_token(&env, &campaign).try_transfer_from(&contract, &recurring.donor, &contract, &recurring.amount).is_err() {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        recurring.next_execution = recurring.next_execution.saturating_add(recurring.interval);
+        recurring.remaining_executions -= 1;
+        env.storage().persistent().set(&key, &recurring);
+
+        let donation_id = Self::complete_donation(&env, &mut campaign, recurring.donor.clone(), recurring.amount, privacy, false, None, None, message)?;
+        publish_between(&env, topics::DONATION, topics::EXECUTED, recurring.campaign_id.clone(), recurring.donor.clone(), (recurring_id, donation_id.clone(), recurring.remaining_executions));
+        Ok(donation_id)
+    }
+
+    /// Stop a recurring donation and release the allowance left for it
+    /// (donor function)
+    pub fn cancel_recurring(env: Env, recurring_id: BytesN<32>) -> Result<(), SaviaError> {
+        let key = DataKey::CampaignData(CampaignKey::Recurring(recurring_id.clone()));
+        let mut recurring: RecurringDonation = env.storage().persistent().get(&key)
+            .ok_or(SaviaError::RecurringNotFound)?;
+
+        recurring.donor.require_auth();
+
+        if recurring.remaining_executions == 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let unused = recurring.amount * recurring.remaining_executions as i128;
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(recurring.campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        let token_client = Self::campaign_token(&env, &campaign);
+        let contract = env.current_contract_address();
+        let allowance = (token_client.allowance(&recurring.donor, &contract) - unused).max(0);
+        token_client.approve(&recurring.donor, &contract, &allowance, &Self::allowance_expiration(&env));
+
+        recurring.remaining_executions = 0;
+        env.storage().persistent().set(&key, &recurring);
+
+        publish_between(&env, topics::DONATION, topics::CANCELLED, recurring.campaign_id, recurring.donor, recurring_id);
+        Ok(())
+    }
+
+    /// Pledge to match donations to a campaign at `ratio_bps` basis points
+    /// of each donation, up to `cap_amount` in total. The cap is moved into
+    /// escrow now; a campaign has at most one pool with funds left.
+    pub fn create_match(
+        env: Env,
+        campaign_id: BytesN<32>,
+        sponsor: Address,
+        ratio_bps: u32,
+        cap_amount: i128,
+    ) -> Result<(), SaviaError> {
+        sponsor.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        Self::require_accepting(&env, &campaign)?;
+
+        if cap_amount <= 0 {
+            return Err(SaviaError::InvalidAmount);
+        }
+        if ratio_bps == 0 || Self::get_match(env.clone(), campaign_id.clone()).is_some_and(|pool| pool.remaining > 0) {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        if Self::campaign_token(&env, &campaign).try_transfer(&sponsor, &env.current_contract_address(), &cap_amount).is_err() {
+            return Err(SaviaError::InsufficientFunds);
+        }
+        Self::mark_pledged(&env, &campaign_id);
+
+        let pool = MatchPool { sponsor, ratio_bps, cap: cap_amount, remaining: cap_amount };
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::Match(campaign_id.clone())), &pool);
+
+        publish_about(&env, topics::CAMPAIGN, topics::MATCH, campaign_id.clone(), (campaign_id, pool.sponsor, ratio_bps, cap_amount));
+        Ok(())
+    }
+
+    /// Get a campaign's match pool, if one was created
+    pub fn get_match(env: Env, campaign_id: BytesN<32>) -> Option<MatchPool> {
+        env.storage().persistent().get(&DataKey::CampaignData(CampaignKey::Match(campaign_id)))
+    }
+
+    /// Return what is left in a match pool to its sponsor once the campaign
+    /// is closed or has ended (sponsor function)
+    pub fn close_match(env: Env, campaign_id: BytesN<32>) -> Result<i128, SaviaError> {
+        let mut pool = Self::get_match(env.clone(), campaign_id.clone()).ok_or(SaviaError::InvalidInput)?;
+
+        pool.sponsor.require_auth();
+
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        if campaign.active && !campaign.has_ended(env.ledger().timestamp()) {
+            return Err(SaviaError::CampaignNotEnded);
+        }
+        if pool.remaining <= 0 {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        let returned = pool.remaining;
+        Self::campaign_token(&env, &campaign).transfer(&env.current_contract_address(), &pool.sponsor, &returned);
+        pool.remaining = 0;
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::Match(campaign_id.clone())), &pool);
+
+        publish_about(&env, topics::CAMPAIGN, topics::WITHDRAWN, campaign_id.clone(), (campaign_id, pool.sponsor, returned));
+        Ok(returned)
+    }
+
+    /// Get a recurring donation schedule
+    pub fn get_recurring(env: Env, recurring_id: BytesN<32>) -> Option<RecurringDonation> {
+        env.storage().persistent().get(&DataKey::CampaignData(CampaignKey::Recurring(recurring_id)))
+    }
+
+    /// List a donor's recurring donations in creation order, finished and
+    /// cancelled ones included
+    pub fn get_recurring_by_donor(
+        env: Env,
+        donor: Address,
+        cursor: Option<Cursor>,
+        limit: u32,
+    ) -> Result<(Vec<RecurringDonation>, Option<Cursor>), SaviaError> {
+        let (ids, next) = Self::index_page(&env, &IndexKey::DonorRecurring(donor), cursor, limit)?;
+
+        let mut schedules = Vec::new(&env);
+        for recurring_id in ids.iter() {
+            if let Some(recurring) = Self::get_recurring(env.clone(), recurring_id) {
+                schedules.push_back(recurring);
+            }
+        }
+        Ok((schedules, next))
+    }
+
+    /// Preview a donation to a campaign: the fee split at the current rate,
+    /// the badge it earns and whether it completes the goal. Fails with the
+    /// same errors `donate` would; changes nothing and needs no auth.
+    pub fn preview_donation(env: Env, campaign_id: BytesN<32>, amount: i128) -> Result<DonationPreview, SaviaError> {
+        Self::require_not_paused(&env)?;
+
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        Self::require_accepting(&env, &campaign)?;
+
+        if amount <= 0 || amount < campaign.min_donation {
+            return Err(SaviaError::InvalidAmount);
+        }
+
+        Self::require_within_cap(&env, &campaign, amount)?;
+
+        let (fee, net) = Self::split_fee(amount, Self::campaign_fee_rate(&env, &campaign).0);
+        Ok(DonationPreview {
+            gross: amount,
+            fee,
+            net,
+            badge_type: Self::get_badge_type(&env, net),
+            completes_goal: campaign.current_amount.saturating_add(net) >= campaign.goal_amount,
+            unverified_headroom: Self::unverified_headroom(&env, &campaign),
+        })
+    }
+
+    /// Get donation details
+    pub fn get_donation(env: Env, donation_id: BytesN<32>) -> Option<Donation> {
+        let donation: Donation = Self::load(&env, &DataKey::Donation(donation_id))?;
+        Some(Self::redact_donation(&env, donation))
+    }
+
+    /// List a campaign's donations in order, redacted like `get_donation`
+    pub fn get_donations_by_campaign(
+        env: Env,
+        campaign_id: BytesN<32>,
+        cursor: Option<Cursor>,
+        limit: u32,
+    ) -> Result<(Vec<Donation>, Option<Cursor>), SaviaError> {
+        let (ids, next) = Self::index_page(&env, &IndexKey::CampaignDonations(campaign_id), cursor, limit)?;
+
+        let mut donations = Vec::new(&env);
+        for donation_id in ids.iter() {
+            if let Some(donation) = Self::get_donation(env.clone(), donation_id) {
+                donations.push_back(donation);
+            }
+        }
+        Ok((donations, next))
+    }
+
+    /// Offset-based form of `get_donations_by_campaign`
+    pub fn get_donations_by_campaign_at(env: Env, campaign_id: BytesN<32>, offset: u32, limit: u32) -> Vec<Donation> {
+        let cursor = Self::cursor_at(&env, &IndexKey::CampaignDonations(campaign_id.clone()), offset);
+        Self::get_donations_by_campaign(env.clone(), campaign_id, Some(cursor), limit)
+            .map(|(donations, _)| donations)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// List a donor's donations in order, leaving out those that hide the
+    /// donor. `offset` counts every donation, so a page can hold fewer than
+    /// `limit`.
+    pub fn get_donations_by_donor(env: Env, donor: Address, offset: u32, limit: u32) -> Vec<Donation> {
+        let mut donations = Vec::new(&env);
+        for donation in Self::donor_donations(&env, &donor, offset, limit).iter() {
+            if !donation.privacy.hides_donor() {
+                donations.push_back(Self::redact_donation(&env, donation));
+            }
+        }
+        donations
+    }
+
+    /// Every donation `donor` made, unredacted; only the donor may read it
+    pub fn get_donations_by_donor_as(env: Env, donor: Address, offset: u32, limit: u32) -> Vec<Donation> {
+        donor.require_auth();
+
+        Self::donor_donations(&env, &donor, offset, limit)
+    }
+
+    /// Page through the donor's donation history in order, anonymous gifts
+    /// included and unredacted; only the donor may read it
+    pub fn get_my_donations(
+        env: Env,
+        donor: Address,
+        cursor: Option<Cursor>,
+        limit: u32,
+    ) -> Result<(Vec<Donation>, Option<Cursor>), SaviaError> {
+        donor.require_auth();
+
+        let (ids, next) = Self::index_page(&env, &IndexKey::DonorDonations(donor), cursor, limit)?;
+
+        let mut donations = Vec::new(&env);
+        for donation_id in ids.iter() {
+            if let Some(donation) = Self::load(&env, &DataKey::Donation(donation_id)) {
+                donations.push_back(donation);
+            }
+        }
+        Ok((donations, next))
+    }
+
+    /// List donations made between `from_ts` and `to_ts` inclusive, oldest
+    /// first and redacted like `get_donation`. The window may span at most
+    /// 31 days; `offset` counts donations inside it.
+    pub fn get_donations_in_range(
+        env: Env,
+        from_ts: u64,
+        to_ts: u64,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<Donation>, SaviaError> {
+        if from_ts > to_ts || to_ts - from_ts > MAX_RANGE_SECONDS {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let mut skipped = 0;
+        let mut donations = Vec::new(&env);
+        for day in from_ts / DAY..=to_ts / DAY {
+            let index = IndexKey::DonationsByDay(day);
+            let mut cursor = None;
+            loop {
+                let (ids, next) = Self::index_page(&env, &index, cursor, MAX_PAGE_SIZE)?;
+                for donation_id in ids.iter() {
+                    let Some(donation) = Self::load::<Donation>(&env, &DataKey::Donation(donation_id)) else {
+                        continue;
+                    };
+                    if donation.timestamp < from_ts || donation.timestamp > to_ts {
+                        continue;
+                    }
+                    if skipped < offset {
+                        skipped += 1;
+                        continue;
+                    }
+                    donations.push_back(Self::redact_donation(&env, donation));
+                    if donations.len() >= limit {
+                        return Ok(donations);
+                    }
+                }
+                match next {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+        }
+        Ok(donations)
+    }
+
+    /// Net amount `donor` has given across all campaigns
+    pub fn get_donor_total(env: Env, donor: Address) -> i128 {
+        Self::get_trust_score(env, donor).map(|trust| trust.total_donated).unwrap_or(0)
+    }
+
+    /// Get the unredacted donation record; `viewer` must be the donor or the admin
+    pub fn get_donation_as(env: Env, donation_id: BytesN<32>, viewer: Address) -> Result<Donation, SaviaError> {
+        viewer.require_auth();
+
+        let donation: Donation = Self::load(&env, &DataKey::Donation(donation_id))
+            .ok_or(SaviaError::DonationNotFound)?;
+
+        let admin: Option<Address> = env.storage().instance().get(&DataKey::Admin);
+        if viewer != donation.donor && Some(viewer) != admin {
+            return Err(SaviaError::Unauthorized);
+        }
+        Ok(donation)
+    }
+
+    /// Return a donation to its donor after an all-or-nothing campaign ended
+    /// below its goal
+    pub fn claim_refund(env: Env, donation_id: BytesN<32>) -> Result<i128, SaviaError> {
+        let mut donation: Donation = Self::load(&env, &DataKey::Donation(donation_id.clone()))
+            .ok_or(SaviaError::DonationNotFound)?;
+
+        donation.donor.require_auth();
+
+        if donation.refunded {
+            return Err(SaviaError::AlreadyRefunded);
+        }
+
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(donation.campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        let refundable = campaign.suspended
+            || (campaign.funding_mode == FundingMode::AllOrNothing
+                && campaign.has_ended(env.ledger().timestamp())
+                && campaign.current_amount < campaign.goal_amount);
+        if !refundable {
+            return Err(SaviaError::RefundNotAllowed);
+        }
+
+        // A suspended campaign may already have paid some funds out
+        if donation.amount > campaign.current_amount - campaign.disbursed_amount {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        Self::campaign_token(&env, &campaign).transfer(
+            &env.current_contract_address(),
+            &donation.donor,
+            &donation.amount,
+        );
+
+        campaign.current_amount -= donation.amount;
+        Self::store(&env, &DataKey::Campaign(donation.campaign_id.clone()), &campaign);
+
+        donation.refunded = true;
+        Self::store(&env, &DataKey::Donation(donation_id.clone()), &donation);
+
+        // The badge no longer reflects a gift; burn it if the donor still holds it
+        if donation.nft_minted && donation.badge_recipient.is_none() {
+            let badge: Option<NFTBadge> = env.storage().persistent()
+                .get::<_, BytesN<32>>(&DataKey::DonorBadge(donation.campaign_id.clone(), donation.donor.clone()))
+                .and_then(|nft_id| Self::load(&env, &DataKey::NFTBadge(nft_id)));
+            if let Some(badge) = badge.filter(|badge| badge.owner == donation.donor) {
+                Self::burn_badge(&env, badge);
+            }
+        }
+
+        let public_donor = if donation.privacy.hides_donor() { env.current_contract_address() } else { donation.donor };
+        let public_amount = if donation.privacy.hides_amount() { None } else { Some(donation.amount) };
+        publish_between(&env, topics::DONATION, topics::REFUNDED, donation.campaign_id.clone(), public_donor, (donation_id, donation.campaign_id, public_amount));
+        Ok(donation.amount)
+    }
+
+    /// Pledge `amount` to a campaign on condition that `counterpart` donates at
+    /// least `min_counterpart_amount` to it before `deadline`. The pledge is
+    /// held in escrow and becomes a donation from the challenger when met.
+    pub fn create_challenge(
+        env: Env,
+        challenger: Address,
+        campaign_id: BytesN<32>,
+        amount: i128,
+        counterpart: Address,
+        min_counterpart_amount: i128,
+        deadline: u64,
+    ) -> Result<BytesN<32>, SaviaError> {
+        challenger.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        Self::require_accepting(&env, &campaign)?;
+
+        let current_time = env.ledger().timestamp();
+
+        if amount <= 0 || min_counterpart_amount <= 0 {
+            return Err(SaviaError::InvalidAmount);
+        }
+
+        if counterpart == challenger || deadline <= current_time {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        if Self::campaign_token(&env, &campaign).try_transfer(&challenger, &env.current_contract_address(), &amount).is_err() {
+            return Err(SaviaError::InsufficientFunds);
+        }
+        Self::mark_pledged(&env, &campaign_id);
+
+        // Get and increment challenge counter
+        let counter: u64 = env.storage().instance().get(&DataKey::ChallengeCounter).unwrap_or(0);
+        let new_counter = counter + 1;
+        env.storage().instance().set(&DataKey::ChallengeCounter, &new_counter);
+
+        // Generate challenge ID
+        let mut hash_input = Bytes::new(&env);
+        hash_input.append(&Bytes::from_slice(&env, campaign_id.to_array().as_slice()));
+        hash_input.append(&challenger.clone().to_xdr(&env));
+        hash_input.append(&counterpart.clone().to_xdr(&env));
+        hash_input.append(&Bytes::from_slice(&env, &amount.to_be_bytes()));
+        hash_input.append(&Bytes::from_slice(&env, &new_counter.to_be_bytes()));
+
+        let challenge_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+
+        let challenge = Challenge {
+            id: challenge_id.clone(),
+            challenger,
+            campaign_id: campaign_id.clone(),
+            amount,
+            counterpart: counterpart.clone(),
+            min_counterpart_amount,
+            deadline,
+            status: ChallengeStatus::Open,
+        };
+        env.storage().persistent().set(&DataKey::Challenge(challenge_id.clone()), &challenge);
+
+        let key = DataKey::OpenChallenges(campaign_id.clone(), counterpart.clone());
+        let mut open: Vec<BytesN<32>> = env.storage().persistent().get(&key).unwrap_or(Vec::new(&env));
+        open.push_back(challenge_id.clone());
+        env.storage().persistent().set(&key, &open);
+
+        publish(&env, topics::CHALLENGE, topics::CREATED, (challenge_id.clone(), campaign_id, counterpart, amount, min_counterpart_amount, deadline));
+        Ok(challenge_id)
+    }
+
+    /// Get challenge details
+    pub fn get_challenge(env: Env, challenge_id: BytesN<32>) -> Option<Challenge> {
+        env.storage().persistent().get(&DataKey::Challenge(challenge_id))
+    }
+
+    /// Return an unmet challenge's pledge to the challenger after its deadline
+    pub fn reclaim_challenge(env: Env, challenge_id: BytesN<32>) -> Result<i128, SaviaError> {
+        let mut challenge: Challenge = env.storage().persistent().get(&DataKey::Challenge(challenge_id.clone()))
+            .ok_or(SaviaError::ChallengeNotFound)?;
+
+        challenge.challenger.require_auth();
+
+        if challenge.status != ChallengeStatus::Open || env.ledger().timestamp() <= challenge.deadline {
+            return Err(SaviaError::ChallengeNotOpen);
+        }
+
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(challenge.campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        Self::campaign_token(&env, &campaign).transfer(
+            &env.current_contract_address(),
+            &challenge.challenger,
+            &challenge.amount,
+        );
+
+        challenge.status = ChallengeStatus::Expired;
+        env.storage().persistent().set(&DataKey::Challenge(challenge_id.clone()), &challenge);
+
+        let key = DataKey::OpenChallenges(challenge.campaign_id.clone(), challenge.counterpart.clone());
+        let open: Vec<BytesN<32>> = env.storage().persistent().get(&key).unwrap_or(Vec::new(&env));
+        let mut remaining = Vec::new(&env);
+        for id in open.iter() {
+            if id != challenge_id {
+                remaining.push_back(id);
+            }
+        }
+        if remaining.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &remaining);
+        }
+
+        publish(&env, topics::CHALLENGE, topics::EXPIRED, (challenge_id, challenge.amount));
+        Ok(challenge.amount)
+    }
+
+    /// Canonical bytes describing a donation, for the donor to sign off-chain
+    /// as proof of donation. Layout (version 2): version byte, contract
+    /// address XDR, network id, donation id, campaign id, donor XDR, amount
+    /// (16-byte i128), timestamp (8-byte u64), nft_minted byte, privacy byte,
+    /// refunded byte. Integers are big-endian.
+    pub fn get_attestation_payload(env: Env, donation_id: BytesN<32>) -> Result<Bytes, SaviaError> {
+        let donation: Donation = Self::load(&env, &DataKey::Donation(donation_id))
+            .ok_or(SaviaError::DonationNotFound)?;
+
+        // The payload names the donor, so only they may fetch it for a hidden donation
+        if donation.privacy.hides_donor() {
+            donation.donor.require_auth();
+        }
+
+        Ok(Self::attestation_payload(&env, &donation))
+    }
+
+    /// Get a tax receipt for a donation. Like the attestation payload, only
+    /// the donor may fetch it for a hidden donation.
+    pub fn get_receipt(env: Env, donation_id: BytesN<32>) -> Result<Receipt, SaviaError> {
+        let donation: Donation = Self::load(&env, &DataKey::Donation(donation_id))
+            .ok_or(SaviaError::DonationNotFound)?;
+
+        if donation.privacy.hides_donor() {
+            donation.donor.require_auth();
+        }
+
+        Self::receipt(&env, donation)
+    }
+
+    /// Check a receipt hash against the donation as currently stored
+    pub fn verify_receipt(env: Env, donation_id: BytesN<32>, receipt_hash: BytesN<32>) -> bool {
+        match Self::load::<Donation>(&env, &DataKey::Donation(donation_id)) {
+            Some(donation) => Self::receipt(&env, donation).is_ok_and(|receipt| receipt.receipt_hash == receipt_hash),
+            None => false,
+        }
+    }
+
+    /// Check that an attestation payload matches the donation as currently stored
+    pub fn verify_attestation(env: Env, donation_id: BytesN<32>, payload: Bytes) -> bool {
+        match Self::load::<Donation>(&env, &DataKey::Donation(donation_id)) {
+            Some(donation) => Self::attestation_payload(&env, &donation) == payload,
+            None => false,
+        }
+    }
+
+    /// Change the platform fee, in basis points (admin function)
+    pub fn set_platform_fee(env: Env, new_fee: u64) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if new_fee > 1000 {
+            return Err(SaviaError::InvalidFee);
+        }
+
+        let old_fee = Self::get_platform_fee(env.clone())?;
+        env.storage().instance().set(&DataKey::PlatformFee, &new_fee);
+
+        publish(&env, topics::FEE, topics::UPDATED, (old_fee, new_fee));
+        Ok(())
+    }
+
+    /// Get the platform fee in basis points, ignoring fee holidays
+    pub fn get_platform_fee(env: Env) -> Result<u64, SaviaError> {
+        env.storage().instance().get(&DataKey::PlatformFee).ok_or(SaviaError::NotInitialized)
+    }
+
+    /// Charge `fee_bps` on donations to one campaign instead of the platform
+    /// fee, fee holidays and waiver included; 0 makes it fee-free (admin
+    /// function)
+    pub fn set_campaign_fee(env: Env, campaign_id: BytesN<32>, fee_bps: u64) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if fee_bps > 1000 {
+            return Err(SaviaError::InvalidFee);
+        }
+        if !env.storage().persistent().has(&DataKey::Campaign(campaign_id.clone())) {
+            return Err(SaviaError::CampaignNotFound);
+        }
+
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::Fee(campaign_id.clone())), &fee_bps);
+
+        publish(&env, topics::FEE, topics::OVERRIDE, (campaign_id, Some(fee_bps)));
+        Ok(())
+    }
+
+    /// Return a campaign to the platform fee (admin function)
+    pub fn clear_campaign_fee(env: Env, campaign_id: BytesN<32>) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        env.storage().persistent().remove(&DataKey::CampaignData(CampaignKey::Fee(campaign_id.clone())));
+
+        publish(&env, topics::FEE, topics::OVERRIDE, (campaign_id, None::<u64>));
+        Ok(())
+    }
+
+    /// Get a campaign's fee override, if one is set
+    pub fn get_campaign_fee(env: Env, campaign_id: BytesN<32>) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::CampaignData(CampaignKey::Fee(campaign_id)))
+    }
+
+    /// Choose whether verified campaigns with a trust score of at least 90
+    /// pay no fee (admin function)
+    pub fn set_verified_fee_waiver(env: Env, enabled: bool) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        env.storage().instance().set(&DataKey::Config(ConfigKey::VerifiedFeeWaiver), &enabled);
+
+        publish(&env, topics::FEE, topics::WAIVER, enabled);
+        Ok(())
+    }
+
+    /// Whether the verified campaign fee waiver is enabled
+    pub fn get_verified_fee_waiver(env: Env) -> bool {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::VerifiedFeeWaiver)).unwrap_or(false)
+    }
+
+    /// Schedule a future window with a reduced platform fee (admin function)
+    pub fn schedule_fee_holiday(env: Env, start: u64, end: u64, fee_bps: u64) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if fee_bps > 1000 {
+            return Err(SaviaError::InvalidFee);
+        }
+
+        let current_time = env.ledger().timestamp();
+        if start < current_time || end <= start {
+            return Err(SaviaError::InvalidDuration);
+        }
+
+        // Drop windows that have already finished before counting
+        let mut holidays = Vec::new(&env);
+        for holiday in Self::get_fee_holidays(env.clone()).iter() {
+            if holiday.end > current_time {
+                if start < holiday.end && holiday.start < end {
+                    return Err(SaviaError::HolidayOverlap);
+                }
+                holidays.push_back(holiday);
+            }
+        }
+
+        if holidays.len() >= MAX_FEE_HOLIDAYS {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        holidays.push_back(FeeHoliday { start, end, fee_bps, used: false });
+        env.storage().instance().set(&DataKey::FeeHolidays, &holidays);
+
+        publish(&env, topics::FEE, topics::SCHEDULED, (start, end, fee_bps));
+        Ok(())
+    }
+
+    /// Set how much detail donation, disbursement and trust events carry (admin function)
+    pub fn set_event_verbosity(env: Env, verbosity: EventVerbosity) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        env.storage().instance().set(&DataKey::EventVerbosity, &verbosity);
+
+        publish(&env, topics::CONFIG, topics::VERBOSITY, verbosity);
+        Ok(())
+    }
+
+    /// Get the configured event verbosity
+    pub fn get_event_verbosity(env: Env) -> EventVerbosity {
+        env.storage().instance().get(&DataKey::EventVerbosity).unwrap_or(EventVerbosity::Standard)
+    }
+
+    /// Get platform fees collected and not yet withdrawn
+    pub fn get_accrued_fees(env: Env) -> i128 {
+        Self::stored_amount(&env, &DataKey::AccruedFees).unwrap_or(0)
+    }
+
+    /// Allow or disallow campaigns to be denominated in `token` (admin
+    /// function). The platform token is always allowed; campaigns already
+    /// in a disallowed token keep it.
+    pub fn allow_token(env: Env, token: Address, allowed: bool) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let key = DataKey::Config(ConfigKey::AllowedToken(token.clone()));
+        if allowed {
+            Self::store(&env, &key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        publish(&env, topics::CONFIG, topics::TOKEN, (token, allowed));
+        Ok(())
+    }
+
+    /// Check whether campaigns may be denominated in `token`
+    pub fn is_token_allowed(env: Env, token: Address) -> bool {
+        env.storage().instance().get(&DataKey::Token) == Some(token.clone())
+            || env.storage().persistent().has(&DataKey::Config(ConfigKey::AllowedToken(token)))
+    }
+
+    /// Get the donation totals for campaigns in `token`
+    pub fn get_token_stats(env: Env, token: Address) -> TokenStats {
+        Self::load(&env, &DataKey::Config(ConfigKey::TokenStats(token))).unwrap_or_default()
+    }
+
+    /// Get fees collected in `token` and not yet withdrawn
+    pub fn get_token_fees(env: Env, token: Address) -> i128 {
+        if env.storage().instance().get(&DataKey::Token) == Some(token.clone()) {
+            return Self::get_accrued_fees(env);
+        }
+        Self::load(&env, &DataKey::Config(ConfigKey::TokenFees(token))).unwrap_or(0)
+    }
+
+    /// Pay out fees collected in `token` (admin function). For the platform
+    /// token this is `withdraw_fees`.
+    pub fn withdraw_token_fees(env: Env, token: Address, to: Address, amount: i128) -> Result<(), SaviaError> {
+        if env.storage().instance().get(&DataKey::Token) == Some(token.clone()) {
+            return Self::withdraw_fees(env, to, amount);
+        }
+        Self::require_admin(&env)?;
+
+        if amount <= 0 {
+            return Err(SaviaError::InvalidAmount);
+        }
+
+        let key = DataKey::Config(ConfigKey::TokenFees(token.clone()));
+        let held: i128 = Self::load(&env, &key).unwrap_or(0);
+        if amount > held {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &to, &amount);
+        Self::store(&env, &key, &(held - amount));
+
+        publish(&env, topics::FEE, topics::WITHDRAWN, (to, amount, token));
+        Ok(())
+    }
+
+    /// Pay out accrued platform fees from escrow (admin function). Shares
+    /// owed to fee recipients can only be claimed by them.
+    pub fn withdraw_fees(env: Env, to: Address, amount: i128) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if amount <= 0 {
+            return Err(SaviaError::InvalidAmount);
+        }
+
+        let accrued = Self::get_accrued_fees(env.clone());
+        let unclaimed: i128 = env.storage().instance().get(&DataKey::Config(ConfigKey::UnclaimedFeeShares)).unwrap_or(0);
+        if amount > accrued - unclaimed {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        let token_client = Self::token_client(&env)?;
+        token_client.transfer(&env.current_contract_address(), &to, &amount);
+        env.storage().instance().set(&DataKey::AccruedFees, &(accrued - amount));
+
+        publish(&env, topics::FEE, topics::WITHDRAWN, (to, amount));
+        Ok(())
+    }
+
+    /// Split every fee collected from now on between `recipients`, each
+    /// `(address, share_bps)` with shares summing to 10000. Each recipient
+    /// claims their share with `claim_fee_share`; rounding dust goes to the
+    /// first. An empty list ends the split (admin function).
+    pub fn set_fee_recipients(env: Env, recipients: Vec<(Address, u32)>) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if recipients.len() > MAX_FEE_RECIPIENTS {
+            return Err(SaviaError::InvalidInput);
+        }
+        let mut total = 0u32;
+        for (index, (recipient, share_bps)) in recipients.iter().enumerate() {
+            if share_bps == 0 || recipients.iter().skip(index + 1).any(|(other, _)| other == recipient) {
+                return Err(SaviaError::InvalidInput);
+            }
+            total = total.saturating_add(share_bps);
+        }
+        if !recipients.is_empty() && total != 10000 {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        env.storage().instance().set(&DataKey::Config(ConfigKey::FeeRecipients), &recipients);
+
+        publish(&env, topics::FEE, topics::RECIPIENTS, recipients);
+        Ok(())
+    }
+
+    /// Get the fee split, empty if fees are not split
+    pub fn get_fee_recipients(env: Env) -> Vec<(Address, u32)> {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::FeeRecipients)).unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the fees `recipient` can claim
+    pub fn get_fee_share(env: Env, recipient: Address) -> i128 {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::FeeShare(recipient))).unwrap_or(0)
+    }
+
+    /// Pay out the fees owed to `recipient` (recipient function). Returns the
+    /// amount paid.
+    pub fn claim_fee_share(env: Env, recipient: Address) -> Result<i128, SaviaError> {
+        recipient.require_auth();
+        Self::require_initialized(&env)?;
+
+        let share = Self::get_fee_share(env.clone(), recipient.clone());
+        if share <= 0 {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        Self::token_client(&env)?.transfer(&env.current_contract_address(), &recipient, &share);
+
+        let storage = env.storage().instance();
+        storage.remove(&DataKey::Config(ConfigKey::FeeShare(recipient.clone())));
+        let unclaimed: i128 = storage.get(&DataKey::Config(ConfigKey::UnclaimedFeeShares)).unwrap_or(0);
+        storage.set(&DataKey::Config(ConfigKey::UnclaimedFeeShares), &(unclaimed - share));
+        storage.set(&DataKey::AccruedFees, &(Self::get_accrued_fees(env.clone()) - share));
+
+        publish(&env, topics::FEE, topics::CLAIMED, (recipient, share));
+        Ok(share)
+    }
+
+    /// Get platform-wide totals
+    pub fn get_platform_stats(env: Env) -> PlatformStats {
+        let storage = env.storage().instance();
+        let minted: u64 = storage.get(&DataKey::NFTCounter).unwrap_or(0);
+        let burned: u64 = storage.get(&DataKey::Config(ConfigKey::BurnedNfts)).unwrap_or(0);
+        PlatformStats {
+            total_campaigns: storage.get(&DataKey::CampaignCounter).unwrap_or(0),
+            active_campaigns: storage.get(&DataKey::ActiveCampaignCounter).unwrap_or(0),
+            total_donations: storage.get(&DataKey::DonationCounter).unwrap_or(0),
+            total_fees_collected: Self::stored_amount(&env, &DataKey::TotalFeesCollected).unwrap_or(0),
+            total_nfts: minted - burned,
+            total_raised: storage.get(&DataKey::Config(ConfigKey::TotalRaised)).unwrap_or(0),
+        }
+    }
+
+    /// Get the totals for campaigns of `category`. `total_nfts` is not
+    /// tracked per category.
+    pub fn get_category_stats(env: Env, category: Category) -> PlatformStats {
+        Self::load(&env, &DataKey::Config(ConfigKey::CategoryStats(category))).unwrap_or_default()
+    }
+
+    /// Get the totals for the 30-day period `epoch`, the one holding
+    /// timestamps `epoch * 2_592_000` up to the next. Campaigns count in the
+    /// period they were created; `active_campaigns` and `total_nfts` are not
+    /// tracked per period.
+    pub fn get_period_stats(env: Env, epoch: u64) -> PlatformStats {
+        Self::load(&env, &DataKey::Config(ConfigKey::PeriodStats(epoch))).unwrap_or_default()
+    }
+
+    /// Get scheduled fee holidays
+    pub fn get_fee_holidays(env: Env) -> Vec<FeeHoliday> {
+        env.storage().instance().get(&DataKey::FeeHolidays).unwrap_or(Vec::new(&env))
+    }
+
+    /// Quote the fee a donation of `amount` would pay right now
+    pub fn quote_donation(env: Env, amount: i128) -> DonationQuote {
+        let (fee_bps, _) = Self::effective_fee_rate(&env);
+        let (fee, net) = Self::split_fee(amount, fee_bps);
+        let disclosure_required = Self::get_kyc_disclosure_threshold(env.clone())
+            .is_some_and(|threshold| amount >= threshold);
+        DonationQuote { gross: amount, fee_bps, fee, net, disclosure_required }
+    }
+
+    /// Quote a donation from `donor` to a specific campaign; unlike
+    /// `quote_donation` the fee honours the campaign's override or waiver
+    /// and the disclosure flag counts what the donor has already given to
+    /// the campaign
+    pub fn quote_donation_for(env: Env, campaign_id: BytesN<32>, donor: Address, amount: i128) -> DonationQuote {
+        let mut quote = Self::quote_donation(env.clone(), amount);
+        let campaign: Option<Campaign> = Self::load(&env, &DataKey::Campaign(campaign_id.clone()));
+        if let Some(campaign) = campaign {
+            quote.fee_bps = Self::campaign_fee_rate(&env, &campaign).0;
+            (quote.fee, quote.net) = Self::split_fee(amount, quote.fee_bps);
+        }
+        quote.disclosure_required = Self::disclosure_required(&env, &campaign_id, &donor, amount);
+        quote
+    }
+
+    /// Set the cumulative per-campaign amount from which a donor can no
+    /// longer hide their identity; `None` lifts the requirement (admin function)
+    pub fn set_kyc_disclosure_threshold(env: Env, threshold: Option<i128>) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        match threshold {
+            Some(value) if value <= 0 => return Err(SaviaError::InvalidAmount),
+            Some(value) => env.storage().instance().set(&DataKey::KycDisclosureThreshold, &value),
+            None => env.storage().instance().remove(&DataKey::KycDisclosureThreshold),
+        }
+
+        publish(&env, topics::CONFIG, topics::KYC_THRESHOLD, threshold);
+        Ok(())
+    }
+
+    /// Get the identity disclosure threshold, if one is configured
+    pub fn get_kyc_disclosure_threshold(env: Env) -> Option<i128> {
+        Self::stored_amount(&env, &DataKey::KycDisclosureThreshold)
+    }
+
+    /// Initialize trust score for new user
+    pub fn initialize_trust_score(env: Env, entity: Address) -> Result<(), SaviaError> {
+        Self::require_initialized(&env)?;
+        if env.storage().persistent().has(&DataKey::TrustScore(entity.clone())) {
+            return Err(SaviaError::ScoreExists);
+        }
+
+        let trust_score = Self::neutral_trust_score(&env, &entity);
+
+        Self::store(&env, &DataKey::TrustScore(entity.clone()), &trust_score);
+
+        Self::publish_trust_event(&env, topics::CREATED, NEUTRAL_TRUST_SCORE, &trust_score, TrustChangeReason::Created);
+        Ok(())
+    }
+
+    /// A fresh record with the neutral score
+    fn neutral_trust_score(env: &Env, entity: &Address) -> TrustScore {
+        TrustScore {
+            entity: entity.clone(),
+            score: NEUTRAL_TRUST_SCORE,
+            verification_level: 0,
+            donation_count: 0,
+            total_donated: 0,
+            campaigns_created: 0,
+            last_updated: env.ledger().timestamp(),
+            adjustment: 0,
+            evidence_hash: BytesN::from_array(env, &[0; 32]),
+            current_streak_months: 0,
+            longest_streak: 0,
+            streak_started_at: 0,
+            last_donation_at: 0,
+            decay_applied: 0,
+            campaigns_completed: 0,
+            campaigns_failed: 0,
+        }
+    }
+
+    /// Apply a clamped adjustment to an entity's trust score, starting from
+    /// a neutral record if it has none, and announce it with `reason`
+    fn apply_trust_adjustment(env: &Env, entity: &Address, delta: i32, reason: String, cause: TrustChangeReason) -> u32 {
+        let mut trust_score: TrustScore = Self::load(env, &DataKey::TrustScore(entity.clone()))
+            .unwrap_or_else(|| Self::neutral_trust_score(env, entity));
+
+        let old_score = trust_score.score;
+        Self::apply_decay(env, &mut trust_score);
+        let previous = trust_score.score as i64;
+        trust_score.score = (previous + delta as i64).clamp(0, 100) as u32;
+        let applied = (trust_score.score as i64 - previous) as i32;
+        trust_score.adjustment = trust_score.adjustment.saturating_add(applied);
+        trust_score.last_updated = env.ledger().timestamp();
+        Self::store(env, &DataKey::TrustScore(entity.clone()), &trust_score);
+
+        publish_about(env, topics::TRUST, topics::ADJUSTED, entity.clone(), (entity.clone(), applied, trust_score.score, reason));
+        Self::publish_trust_event(env, topics::UPDATED, old_score, &trust_score, cause);
+        trust_score.score
+    }
+
+    /// Lower a score above neutral by the decay for each whole period since
+    /// `last_updated`, once the grace has passed. `last_updated` moves
+    /// forward by the periods applied, so applying twice is a no-op.
+    /// Returns whether anything changed.
+    fn apply_decay(env: &Env, trust_score: &mut TrustScore) -> bool {
+        let decay = Self::get_trust_decay(env.clone());
+        let elapsed = env.ledger().timestamp().saturating_sub(trust_score.last_updated);
+        if elapsed <= decay.grace || trust_score.score <= NEUTRAL_TRUST_SCORE || decay.points == 0 {
+            return false;
+        }
+
+        let periods = elapsed / decay.period;
+        let lost = (periods.saturating_mul(decay.points as u64)).min(100) as u32;
+        let decayed = trust_score.score.saturating_sub(lost).max(NEUTRAL_TRUST_SCORE);
+        trust_score.decay_applied += trust_score.score - decayed;
+        trust_score.score = decayed;
+        trust_score.last_updated += periods * decay.period;
+        true
+    }
+
+    /// Update donor trust score
+    /// Count a donation toward the donor's score. A gift that hides its donor
+    /// updates the score without an event naming them.
+    fn update_donor_trust_score(env: Env, donor: Address, amount: i128, hidden: bool) -> Result<(), SaviaError> {
+        let mut trust_score: TrustScore = Self::load(&env, &DataKey::TrustScore(donor.clone()))
+            .unwrap_or_else(|| Self::neutral_trust_score(&env, &donor));
+        let old_score = trust_score.score;
+
+        let now = env.ledger().timestamp();
+        if trust_score.current_streak_months == 0 || now.saturating_sub(trust_score.last_donation_at) > STREAK_MAX_GAP {
+            trust_score.streak_started_at = now;
+        }
+        trust_score.current_streak_months = (now.saturating_sub(trust_score.streak_started_at) / STREAK_MONTH) as u32 + 1;
+        trust_score.longest_streak = trust_score.longest_streak.max(trust_score.current_streak_months);
+        trust_score.last_donation_at = now;
+
+        trust_score.donation_count += 1;
+        trust_score.total_donated = Self::checked_add(trust_score.total_donated, amount)?;
+        trust_score.last_updated = now;
+
+        Self::recalculate_trust_score(&mut trust_score);
+        Self::store(&env, &DataKey::TrustScore(donor.clone()), &trust_score);
+
+        if !hidden {
+            Self::publish_trust_event(&env, topics::UPDATED, old_score, &trust_score, TrustChangeReason::Donation);
+        }
+        Ok(())
+    }
+
+    /// Count a finalized campaign's outcome towards its beneficiary's score.
+    /// Campaigns between a quarter of their goal and the goal don't count.
+    fn update_beneficiary_trust_score(env: &Env, campaign: &Campaign) {
+        let failed_below = campaign.goal_amount / 10000 * CAMPAIGN_FAILED_BPS + campaign.goal_amount % 10000 * CAMPAIGN_FAILED_BPS / 10000;
+        let failed = campaign.current_amount < failed_below;
+        if !campaign.goal_reached && !failed {
+            return;
+        }
+
+        let mut trust_score: TrustScore = Self::load(env, &DataKey::TrustScore(campaign.beneficiary.clone()))
+            .unwrap_or_else(|| Self::neutral_trust_score(env, &campaign.beneficiary));
+        let old_score = trust_score.score;
+        if campaign.goal_reached {
+            trust_score.campaigns_completed += 1;
+        } else {
+            trust_score.campaigns_failed += 1;
+        }
+        trust_score.last_updated = env.ledger().timestamp();
+
+        Self::recalculate_trust_score(&mut trust_score);
+        Self::store(env, &DataKey::TrustScore(campaign.beneficiary.clone()), &trust_score);
+
+        Self::publish_trust_event(env, topics::UPDATED, old_score, &trust_score, TrustChangeReason::CampaignOutcome);
+    }
+
+    /// Count a new campaign towards its beneficiary's record. The score
+    /// itself only moves by any pending decay.
+    fn record_campaign_created(env: &Env, beneficiary: &Address) {
+        let mut trust_score: TrustScore = Self::load(env, &DataKey::TrustScore(beneficiary.clone()))
+            .unwrap_or_else(|| Self::neutral_trust_score(env, beneficiary));
+        let old_score = trust_score.score;
+        Self::apply_decay(env, &mut trust_score);
+        trust_score.campaigns_created += 1;
+        Self::store(env, &DataKey::TrustScore(beneficiary.clone()), &trust_score);
+
+        Self::publish_trust_event(env, topics::UPDATED, old_score, &trust_score, TrustChangeReason::CampaignCreated);
+    }
+
+    /// Recompute a score from the record's history, clearing any decay
+    fn recalculate_trust_score(trust_score: &mut TrustScore) {
+        trust_score.decay_applied = 0;
+        trust_score.score = Self::score_breakdown(trust_score).total();
+    }
+
+    /// Split a trust record into the components of its score. `score` is
+    /// copied from the record.
+    fn score_breakdown(trust_score: &TrustScore) -> ScoreBreakdown {
+        let donation_factor = trust_score.donation_count.min(100);
+        let amount_factor = trust_score.total_donated.clamp(0, 100000) as u64;
+        let streak_bonus = (trust_score.current_streak_months.saturating_sub(1) as u64 * STREAK_BONUS_PER_MONTH).min(MAX_STREAK_BONUS);
+
+        let amount_points = (20 * amount_factor / 100000) as u32;
+        let completed_points = (trust_score.campaigns_completed as i32).saturating_mul(CAMPAIGN_COMPLETED_POINTS);
+        let failed_points = (trust_score.campaigns_failed as i32).saturating_mul(CAMPAIGN_FAILED_POINTS);
+        ScoreBreakdown {
+            score: trust_score.score,
+            base: NEUTRAL_TRUST_SCORE,
+            donation_points: 25 * donation_factor / 100,
+            amount_points,
+            streak_bonus: (amount_points as u64 * streak_bonus / 100) as u32,
+            campaign_points: completed_points.min(MAX_CAMPAIGN_COMPLETED_POINTS) - failed_points,
+            verification_bonus: trust_score.verification_level * VERIFICATION_LEVEL_BONUS,
+            adjustment: trust_score.adjustment,
+            decay: trust_score.decay_applied,
+        }
+    }
+
+    /// Raise or lower an entity's trust score by `delta`, clamped to 0-100
+    /// (admin function). The adjustment persists across later score
+    /// recalculations. Returns the new score.
+    pub fn adjust_trust_score(env: Env, entity: Address, delta: i32, reason: String) -> Result<u32, SaviaError> {
+        Self::require_admin(&env)?;
+
+        Ok(Self::apply_trust_adjustment(&env, &entity, delta, reason, TrustChangeReason::AdminAdjustment))
+    }
+
+    /// Record an entity's KYC tier, 0-3 for unverified, email, identity and
+    /// organization, with a hash of the evidence (admin function). The score
+    /// moves by the change in the verification bonus.
+    pub fn set_verification_level(env: Env, entity: Address, level: u32, evidence_hash: BytesN<32>) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if level > MAX_VERIFICATION_LEVEL {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let mut trust_score: TrustScore = Self::load(&env, &DataKey::TrustScore(entity.clone()))
+            .unwrap_or_else(|| Self::neutral_trust_score(&env, &entity));
+        let old_score = trust_score.score;
+        Self::apply_decay(&env, &mut trust_score);
+
+        let bonus_change = (level as i64 - trust_score.verification_level as i64) * VERIFICATION_LEVEL_BONUS as i64;
+        trust_score.score = (trust_score.score as i64 + bonus_change).clamp(0, 100) as u32;
+        trust_score.verification_level = level;
+        trust_score.evidence_hash = evidence_hash.clone();
+        trust_score.last_updated = env.ledger().timestamp();
+        Self::store(&env, &DataKey::TrustScore(entity.clone()), &trust_score);
+
+        // Identity checks cover the name the entity currently shows
+        let profile_key = DataKey::Config(ConfigKey::Profile(entity.clone()));
+        if let Some(mut profile) = Self::load::<Profile>(&env, &profile_key) {
+            profile.name_verified = level >= NAME_VERIFICATION_LEVEL;
+            Self::store(&env, &profile_key, &profile);
+        }
+
+        publish_about(&env, topics::TRUST, topics::VERIFIED, entity.clone(), (entity, level, evidence_hash));
+        Self::publish_trust_event(&env, topics::UPDATED, old_score, &trust_score, TrustChangeReason::Verification);
+        Ok(())
+    }
+
+    /// Create or replace `entity`'s public profile. The display name is
+    /// required; a changed profile loses its verified name until the admin
+    /// verifies it again.
+    pub fn register_profile(
+        env: Env,
+        entity: Address,
+        display_name: String,
+        bio_uri: String,
+        website: String,
+    ) -> Result<(), SaviaError> {
+        entity.require_auth();
+        Self::require_initialized(&env)?;
+
+        if display_name.is_empty() || display_name.len() > MAX_DISPLAY_NAME_LEN {
+            return Err(SaviaError::InvalidInput);
+        }
+        if bio_uri.len() > MAX_PROFILE_URI_LEN || website.len() > MAX_PROFILE_URI_LEN {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let profile = Profile {
+            display_name: display_name.clone(),
+            bio_uri,
+            website,
+            name_verified: false,
+            updated_at: env.ledger().timestamp(),
+        };
+        Self::store(&env, &DataKey::Config(ConfigKey::Profile(entity.clone())), &profile);
+
+        publish_about(&env, topics::PROFILE, topics::UPDATED, entity.clone(), display_name);
+        Ok(())
+    }
+
+    /// Set the most an unverified campaign may raise, net of fees; 0
+    /// disables the cap (admin function)
+    pub fn set_unverified_raise_cap(env: Env, cap: i128) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if cap < 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::Config(ConfigKey::UnverifiedRaiseCap), &cap);
+
+        publish(&env, topics::CONFIG, topics::UNVERIFIED_CAP, cap);
+        Ok(())
+    }
+
+    /// Get the most an unverified campaign may raise
+    pub fn get_unverified_raise_cap(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::UnverifiedRaiseCap))
+            .unwrap_or(DEFAULT_UNVERIFIED_RAISE_CAP)
+    }
+
+    /// Set how long after a campaign ends it can be archived (admin function)
+    pub fn set_archive_grace_period(env: Env, seconds: u64) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        env.storage().instance().set(&DataKey::Config(ConfigKey::ArchiveGracePeriod), &seconds);
+
+        publish(&env, topics::CONFIG, topics::ARCHIVE_GRACE, seconds);
+        Ok(())
+    }
+
+    /// Get how long after a campaign ends it can be archived
+    pub fn get_archive_grace_period(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::ArchiveGracePeriod))
+            .unwrap_or(DEFAULT_ARCHIVE_GRACE_PERIOD)
+    }
+
+    /// Get an entity's public profile
+    pub fn get_profile(env: Env, entity: Address) -> Option<Profile> {
+        Self::load(&env, &DataKey::Config(ConfigKey::Profile(entity)))
+    }
+
+    /// Require campaign creators to have at least verification `level`; 0
+    /// lifts the requirement (admin function)
+    pub fn set_min_creator_level(env: Env, level: u32) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if level > MAX_VERIFICATION_LEVEL {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::Config(ConfigKey::MinCreatorLevel), &level);
+
+        publish(&env, topics::CONFIG, topics::MIN_LEVEL, level);
+        Ok(())
+    }
+
+    /// Get the verification level campaign creators must have
+    pub fn get_min_creator_level(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::MinCreatorLevel)).unwrap_or(0)
+    }
+
+    /// Set how many campaigns each beneficiary may run and create (admin
+    /// function). Creations past either limit fail with `RateLimited`.
+    pub fn set_creation_limits(env: Env, limits: CreationLimits) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        env.storage().instance().set(&DataKey::Config(ConfigKey::CreationLimits), &limits);
+
+        publish(&env, topics::CONFIG, topics::CREATION_LIMITS, limits);
+        Ok(())
+    }
+
+    /// Get the campaign creation limits
+    pub fn get_creation_limits(env: Env) -> CreationLimits {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::CreationLimits)).unwrap_or(CreationLimits {
+            max_active: DEFAULT_MAX_ACTIVE_CAMPAIGNS,
+            max_per_day: DEFAULT_MAX_CREATIONS_PER_DAY,
+        })
+    }
+
+    /// Exempt a verified address from the creation limits, or lift the
+    /// exemption (admin function)
+    pub fn set_creation_exempt(env: Env, addr: Address, exempt: bool) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let key = DataKey::Config(ConfigKey::CreationExempt(addr.clone()));
+        if exempt {
+            let level = Self::get_trust_score(env.clone(), addr.clone())
+                .map(|trust| trust.verification_level)
+                .unwrap_or(0);
+            if level == 0 {
+                return Err(SaviaError::VerificationRequired);
+            }
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        publish(&env, topics::ADMIN, topics::EXEMPT, (addr, exempt));
+        Ok(())
+    }
+
+    /// Whether `addr` is exempt from the creation limits
+    pub fn is_creation_exempt(env: Env, addr: Address) -> bool {
+        env.storage().persistent().get(&DataKey::Config(ConfigKey::CreationExempt(addr))).unwrap_or(false)
+    }
+
+    /// Set the trust score points a beneficiary loses when their campaign
+    /// is suspended; 0 disables the penalty (admin function)
+    pub fn set_suspension_penalty(env: Env, penalty: u32) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if penalty > 100 {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::Config(ConfigKey::SuspensionPenalty), &penalty);
+
+        publish(&env, topics::CONFIG, topics::PENALTY, penalty);
+        Ok(())
+    }
+
+    /// Get the trust score penalty for a suspended campaign's beneficiary
+    pub fn get_suspension_penalty(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::SuspensionPenalty))
+            .unwrap_or(DEFAULT_SUSPENSION_PENALTY)
+    }
+
+    /// Get trust score, with any inactivity decay applied
+    pub fn get_trust_score(env: Env, entity: Address) -> Option<TrustScore> {
+        let mut trust_score: TrustScore = Self::load(&env, &DataKey::TrustScore(entity))?;
+        Self::apply_decay(&env, &mut trust_score);
+        if env.ledger().timestamp().saturating_sub(trust_score.last_donation_at) > STREAK_MAX_GAP {
+            trust_score.current_streak_months = 0;
+        }
+        Some(trust_score)
+    }
+
+    /// Explain an entity's trust score, with any inactivity decay applied
+    pub fn explain_trust_score(env: Env, entity: Address) -> Option<ScoreBreakdown> {
+        let mut trust_score: TrustScore = Self::load(&env, &DataKey::TrustScore(entity))?;
+        Self::apply_decay(&env, &mut trust_score);
+        Some(Self::score_breakdown(&trust_score))
+    }
+
+    /// Store the inactivity decay of an entity's trust score. Anyone may
+    /// call this; returns the refreshed score.
+    pub fn refresh_trust_score(env: Env, entity: Address) -> Result<u32, SaviaError> {
+        Self::require_initialized(&env)?;
+        let mut trust_score: TrustScore = Self::load(&env, &DataKey::TrustScore(entity.clone()))
+            .ok_or(SaviaError::InvalidInput)?;
+
+        let previous = trust_score.score;
+        if Self::apply_decay(&env, &mut trust_score) {
+            Self::store(&env, &DataKey::TrustScore(entity.clone()), &trust_score);
+            publish_about(&env, topics::TRUST, topics::DECAYED, entity.clone(), (entity, previous, trust_score.score));
+            Self::publish_trust_event(&env, topics::UPDATED, previous, &trust_score, TrustChangeReason::Decay);
+        }
+        Ok(trust_score.score)
+    }
+
+    /// Set how trust scores decay after inactivity (admin function)
+    pub fn set_trust_decay(env: Env, decay: TrustDecay) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if decay.period == 0 || decay.points > 100 {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::Config(ConfigKey::TrustDecay), &decay);
+
+        publish(&env, topics::CONFIG, topics::DECAY, decay);
+        Ok(())
+    }
+
+    /// Get the trust score decay policy
+    pub fn get_trust_decay(env: Env) -> TrustDecay {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::TrustDecay)).unwrap_or(TrustDecay {
+            grace: DEFAULT_TRUST_DECAY_GRACE,
+            period: DEFAULT_TRUST_DECAY_PERIOD,
+            points: DEFAULT_TRUST_DECAY_POINTS,
+        })
+    }
+
+    /// Seed trust scores from a previous deployment (admin function). Only
+    /// allowed during the bootstrap window after initialize; entities that
+    /// already have a record are skipped and scores are capped at 90. An
+    /// entry dated in the future or above the highest verification level
+    /// fails the whole batch. Returns how many records were written.
+    pub fn import_trust_scores(env: Env, entries: Vec<TrustScore>) -> Result<u32, SaviaError> {
+        Self::require_admin(&env)?;
+
+        let initialized_at: u64 = env.storage().instance().get(&DataKey::InitializedAt)
+            .ok_or(SaviaError::NotInitialized)?;
+        let window: u64 = env.storage().instance().get(&DataKey::BootstrapWindow).unwrap_or(DEFAULT_BOOTSTRAP_WINDOW);
+        if env.ledger().timestamp() > initialized_at.saturating_add(window) {
+            return Err(SaviaError::BootstrapClosed);
+        }
+
+        if entries.len() > MAX_IMPORT_BATCH {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let now = env.ledger().timestamp();
+        let mut imported = 0;
+        for mut entry in entries.iter() {
+            if entry.verification_level > MAX_VERIFICATION_LEVEL
+                || entry.last_updated > now
+                || entry.last_donation_at > now
+                || entry.streak_started_at > now
+            {
+                return Err(SaviaError::InvalidInput);
+            }
+            let key = DataKey::TrustScore(entry.entity.clone());
+            if env.storage().persistent().has(&key) {
+                continue;
+            }
+
+            entry.score = entry.score.min(MAX_IMPORTED_SCORE);
+            Self::store(&env, &key, &entry);
+            imported += 1;
+
+            Self::publish_trust_event(&env, topics::UPDATED, NEUTRAL_TRUST_SCORE, &entry, TrustChangeReason::Created);
+            publish_about(&env, topics::TRUST, topics::IMPORTED, entry.entity.clone(), (entry.entity, entry.score));
+        }
+
+        Ok(imported)
+    }
+
+    /// Set how long after initialize trust scores can be imported (admin function)
+    pub fn set_bootstrap_window(env: Env, seconds: u64) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        env.storage().instance().set(&DataKey::BootstrapWindow, &seconds);
+
+        publish(&env, topics::CONFIG, topics::BOOTSTRAP, seconds);
+        Ok(())
+    }
+
+    /// Set how long before an underfunded campaign's end new disbursement
+    /// requests are blocked (admin function)
+    pub fn set_endgame_window(env: Env, seconds: u64) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        env.storage().instance().set(&DataKey::EndgameWindow, &seconds);
+
+        publish(&env, topics::CONFIG, topics::ENDGAME, seconds);
+        Ok(())
+    }
+
+    /// Set how long new disbursement requests stay executable, in seconds
+    /// (admin function)
+    pub fn set_disbursement_validity(env: Env, seconds: u64) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if seconds == 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::Config(ConfigKey::DisbursementValidity), &seconds);
+
+        publish(&env, topics::CONFIG, topics::VALIDITY, seconds);
+        Ok(())
+    }
+
+    /// Get how long new disbursement requests stay executable, in seconds
+    pub fn get_disbursement_validity(env: Env) -> u64 {
+        env.storage().instance()
+            .get(&DataKey::Config(ConfigKey::DisbursementValidity))
+            .unwrap_or(DEFAULT_DISBURSEMENT_VALIDITY)
+    }
+
+    /// Get the endgame disbursement lock window in seconds
+    pub fn get_endgame_window(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::EndgameWindow).unwrap_or(DEFAULT_ENDGAME_WINDOW)
+    }
+
+    /// Set the TTL policy for records: whenever a record is read or written
+    /// with fewer than `threshold` ledgers left, it is extended to `extend_to`
+    /// ledgers (admin function)
+    pub fn set_ttl_config(env: Env, threshold: u32, extend_to: u32) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if extend_to == 0 || threshold > extend_to || extend_to > env.storage().max_ttl() {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::Config(ConfigKey::Ttl), &(threshold, extend_to));
+        env.storage().instance().extend_ttl(threshold, extend_to);
+
+        publish(&env, topics::CONFIG, topics::TTL, (threshold, extend_to));
+        Ok(())
+    }
+
+    /// Get the record TTL policy as `(threshold, extend_to)` in ledgers
+    pub fn get_ttl_config(env: Env) -> (u32, u32) {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::Ttl))
+            .unwrap_or((DEFAULT_TTL_THRESHOLD, DEFAULT_TTL_EXTEND_TO))
+    }
+
+    /// Allow a campaign category (admin function)
+    pub fn add_category(env: Env, category: Category) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let mut categories = Self::get_categories(env.clone());
+        if categories.contains(category) {
+            return Err(SaviaError::InvalidInput);
+        }
+        categories.push_back(category);
+        env.storage().instance().set(&DataKey::Categories, &categories);
+
+        publish(&env, topics::CONFIG, topics::CATEGORY_ADDED, category);
+        Ok(())
+    }
+
+    /// Remove a category from the allowlist along with its labels (admin function)
+    pub fn remove_category(env: Env, category: Category) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let mut categories = Self::get_categories(env.clone());
+        let index = categories.first_index_of(category)
+            .ok_or(SaviaError::InvalidInput)?;
+        categories.remove(index);
+        env.storage().instance().set(&DataKey::Categories, &categories);
+
+        let langs_key = DataKey::CategoryLangs(category);
+        let langs: Vec<Symbol> = env.storage().persistent().get(&langs_key).unwrap_or(Vec::new(&env));
+        for lang in langs.iter() {
+            env.storage().persistent().remove(&DataKey::CategoryLabel(category, lang));
+        }
+        env.storage().persistent().remove(&langs_key);
+
+        publish(&env, topics::CONFIG, topics::CATEGORY_REMOVED, category);
+        Ok(())
+    }
+
+    /// Get the category allowlist
+    pub fn get_categories(env: Env) -> Vec<Category> {
+        env.storage().instance().get(&DataKey::Categories).unwrap_or(Vec::new(&env))
+    }
+
+    /// Set the display label of an allowed category in language `lang` (admin function)
+    pub fn set_category_label(env: Env, category: Category, lang: Symbol, label: String) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if !Self::get_categories(env.clone()).contains(category) {
+            return Err(SaviaError::InvalidInput);
+        }
+        if label.is_empty() || label.len() > MAX_LABEL_LEN {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let langs_key = DataKey::CategoryLangs(category);
+        let mut langs: Vec<Symbol> = env.storage().persistent().get(&langs_key).unwrap_or(Vec::new(&env));
+        if !langs.contains(&lang) {
+            langs.push_back(lang.clone());
+            env.storage().persistent().set(&langs_key, &langs);
+        }
+        env.storage().persistent().set(&DataKey::CategoryLabel(category, lang.clone()), &label);
+
+        publish(&env, topics::CONFIG, topics::LABEL, (category, lang, label));
+        Ok(())
+    }
+
+    /// Get a category's label in language `lang`, if one is set
+    pub fn get_category_label(env: Env, category: Category, lang: Symbol) -> Option<String> {
+        env.storage().persistent().get(&DataKey::CategoryLabel(category, lang))
+    }
+
+    /// Every allowed category paired with its label in `lang`, falling back
+    /// to the category name where no label is set
+    pub fn get_categories_localized(env: Env, lang: Symbol) -> Vec<(Category, String)> {
+        let mut localized = Vec::new(&env);
+        for category in Self::get_categories(env.clone()).iter() {
+            let label = Self::get_category_label(env.clone(), category, lang.clone())
+                .unwrap_or(String::from_str(&env, category.name()));
+            localized.push_back((category, label));
+        }
+        localized
+    }
+
+    /// Mint donation NFT
+    fn mint_donation_nft(
+        env: Env,
+        owner: Address,
+        campaign_id: BytesN<32>,
+        donation_id: BytesN<32>,
+        amount: i128,
+    ) -> Result<BytesN<32>, SaviaError> {
+        Self::mint_badge(env, owner, campaign_id, donation_id, amount, BadgeTier::for_amount(amount))
+    }
+
+    /// Mint a badge of `tier` for `amount` given or raised, `donation_id`
+    /// being the donation that earned it
+    fn mint_badge(
+        env: Env,
+        owner: Address,
+        campaign_id: BytesN<32>,
+        donation_id: BytesN<32>,
+        amount: i128,
+        tier: BadgeTier,
+    ) -> Result<BytesN<32>, SaviaError> {
+        // Get and increment NFT counter
+        let counter: u64 = env.storage().instance().get(&DataKey::NFTCounter).unwrap_or(0);
+        let new_counter = counter + 1;
+        env.storage().instance().set(&DataKey::NFTCounter, &new_counter);
+
+        // Generate NFT ID
+        let mut hash_input = Bytes::new(&env);
+        
+        let owner_bytes = owner.clone().to_xdr(&env);
+        let campaign_bytes = Bytes::from_slice(&env, campaign_id.to_array().as_slice());
+        let donation_bytes = Bytes::from_slice(&env, donation_id.to_array().as_slice());
+        
+        hash_input.append(&owner_bytes);
+        hash_input.append(&campaign_bytes);
+        hash_input.append(&donation_bytes);
+        hash_input.append(&Bytes::from_slice(&env, &amount.to_be_bytes()));
+        hash_input.append(&Bytes::from_slice(&env, &new_counter.to_be_bytes()));
+        
+        let nft_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+
+        let badge_type = String::from_str(&env, tier.name());
+        let soulbound = !Self::is_badge_transferable(env.clone(), badge_type.clone());
+        let metadata_uri = Self::badge_metadata_uri(&env, &badge_type, &nft_id);
+
+        let nft_badge = NFTBadge {
+            id: nft_id.clone(),
+            owner: owner.clone(),
+            badge_type: badge_type.clone(),
+            campaign_id: campaign_id.clone(),
+            minted_at: env.ledger().timestamp(),
+            metadata_uri,
+            soulbound,
+            tier,
+            donation_amount: amount,
+            serial: new_counter,
+        };
+
+        Self::store(&env, &DataKey::NFTBadge(nft_id.clone()), &nft_badge);
+        Self::index_push(&env, IndexKey::AllNfts, nft_id.clone());
+        Self::add_owned_nft(&env, &owner, &nft_id);
+
+        publish_between(&env, topics::NFT, topics::MINTED, campaign_id, owner.clone(), (nft_id.clone(), owner, badge_type));
+        Ok(nft_id)
+    }
+
+    /// Award the donor's badge for a campaign at the tier their cumulative
+    /// net `total` reaches: upgrade the badge they already hold for it in
+    /// place, or mint one
+    fn award_donation_badge(
+        env: &Env,
+        donor: Address,
+        campaign_id: BytesN<32>,
+        donation_id: BytesN<32>,
+        total: i128,
+    ) -> Result<BytesN<32>, SaviaError> {
+        let held: Option<NFTBadge> = env.storage().persistent()
+            .get::<_, BytesN<32>>(&DataKey::DonorBadge(campaign_id.clone(), donor.clone()))
+            .and_then(|nft_id| Self::load(env, &DataKey::NFTBadge(nft_id)))
+            .filter(|badge: &NFTBadge| badge.owner == donor);
+        let mut badge = match held {
+            Some(badge) => badge,
+            None => {
+                let nft_id = Self::mint_donation_nft(env.clone(), donor.clone(), campaign_id.clone(), donation_id, total)?;
+                env.storage().persistent().set(&DataKey::DonorBadge(campaign_id, donor), &nft_id);
+                return Ok(nft_id);
+            }
+        };
+
+        let tier = BadgeTier::for_amount(total);
+        badge.donation_amount = total;
+        if tier != badge.tier {
+            let badge_type = String::from_str(env, tier.name());
+            badge.metadata_uri = Self::badge_metadata_uri(env, &badge_type, &badge.id);
+            badge.badge_type = badge_type.clone();
+            badge.tier = tier;
+            Self::store(env, &DataKey::NFTBadge(badge.id.clone()), &badge);
+
+            publish_between(env, topics::NFT, topics::UPGRADED, badge.campaign_id.clone(), donor.clone(), (badge.id.clone(), donor, badge_type));
+        } else {
+            Self::store(env, &DataKey::NFTBadge(badge.id.clone()), &badge);
+        }
+        Ok(badge.id)
+    }
+
+    /// Metadata URI for a badge of `badge_type`: the URI configured for the
+    /// tier, or else `<base>/<nft id in hex>`
+    fn badge_metadata_uri(env: &Env, badge_type: &String, nft_id: &BytesN<32>) -> String {
+        if let Some(uri) = Self::get_badge_metadata(env.clone(), badge_type.clone()) {
+            return uri;
+        }
+
+        let base = Self::get_metadata_base_uri(env.clone());
+        let mut buf = [0u8; MAX_METADATA_BASE_URI_LEN as usize + 65];
+        let mut len = base.len() as usize;
+        base.copy_into_slice(&mut buf[..len]);
+        if buf[len - 1] != b'/' {
+            buf[len] = b'/';
+            len += 1;
+        }
+        buf[len..len + 64].copy_from_slice(&hex_encode(nft_id));
+        String::from_bytes(env, &buf[..len + 64])
+    }
+
+    /// Get NFT details
+    pub fn get_nft(env: Env, nft_id: BytesN<32>) -> Option<NFTBadge> {
+        Self::load(&env, &DataKey::NFTBadge(nft_id))
+    }
+
+    /// Metadata URI of a badge, as minted
+    pub fn token_uri(env: Env, nft_id: BytesN<32>) -> Result<String, SaviaError> {
+        let badge: NFTBadge = Self::load(&env, &DataKey::NFTBadge(nft_id))
+            .ok_or(SaviaError::NFTNotFound)?;
+        Ok(badge.metadata_uri)
+    }
+
+    /// Number of badges ever minted
+    pub fn total_nft_supply(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::NFTCounter).unwrap_or(0)
+    }
+
+    /// Badge minted `index`-th, from 0 (its serial minus one)
+    pub fn get_nft_by_index(env: Env, index: u64) -> Option<NFTBadge> {
+        let chunk = u32::try_from(index / INDEX_CHUNK_SIZE as u64).ok()?;
+        let ids: Vec<BytesN<32>> = env.storage().persistent()
+            .get(&DataKey::IndexChunk(IndexKey::AllNfts, chunk))?;
+        let nft_id = ids.get((index % INDEX_CHUNK_SIZE as u64) as u32)?;
+        Self::get_nft(env, nft_id)
+    }
+
+    /// List the badges held by `owner` in minting order
+    pub fn get_nfts_by_owner(
+        env: Env,
+        owner: Address,
+        cursor: Option<Cursor>,
+        limit: u32,
+    ) -> Result<(Vec<NFTBadge>, Option<Cursor>), SaviaError> {
+        let (ids, next) = Self::index_page(&env, &IndexKey::OwnerNfts(owner), cursor, limit)?;
+
+        let mut badges = Vec::new(&env);
+        for nft_id in ids.iter() {
+            if let Some(badge) = Self::get_nft(env.clone(), nft_id) {
+                badges.push_back(badge);
+            }
+        }
+        Ok((badges, next))
+    }
+
+    /// Offset-based form of `get_nfts_by_owner`
+    pub fn get_nfts_by_owner_at(env: Env, owner: Address, offset: u32, limit: u32) -> Vec<NFTBadge> {
+        let cursor = Self::cursor_at(&env, &IndexKey::OwnerNfts(owner.clone()), offset);
+        Self::get_nfts_by_owner(env.clone(), owner, Some(cursor), limit)
+            .map(|(badges, _)| badges)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Number of badges `owner` holds
+    pub fn get_nft_count(env: Env, owner: Address) -> u32 {
+        env.storage().persistent().get(&DataKey::OwnerNftCount(owner)).unwrap_or(0)
+    }
+
+    /// Move a badge from its current owner to `to` (owner function)
+    pub fn transfer_nft(env: Env, nft_id: BytesN<32>, from: Address, to: Address) -> Result<(), SaviaError> {
+        from.require_auth();
+
+        let mut badge: NFTBadge = Self::load(&env, &DataKey::NFTBadge(nft_id.clone()))
+            .ok_or(SaviaError::NFTNotFound)?;
+        if badge.owner != from {
+            return Err(SaviaError::Unauthorized);
+        }
+        if badge.soulbound {
+            return Err(SaviaError::NonTransferable);
+        }
+        if from == to {
+            return Ok(());
+        }
+
+        badge.owner = to.clone();
+        Self::store(&env, &DataKey::NFTBadge(nft_id.clone()), &badge);
+        Self::remove_owned_nft(&env, &from, &nft_id);
+        Self::add_owned_nft(&env, &to, &nft_id);
+
+        publish_between(&env, topics::NFT, topics::TRANSFERRED, badge.campaign_id, from.clone(), (nft_id, from, to));
+        Ok(())
+    }
+
+    /// Destroy a badge (owner function)
+    pub fn burn_nft(env: Env, nft_id: BytesN<32>) -> Result<(), SaviaError> {
+        let badge: NFTBadge = Self::load(&env, &DataKey::NFTBadge(nft_id))
+            .ok_or(SaviaError::NFTNotFound)?;
+
+        badge.owner.require_auth();
+
+        Self::burn_badge(&env, badge);
+        Ok(())
+    }
+
+    /// Destroy any badge, e.g. one minted from a fraudulent donation (admin
+    /// function)
+    pub fn admin_burn_nft(env: Env, nft_id: BytesN<32>) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let badge: NFTBadge = Self::load(&env, &DataKey::NFTBadge(nft_id))
+            .ok_or(SaviaError::NFTNotFound)?;
+
+        Self::burn_badge(&env, badge);
+        Ok(())
+    }
+
+    /// Choose whether badges of `badge_type` minted from now on can be
+    /// transferred. Donation badges are soulbound unless enabled here (admin
+    /// function).
+    pub fn set_badge_transferable(env: Env, badge_type: String, transferable: bool) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        env.storage().instance().set(&DataKey::TransferableBadge(badge_type.clone()), &transferable);
+
+        publish(&env, topics::CONFIG, topics::TRANSFERABLE, (badge_type, transferable));
+        Ok(())
+    }
+
+    /// Set the metadata URI badges of `badge_type` are minted with (admin
+    /// function)
+    pub fn set_badge_metadata(env: Env, badge_type: String, uri: String) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if uri.is_empty() {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::BadgeMetadata(badge_type.clone()), &uri);
+
+        publish(&env, topics::CONFIG, topics::METADATA, (badge_type, uri));
+        Ok(())
+    }
+
+    /// Get the metadata URI configured for `badge_type`, if any
+    pub fn get_badge_metadata(env: Env, badge_type: String) -> Option<String> {
+        env.storage().instance().get(&DataKey::BadgeMetadata(badge_type))
+    }
+
+    /// Set the base URI badges without a tier URI are minted with; each
+    /// gets `<base>/<nft id in hex>` (admin function)
+    pub fn set_metadata_base_uri(env: Env, uri: String) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if uri.is_empty() || uri.len() > MAX_METADATA_BASE_URI_LEN {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().instance().set(&DataKey::Config(ConfigKey::MetadataBaseUri), &uri);
+
+        publish(&env, topics::CONFIG, topics::METADATA_BASE, uri);
+        Ok(())
+    }
+
+    /// Get the base URI badges without a tier URI are minted with
+    pub fn get_metadata_base_uri(env: Env) -> String {
+        env.storage().instance().get(&DataKey::Config(ConfigKey::MetadataBaseUri))
+            .unwrap_or(String::from_str(&env, DEFAULT_METADATA_BASE_URI))
+    }
+
+    /// Whether newly minted badges of `badge_type` are transferable
+    pub fn is_badge_transferable(env: Env, badge_type: String) -> bool {
+        env.storage().instance().get(&DataKey::TransferableBadge(badge_type)).unwrap_or(false)
+    }
+
+    /// Set a campaign's milestone plan. Percentages of the goal must sum to
+    /// 100. Only allowed before the first donation (beneficiary function).
+    pub fn set_milestones(env: Env, campaign_id: BytesN<32>, milestones: Vec<Milestone>) -> Result<(), SaviaError> {
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if Self::get_campaign_stats(env.clone(), campaign_id.clone()).donation_count > 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+        let mut total = 0u32;
+        for milestone in milestones.iter() {
+            if milestone.percentage == 0 || milestone.title.is_empty() {
+                return Err(SaviaError::InvalidInput);
+            }
+            total = total.saturating_add(milestone.percentage);
+        }
+        if total != 100 {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::Milestones(campaign_id.clone())), &milestones);
+
+        publish_about(&env, topics::CAMPAIGN, topics::MILESTONES, campaign_id.clone(), (campaign_id, milestones.len()));
+        Ok(())
+    }
+
+    /// Set the smallest accepted donation and the most one donor may give in
+    /// total, 0 meaning no limit. Only allowed before the first donation
+    /// (beneficiary function).
+    pub fn set_donation_limits(
+        env: Env,
+        campaign_id: BytesN<32>,
+        min_donation: i128,
+        max_donation_per_donor: i128,
+    ) -> Result<(), SaviaError> {
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if Self::get_campaign_stats(env.clone(), campaign_id.clone()).donation_count > 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+        if min_donation < 0 || max_donation_per_donor < 0 {
+            return Err(SaviaError::InvalidAmount);
+        }
+        if max_donation_per_donor > 0 && min_donation > max_donation_per_donor {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        campaign.min_donation = min_donation;
+        campaign.max_donation_per_donor = max_donation_per_donor;
+        Self::store(&env, &DataKey::Campaign(campaign_id.clone()), &campaign);
+
+        publish_about(&env, topics::CAMPAIGN, topics::LIMITS, campaign_id.clone(), (campaign_id, min_donation, max_donation_per_donor));
+        Ok(())
+    }
+
+    /// Correct a campaign's title, description, location or metadata URI;
+    /// `None` leaves a field unchanged. Only allowed before the campaign
+    /// receives funds or is verified (beneficiary function).
+    pub fn update_campaign(
+        env: Env,
+        campaign_id: BytesN<32>,
+        title: Option<String>,
+        description: Option<String>,
+        location: Option<String>,
+        metadata_uri: Option<String>,
+    ) -> Result<(), SaviaError> {
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if campaign.current_amount != 0 || campaign.verified {
+            return Err(SaviaError::CampaignLocked);
+        }
+        let fields = [&title, &description, &location, &metadata_uri];
+        if fields.iter().any(|field| field.as_ref().is_some_and(|value| value.is_empty())) {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        if let Some(title) = title {
+            // Move the duplicate-title claim over to the new title
+            let old_key = Self::title_key(&env, &campaign.title);
+            if env.storage().persistent().get(&old_key) == Some(campaign_id.clone()) {
+                env.storage().persistent().remove(&old_key);
+            }
+            let new_key = Self::title_key(&env, &title);
+            campaign.possible_duplicate = env.storage().persistent().has(&new_key);
+            if !campaign.possible_duplicate {
+                env.storage().persistent().set(&new_key, &campaign_id);
+            }
+            campaign.title = title;
+        }
+        if let Some(description) = description {
+            campaign.description = description;
+        }
+        if let Some(location) = location {
+            campaign.location = location;
+        }
+        if metadata_uri.is_some() {
+            campaign.metadata_uri = metadata_uri;
+        }
+        Self::store(&env, &DataKey::Campaign(campaign_id.clone()), &campaign);
+
+        publish_about(&env, topics::CAMPAIGN, topics::UPDATED, campaign_id.clone(), campaign_id);
+        Ok(())
+    }
+
+    /// Post a progress report on the campaign, pointing at content kept off
+    /// chain (beneficiary function)
+    pub fn post_update(
+        env: Env,
+        campaign_id: BytesN<32>,
+        title: String,
+        content_uri: String,
+    ) -> Result<u32, SaviaError> {
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if title.is_empty() || content_uri.is_empty() {
+            return Err(SaviaError::InvalidInput);
+        }
+        let key = DataKey::CampaignData(CampaignKey::Updates(campaign_id.clone()));
+        let mut updates: Vec<CampaignUpdate> = Self::load(&env, &key).unwrap_or(Vec::new(&env));
+        if updates.len() >= MAX_CAMPAIGN_UPDATES {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        let id = updates.len();
+        updates.push_back(CampaignUpdate {
+            id,
+            campaign_id: campaign_id.clone(),
+            title,
+            content_uri,
+            posted_at: env.ledger().timestamp(),
+        });
+        Self::store(&env, &key, &updates);
+
+        publish_about(&env, topics::CAMPAIGN, topics::POSTED, campaign_id.clone(), (campaign_id, id));
+        Ok(id)
+    }
+
+    /// List a campaign's progress updates, oldest first
+    pub fn get_updates(env: Env, campaign_id: BytesN<32>, offset: u32, limit: u32) -> Vec<CampaignUpdate> {
+        let updates: Vec<CampaignUpdate> = Self::load(&env, &DataKey::CampaignData(CampaignKey::Updates(campaign_id)))
+            .unwrap_or(Vec::new(&env));
+        let start = offset.min(updates.len());
+        let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(updates.len());
+        updates.slice(start..end)
+    }
+
+    /// Replace the campaign's donor whitelist with `donors` and choose whether
+    /// only they may donate (beneficiary function). Anyone else's donation
+    /// fails with `NotWhitelisted`.
+    pub fn set_donor_whitelist(
+        env: Env,
+        campaign_id: BytesN<32>,
+        donors: Vec<Address>,
+        enabled: bool,
+    ) -> Result<(), SaviaError> {
+        Self::require_beneficiary(&env, &campaign_id)?;
+
+        let mut whitelist = Map::new(&env);
+        for donor in donors.iter() {
+            whitelist.set(donor, true);
+        }
+        Self::store_whitelist(&env, &campaign_id, &whitelist)?;
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::WhitelistEnabled(campaign_id.clone())), &enabled);
+
+        publish_about(&env, topics::CAMPAIGN, topics::WHITELIST, campaign_id.clone(), (campaign_id, enabled, whitelist.len()));
+        Ok(())
+    }
+
+    /// Add `donors` to the campaign's whitelist (beneficiary function)
+    pub fn add_to_whitelist(env: Env, campaign_id: BytesN<32>, donors: Vec<Address>) -> Result<(), SaviaError> {
+        Self::require_beneficiary(&env, &campaign_id)?;
+
+        let mut whitelist = Self::whitelist(&env, &campaign_id);
+        for donor in donors.iter() {
+            whitelist.set(donor, true);
+        }
+        Self::store_whitelist(&env, &campaign_id, &whitelist)?;
+
+        let enabled = Self::is_whitelist_enabled(env.clone(), campaign_id.clone());
+        publish_about(&env, topics::CAMPAIGN, topics::WHITELIST, campaign_id.clone(), (campaign_id, enabled, whitelist.len()));
+        Ok(())
+    }
+
+    /// Remove `donors` from the campaign's whitelist (beneficiary function)
+    pub fn remove_from_whitelist(env: Env, campaign_id: BytesN<32>, donors: Vec<Address>) -> Result<(), SaviaError> {
+        Self::require_beneficiary(&env, &campaign_id)?;
+
+        let mut whitelist = Self::whitelist(&env, &campaign_id);
+        for donor in donors.iter() {
+            whitelist.remove(donor);
+        }
+        Self::store_whitelist(&env, &campaign_id, &whitelist)?;
+
+        let enabled = Self::is_whitelist_enabled(env.clone(), campaign_id.clone());
+        publish_about(&env, topics::CAMPAIGN, topics::WHITELIST, campaign_id.clone(), (campaign_id, enabled, whitelist.len()));
+        Ok(())
+    }
+
+    /// Whether the campaign only accepts donations from whitelisted donors
+    pub fn is_whitelist_enabled(env: Env, campaign_id: BytesN<32>) -> bool {
+        env.storage().persistent()
+            .get(&DataKey::CampaignData(CampaignKey::WhitelistEnabled(campaign_id)))
+            .unwrap_or(false)
+    }
+
+    /// Whether `donor` may donate to the campaign under its whitelist
+    pub fn is_donor_allowed(env: Env, campaign_id: BytesN<32>, donor: Address) -> bool {
+        !Self::is_whitelist_enabled(env.clone(), campaign_id.clone())
+            || Self::whitelist(&env, &campaign_id).contains_key(donor)
+    }
+
+    /// Choose whether the campaign stops accepting donations once it reaches
+    /// its goal. Only allowed before the first donation (beneficiary
+    /// function).
+    pub fn set_close_on_goal(env: Env, campaign_id: BytesN<32>, close_on_goal: bool) -> Result<(), SaviaError> {
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if Self::get_campaign_stats(env.clone(), campaign_id.clone()).donation_count > 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        campaign.close_on_goal = close_on_goal;
+        Self::store(&env, &DataKey::Campaign(campaign_id.clone()), &campaign);
+
+        publish_about(&env, topics::CAMPAIGN, topics::CLOSE_ON_GOAL, campaign_id.clone(), (campaign_id, close_on_goal));
+        Ok(())
+    }
+
+    /// Denominate the campaign in `token`, which the admin must have allowed.
+    /// Only allowed before the first donation and before any match pool,
+    /// challenge or recurring donation is pledged (beneficiary function).
+    pub fn set_campaign_token(env: Env, campaign_id: BytesN<32>, token: Address) -> Result<(), SaviaError> {
+        let mut campaign = Self::require_beneficiary(&env, &campaign_id)?;
+
+        let pledged = env.storage().persistent().has(&DataKey::CampaignData(CampaignKey::Pledged(campaign_id.clone())));
+        if pledged || Self::get_campaign_stats(env.clone(), campaign_id.clone()).donation_count > 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+        if !Self::is_token_allowed(env.clone(), token.clone()) {
+            return Err(SaviaError::WrongToken);
+        }
+
+        campaign.token = token.clone();
+        Self::store(&env, &DataKey::Campaign(campaign_id.clone()), &campaign);
+
+        publish_about(&env, topics::CAMPAIGN, topics::TOKEN, campaign_id.clone(), (campaign_id, token));
+        Ok(())
+    }
+
+    /// Cap what the campaign may raise at `limit_bps` basis points of its
+    /// goal, at least 10000; 0 removes the cap. Only allowed before the
+    /// first donation (beneficiary function).
+    pub fn set_overfunding_limit(env: Env, campaign_id: BytesN<32>, limit_bps: u32) -> Result<(), SaviaError> {
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if Self::get_campaign_stats(env.clone(), campaign_id.clone()).donation_count > 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+        if limit_bps != 0 && limit_bps < 10000 {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        campaign.overfunding_limit_bps = limit_bps;
+        Self::store(&env, &DataKey::Campaign(campaign_id.clone()), &campaign);
+
+        publish_about(&env, topics::CAMPAIGN, topics::OVERFUNDING, campaign_id.clone(), (campaign_id, limit_bps));
+        Ok(())
+    }
+
+    /// Get a campaign's milestone plan; empty if none was set
+    pub fn get_milestones(env: Env, campaign_id: BytesN<32>) -> Vec<Milestone> {
+        env.storage().persistent().get(&DataKey::CampaignData(CampaignKey::Milestones(campaign_id))).unwrap_or(Vec::new(&env))
+    }
+
+    /// Create a disbursement request against milestone `milestone_index` of
+    /// the campaign's plan (beneficiary function). A campaign without a plan
+    /// has a single milestone, index 0, covering the whole goal. A recipient
+    /// other than the beneficiary must sign too, acknowledging the payout.
+    pub fn create_disbursement(
+        env: Env,
+        campaign_id: BytesN<32>,
+        recipient: Address,
+        amount: i128,
+        milestone_index: u32,
+    ) -> Result<BytesN<32>, SaviaError> {
+        Self::require_not_paused(&env)?;
+
+        let campaign = Self::require_beneficiary(&env, &campaign_id)?;
+        if recipient != campaign.beneficiary {
+            recipient.require_auth();
+        }
+
+        if campaign.suspended {
+            return Err(SaviaError::CampaignSuspended);
+        }
+        if Self::is_frozen(env.clone(), campaign_id.clone()) {
+            return Err(SaviaError::CampaignFrozen);
+        }
+        if Self::is_dispute_hold(env.clone(), campaign_id.clone()) {
+            return Err(SaviaError::CampaignLocked);
+        }
+        if !campaign.outcome.allows_disbursement(campaign.funding_mode) {
+            return Err(SaviaError::GoalNotReached);
+        }
+        Self::require_not_blacklisted(&env, &campaign.beneficiary)?;
+        Self::require_not_blacklisted(&env, &recipient)?;
+
+        if amount <= 0 {
+            return Err(SaviaError::InvalidAmount);
+        }
+
+        // Pending, approved and executed requests all count against the milestone
+        let plan = Self::get_milestones(env.clone(), campaign_id.clone());
+        let (milestone, allocation) = if plan.is_empty() {
+            if milestone_index != 0 {
+                return Err(SaviaError::MilestoneNotFound);
+            }
+            (String::from_str(&env, ""), i128::MAX)
+        } else {
+            let planned = plan.get(milestone_index).ok_or(SaviaError::MilestoneNotFound)?;
+            let percentage = planned.percentage as i128;
+            let allocation = campaign.goal_amount.checked_mul(percentage)
+                .map(|share| share / 100)
+                .unwrap_or(campaign.goal_amount / 100 * percentage);
+            (planned.title, allocation)
+        };
+        let committed_key = DataKey::CampaignData(CampaignKey::MilestoneCommitted(campaign_id.clone(), milestone_index));
+        let committed: i128 = env.storage().persistent().get(&committed_key).unwrap_or(0);
+        if amount > allocation - committed {
+            return Err(SaviaError::MilestoneAllocationExceeded);
+        }
+
+        if amount > campaign.current_amount - campaign.disbursed_amount {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        if amount > Self::get_disbursement_limit(env.clone(), campaign.beneficiary.clone()) {
+            return Err(SaviaError::DisbursementLimitExceeded);
+        }
+
+        if Self::in_endgame(&env, &campaign) {
+            return Err(SaviaError::EndgameLocked);
+        }
+
+        // Get and increment disbursement counter
+        let counter: u64 = env.storage().instance().get(&DataKey::DisbursementCounter).unwrap_or(0);
+        let new_counter = counter + 1;
+        env.storage().instance().set(&DataKey::DisbursementCounter, &new_counter);
+
+        // Generate disbursement ID
+        let mut hash_input = Bytes::new(&env);
+        
+        let campaign_bytes = Bytes::from_slice(&env, campaign_id.to_array().as_slice());
+        let recipient_bytes = recipient.clone().to_xdr(&env);
+        
+        hash_input.append(&campaign_bytes);
+        hash_input.append(&recipient_bytes);
+        hash_input.append(&Bytes::from_slice(&env, &amount.to_be_bytes()));
+        hash_input.append(&Bytes::from_slice(&env, &milestone_index.to_be_bytes()));
+        hash_input.append(&Bytes::from_slice(&env, &new_counter.to_be_bytes()));
+        
+        let disbursement_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+
+        let disbursement = Disbursement {
+            id: disbursement_id.clone(),
+            campaign_id: campaign_id.clone(),
+            recipient,
+            amount,
+            milestone,
+            status: DisbursementStatus::Pending,
+            created_at: env.ledger().timestamp(),
+            executed_at: None,
+            execution_nonce: 0,
+            rejection_reason: None,
+            milestone_index,
+            expires_at: env.ledger().timestamp().saturating_add(Self::get_disbursement_validity(env.clone())),
+        };
+
+        Self::store(&env, &DataKey::Disbursement(disbursement_id.clone()), &disbursement);
+        env.storage().persistent().set(&committed_key, &(committed + amount));
+        if let Some(window) = Self::get_voting_window(env.clone(), campaign_id.clone()) {
+            let tally = VoteTally {
+                deadline: env.ledger().timestamp().saturating_add(window),
+                approve_weight: 0,
+                reject_weight: 0,
+            };
+            env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::VoteTally(disbursement_id.clone())), &tally);
+        }
+
+        match Self::get_event_verbosity(env.clone()) {
+            EventVerbosity::Minimal => publish_between(&env, topics::DISBURSEMENT, topics::CREATED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement_id.clone()),
+            EventVerbosity::Standard => publish_between(&env, topics::DISBURSEMENT, topics::CREATED, disbursement.campaign_id.clone(), disbursement.id.clone(), (disbursement_id.clone(), campaign_id, amount)),
+            EventVerbosity::Verbose => publish_between(&env, topics::DISBURSEMENT, topics::CREATED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement),
+        }
+        Ok(disbursement_id)
+    }
+
+    /// Sign off on a pending disbursement as `approver`: the admin, or one of
+    /// the configured approvers once `set_approvers` is used, in which case
+    /// the disbursement is approved when enough of them have signed.
+    /// Disbursements of campaigns with donor voting are approved by
+    /// `tally_disbursement`.
+    pub fn approve_disbursement(env: Env, disbursement_id: BytesN<32>, approver: Address) -> Result<(), SaviaError> {
+        approver.require_auth();
+
+        let (approvers, threshold) = Self::require_approver(&env, &approver)?;
+        Self::sign_disbursement(&env, disbursement_id, &approver, &approvers, threshold)
+    }
+
+    /// Sign off on up to 25 disbursements at once, authorizing once. Ids
+    /// that are missing or cannot be approved are skipped rather than failing
+    /// the batch. Returns each id with whether it was signed.
+    pub fn approve_disbursements(
+        env: Env,
+        disbursement_ids: Vec<BytesN<32>>,
+        approver: Address,
+    ) -> Result<Vec<(BytesN<32>, bool)>, SaviaError> {
+        approver.require_auth();
+
+        if disbursement_ids.len() > MAX_APPROVAL_BATCH {
+            return Err(SaviaError::InvalidInput);
+        }
+        let (approvers, threshold) = Self::require_approver(&env, &approver)?;
+
+        let mut results = Vec::new(&env);
+        for disbursement_id in disbursement_ids.iter() {
+            let signed = Self::sign_disbursement(&env, disbursement_id.clone(), &approver, &approvers, threshold).is_ok();
+            results.push_back((disbursement_id, signed));
+        }
+        Ok(results)
+    }
+
+    /// Require `threshold` distinct `approvers` to approve each disbursement
+    /// instead of the admin alone; an empty list hands approval back to the
+    /// admin (admin function)
+    pub fn set_approvers(env: Env, approvers: Vec<Address>, threshold: u32) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        if approvers.len() > MAX_APPROVERS || threshold > approvers.len() || (threshold == 0) != approvers.is_empty() {
+            return Err(SaviaError::InvalidInput);
+        }
+        for (index, approver) in approvers.iter().enumerate() {
+            if approvers.iter().skip(index + 1).any(|other| other == approver) {
+                return Err(SaviaError::InvalidInput);
+            }
+        }
+
+        env.storage().instance().set(&DataKey::Config(ConfigKey::Approvers), &approvers);
+        env.storage().instance().set(&DataKey::Config(ConfigKey::ApprovalThreshold), &threshold);
+
+        publish(&env, topics::CONFIG, topics::APPROVERS, (approvers, threshold));
+        Ok(())
+    }
+
+    /// Get the disbursement approvers and how many must approve; empty when
+    /// the admin approves alone
+    pub fn get_approvers(env: Env) -> (Vec<Address>, u32) {
+        let storage = env.storage().instance();
+        (
+            storage.get(&DataKey::Config(ConfigKey::Approvers)).unwrap_or(Vec::new(&env)),
+            storage.get(&DataKey::Config(ConfigKey::ApprovalThreshold)).unwrap_or(0),
+        )
+    }
+
+    /// Get who has approved a disbursement so far, removed approvers included
+    pub fn get_approvals(env: Env, disbursement_id: BytesN<32>) -> Vec<Address> {
+        env.storage().persistent()
+            .get(&DataKey::CampaignData(CampaignKey::Approvals(disbursement_id)))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Let donors approve this campaign's disbursements by vote. Each
+    /// request stays open for `window` seconds. Only allowed before the
+    /// first donation (beneficiary function).
+    pub fn enable_donor_voting(env: Env, campaign_id: BytesN<32>, window: u64) -> Result<(), SaviaError> {
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if window == 0 || Self::get_campaign_stats(env.clone(), campaign_id.clone()).donation_count > 0 {
+            return Err(SaviaError::InvalidInput);
+        }
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::VotingWindow(campaign_id.clone())), &window);
+
+        publish_about(&env, topics::CAMPAIGN, topics::VOTING, campaign_id.clone(), (campaign_id, window));
+        Ok(())
+    }
+
+    /// Get the voting window of a campaign with donor voting
+    pub fn get_voting_window(env: Env, campaign_id: BytesN<32>) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::CampaignData(CampaignKey::VotingWindow(campaign_id)))
+    }
+
+    /// Get the donor vote on a disbursement, if it is decided by vote
+    pub fn get_vote_tally(env: Env, disbursement_id: BytesN<32>) -> Option<VoteTally> {
+        env.storage().persistent().get(&DataKey::CampaignData(CampaignKey::VoteTally(disbursement_id)))
+    }
+
+    /// Vote on a pending disbursement, weighted by what `donor` has given the
+    /// campaign net of fees. Each donor votes once (donor function).
+    pub fn vote_disbursement(env: Env, disbursement_id: BytesN<32>, donor: Address, approve: bool) -> Result<(), SaviaError> {
+        donor.require_auth();
+
+        let disbursement: Disbursement = Self::load(&env, &DataKey::Disbursement(disbursement_id.clone()))
+            .ok_or(SaviaError::DisbursementNotFound)?;
+        let mut tally = Self::get_vote_tally(env.clone(), disbursement_id.clone())
+            .ok_or(SaviaError::InvalidInput)?;
+
+        if disbursement.status != DisbursementStatus::Pending {
+            return Err(SaviaError::DisbursementNotPending);
+        }
+        if env.ledger().timestamp() > tally.deadline {
+            return Err(SaviaError::InvalidInput);
+        }
+        let weight: i128 = env.storage().persistent()
+            .get(&DataKey::DonorNetTotal(disbursement.campaign_id.clone(), donor.clone()))
+            .unwrap_or(0);
+        if weight <= 0 {
+            return Err(SaviaError::Unauthorized);
+        }
+        let voted_key = DataKey::CampaignData(CampaignKey::Voted(disbursement_id.clone(), donor));
+        if env.storage().persistent().has(&voted_key) {
+            return Err(SaviaError::AlreadyVoted);
+        }
+
+        if approve {
+            tally.approve_weight = Self::checked_add(tally.approve_weight, weight)?;
+        } else {
+            tally.reject_weight = Self::checked_add(tally.reject_weight, weight)?;
+        }
+        env.storage().persistent().set(&voted_key, &approve);
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::VoteTally(disbursement_id.clone())), &tally);
+
+        publish_between(&env, topics::DISBURSEMENT, topics::VOTED, disbursement.campaign_id.clone(), disbursement.id.clone(), (disbursement_id, approve, weight));
+        Ok(())
+    }
+
+    /// Settle a donor vote. Approves once more than half of the campaign's
+    /// donated weight approves; after the deadline, anything short of that
+    /// is rejected. Anyone may call this. Returns the new status.
+    pub fn tally_disbursement(env: Env, disbursement_id: BytesN<32>) -> Result<DisbursementStatus, SaviaError> {
+        let mut disbursement: Disbursement = Self::load(&env, &DataKey::Disbursement(disbursement_id.clone()))
+            .ok_or(SaviaError::DisbursementNotFound)?;
+        let tally = Self::get_vote_tally(env.clone(), disbursement_id.clone())
+            .ok_or(SaviaError::InvalidInput)?;
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(disbursement.campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        if disbursement.status != DisbursementStatus::Pending {
+            return Err(SaviaError::DisbursementNotPending);
+        }
+
+        if tally.approve_weight > campaign.current_amount / 2 {
+            if campaign.suspended {
+                return Err(SaviaError::CampaignSuspended);
+            }
+            Self::mark_approved(&env, &mut disbursement);
+        } else if env.ledger().timestamp() > tally.deadline {
+            Self::mark_rejected(&env, &mut disbursement, String::from_str(&env, "Not approved by donor vote"));
+        } else {
+            return Err(SaviaError::InvalidInput);
+        }
+        Ok(disbursement.status)
+    }
+
+    /// Reject a pending disbursement request with a reason (admin function)
+    pub fn reject_disbursement(env: Env, disbursement_id: BytesN<32>, reason: String) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let mut disbursement: Disbursement = Self::load(&env, &DataKey::Disbursement(disbursement_id.clone()))
+            .ok_or(SaviaError::DisbursementNotFound)?;
+
+        if disbursement.status != DisbursementStatus::Pending {
+            return Err(SaviaError::DisbursementNotPending);
+        }
+
+        Self::mark_rejected(&env, &mut disbursement, reason);
+        Ok(())
+    }
+
+    /// Withdraw a pending disbursement request (beneficiary function)
+    pub fn cancel_disbursement(env: Env, disbursement_id: BytesN<32>) -> Result<(), SaviaError> {
+        let mut disbursement: Disbursement = Self::load(&env, &DataKey::Disbursement(disbursement_id.clone()))
+            .ok_or(SaviaError::DisbursementNotFound)?;
+        let campaign: Campaign = Self::load(&env, &DataKey::Campaign(disbursement.campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        campaign.beneficiary.require_auth();
+
+        if disbursement.status != DisbursementStatus::Pending {
+            return Err(SaviaError::DisbursementNotPending);
+        }
+
+        disbursement.status = DisbursementStatus::Cancelled;
+        Self::store(&env, &DataKey::Disbursement(disbursement_id.clone()), &disbursement);
+        Self::release_milestone_commitment(&env, &disbursement);
+
+        match Self::get_event_verbosity(env.clone()) {
+            EventVerbosity::Minimal | EventVerbosity::Standard => publish_between(&env, topics::DISBURSEMENT, topics::CANCELLED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement_id),
+            EventVerbosity::Verbose => publish_between(&env, topics::DISBURSEMENT, topics::CANCELLED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement),
+        }
+        Ok(())
+    }
+
+    /// Mark a pending or approved disbursement past its `expires_at` as
+    /// expired, freeing its milestone share. Anyone may call this.
+    pub fn expire_disbursement(env: Env, disbursement_id: BytesN<32>) -> Result<(), SaviaError> {
+        let mut disbursement: Disbursement = Self::load(&env, &DataKey::Disbursement(disbursement_id.clone()))
+            .ok_or(SaviaError::DisbursementNotFound)?;
+
+        if !matches!(disbursement.status, DisbursementStatus::Pending | DisbursementStatus::Approved) {
+            return Err(SaviaError::DisbursementNotPending);
+        }
+        if env.ledger().timestamp() <= disbursement.expires_at {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        disbursement.status = DisbursementStatus::Expired;
+        Self::store(&env, &DataKey::Disbursement(disbursement_id.clone()), &disbursement);
+        Self::release_milestone_commitment(&env, &disbursement);
+
+        match Self::get_event_verbosity(env.clone()) {
+            EventVerbosity::Minimal | EventVerbosity::Standard => publish_between(&env, topics::DISBURSEMENT, topics::EXPIRED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement_id),
+            EventVerbosity::Verbose => publish_between(&env, topics::DISBURSEMENT, topics::EXPIRED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement),
+        }
+        Ok(())
+    }
+
+    /// Execute approved disbursement. `nonce` must equal the disbursement's
+    /// current `execution_nonce`, so a replayed call is rejected. Past
+    /// `expires_at` it fails with `DisbursementExpired`.
+    pub fn execute_disbursement(
+        env: Env,
+        disbursement_id: BytesN<32>,
+        nonce: u32,
+    ) -> Result<(), SaviaError> {
+        Self::require_not_paused(&env)?;
+
+        let mut disbursement: Disbursement = Self::load(&env, &DataKey::Disbursement(disbursement_id.clone()))
+            .ok_or(SaviaError::DisbursementNotFound)?;
+
+        if nonce != disbursement.execution_nonce {
+            return Err(SaviaError::StaleNonce);
+        }
+
+        if disbursement.status == DisbursementStatus::Expired {
+            return Err(SaviaError::DisbursementExpired);
+        }
+        if disbursement.status != DisbursementStatus::Approved {
+            return Err(SaviaError::NotApproved);
+        }
+        // An approval that lapsed is no approval
+        if env.ledger().timestamp() > disbursement.expires_at {
+            return Err(SaviaError::DisbursementExpired);
+        }
+
+        // Funds may have been paid out by other disbursements since creation
+        let mut campaign: Campaign = Self::load(&env, &DataKey::Campaign(disbursement.campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        if campaign.suspended {
+            return Err(SaviaError::CampaignSuspended);
+        }
+        if Self::is_dispute_hold(env.clone(), campaign.id.clone()) {
+            return Err(SaviaError::CampaignLocked);
+        }
+        Self::require_not_blacklisted(&env, &campaign.beneficiary)?;
+        Self::require_not_blacklisted(&env, &disbursement.recipient)?;
+        if disbursement.amount > campaign.current_amount - campaign.disbursed_amount {
+            return Err(SaviaError::InsufficientFunds);
+        }
+
+        Self::campaign_token(&env, &campaign).transfer(
+            &env.current_contract_address(),
+            &disbursement.recipient,
+            &disbursement.amount,
+        );
+
+        campaign.disbursed_amount = Self::checked_add(campaign.disbursed_amount, disbursement.amount)?;
+        Self::store(&env, &DataKey::Campaign(disbursement.campaign_id.clone()), &campaign);
+
+        disbursement.status = DisbursementStatus::Executed;
+        disbursement.executed_at = Some(env.ledger().timestamp());
+        disbursement.execution_nonce += 1;
+
+        Self::store(&env, &DataKey::Disbursement(disbursement_id.clone()), &disbursement);
+
+        match Self::get_event_verbosity(env.clone()) {
+            EventVerbosity::Minimal => publish_between(&env, topics::DISBURSEMENT, topics::EXECUTED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement_id),
+            EventVerbosity::Standard => publish_between(&env, topics::DISBURSEMENT, topics::EXECUTED, disbursement.campaign_id.clone(), disbursement.id.clone(), (disbursement_id, disbursement.amount)),
+            EventVerbosity::Verbose => publish_between(&env, topics::DISBURSEMENT, topics::EXECUTED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement),
+        }
+        Ok(())
+    }
+
+    /// Get disbursement details
+    pub fn get_disbursement(env: Env, disbursement_id: BytesN<32>) -> Option<Disbursement> {
+        Self::load(&env, &DataKey::Disbursement(disbursement_id))
+    }
+
+    /// Set the per-disbursement caps by verification level (admin function).
+    /// Each entry is `(min_level, max_amount)`, with levels strictly ascending
+    /// from 0; a beneficiary gets the cap of the highest level they reach.
+    pub fn set_disbursement_limits(env: Env, limits: Vec<(u32, i128)>) -> Result<(), SaviaError> {
+        Self::require_admin(&env)?;
+
+        let mut previous: Option<u32> = None;
+        for (level, _) in limits.iter() {
+            let ascending = match previous {
+                None => level == 0,
+                Some(prev) => level > prev,
+            };
+            if !ascending {
+                return Err(SaviaError::InvalidInput);
+            }
+            previous = Some(level);
+        }
+        if previous.is_none() {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        env.storage().instance().set(&DataKey::DisbursementLimits, &limits);
+
+        publish(&env, topics::CONFIG, topics::LIMITS, limits);
+        Ok(())
+    }
+
+    /// Get the largest single disbursement a beneficiary may currently request
+    pub fn get_disbursement_limit(env: Env, beneficiary: Address) -> i128 {
+        let limits = Self::stored_disbursement_limits(&env)
+            .unwrap_or(soroban_sdk::vec![&env, (0, 1_000), (1, 25_000), (2, i128::MAX)]);
+        let level = Self::get_trust_score(env, beneficiary)
+            .map(|score| score.verification_level)
+            .unwrap_or(0);
+
+        let mut limit = 0;
+        for (min_level, max_amount) in limits.iter() {
+            if level >= min_level {
+                limit = max_amount;
+            }
+        }
+        limit
+    }
+
+    /// Record `owner` as holding `nft_id` in the owner index. Anything that
+    /// changes a badge's owner must go through this.
+    fn add_owned_nft(env: &Env, owner: &Address, nft_id: &BytesN<32>) {
+        Self::index_push(env, IndexKey::OwnerNfts(owner.clone()), nft_id.clone());
+        let key = DataKey::OwnerNftCount(owner.clone());
+        let count: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage().persistent().set(&key, &(count + 1));
+    }
+
+    /// Drop `nft_id` from `owner`'s entry in the owner index. Counterpart of
+    /// `add_owned_nft`.
+    fn remove_owned_nft(env: &Env, owner: &Address, nft_id: &BytesN<32>) {
+        if Self::index_retain(env, &IndexKey::OwnerNfts(owner.clone()), |id| id != nft_id) == 0 {
+            return;
+        }
+        let key = DataKey::OwnerNftCount(owner.clone());
+        let count: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage().persistent().set(&key, &count.saturating_sub(1));
+    }
+
+    /// Remove a badge and every index entry pointing at it. Its serial is
+    /// not reused, so `get_nft_by_index` returns `None` for it from now on.
+    fn burn_badge(env: &Env, badge: NFTBadge) {
+        env.storage().persistent().remove(&DataKey::NFTBadge(badge.id.clone()));
+        Self::remove_owned_nft(env, &badge.owner, &badge.id);
+        let donor_badge = DataKey::DonorBadge(badge.campaign_id.clone(), badge.owner.clone());
+        if env.storage().persistent().get(&donor_badge) == Some(badge.id.clone()) {
+            env.storage().persistent().remove(&donor_badge);
+        }
+
+        let burned_key = DataKey::Config(ConfigKey::BurnedNfts);
+        let burned: u64 = env.storage().instance().get(&burned_key).unwrap_or(0);
+        env.storage().instance().set(&burned_key, &(burned + 1));
+
+        publish_between(env, topics::NFT, topics::BURNED, badge.campaign_id, badge.owner.clone(), (badge.id, badge.owner));
+    }
+
+    /// Fail unless `approver` may sign disbursements: the admin while no
+    /// approvers are configured, else one of them. Returns the approvers and
+    /// threshold.
+    fn require_approver(env: &Env, approver: &Address) -> Result<(Vec<Address>, u32), SaviaError> {
+        Self::require_initialized(env)?;
+        let (approvers, threshold) = Self::get_approvers(env.clone());
+        let authorized = if approvers.is_empty() {
+            env.storage().instance().get(&DataKey::Admin) == Some(approver.clone())
+        } else {
+            approvers.contains(approver)
+        };
+        if !authorized {
+            return Err(SaviaError::Unauthorized);
+        }
+        Ok((approvers, threshold))
+    }
+
+    /// Record `approver`'s signature on a pending disbursement, approving it
+    /// once the threshold is met. Checks everything before writing.
+    fn sign_disbursement(
+        env: &Env,
+        disbursement_id: BytesN<32>,
+        approver: &Address,
+        approvers: &Vec<Address>,
+        threshold: u32,
+    ) -> Result<(), SaviaError> {
+        let mut disbursement: Disbursement = Self::load(env, &DataKey::Disbursement(disbursement_id.clone()))
+            .ok_or(SaviaError::DisbursementNotFound)?;
+
+        if disbursement.status != DisbursementStatus::Pending {
+            return Err(SaviaError::DisbursementNotPending);
+        }
+        if Self::get_vote_tally(env.clone(), disbursement_id.clone()).is_some() {
+            return Err(SaviaError::Unauthorized);
+        }
+        Self::require_not_suspended(env, &disbursement.campaign_id)?;
+
+        if approvers.is_empty() {
+            Self::mark_approved(env, &mut disbursement);
+            return Ok(());
+        }
+
+        let key = DataKey::CampaignData(CampaignKey::Approvals(disbursement_id.clone()));
+        let mut approvals: Vec<Address> = env.storage().persistent().get(&key).unwrap_or(Vec::new(env));
+        if !approvals.contains(approver) {
+            approvals.push_back(approver.clone());
+            env.storage().persistent().set(&key, &approvals);
+        }
+
+        // Signatures of removed approvers no longer count
+        let signed = approvals.iter().filter(|signer| approvers.contains(signer)).count() as u32;
+        publish_between(env, topics::DISBURSEMENT, topics::SIGNED, disbursement.campaign_id.clone(), disbursement.id.clone(), (disbursement_id, approver.clone(), signed, threshold));
+        if signed >= threshold {
+            Self::mark_approved(env, &mut disbursement);
+        }
+        Ok(())
+    }
+
+    /// Move a pending disbursement to `Approved` and announce it
+    fn mark_approved(env: &Env, disbursement: &mut Disbursement) {
+        disbursement.status = DisbursementStatus::Approved;
+        Self::store(env, &DataKey::Disbursement(disbursement.id.clone()), disbursement);
+
+        match Self::get_event_verbosity(env.clone()) {
+            EventVerbosity::Minimal | EventVerbosity::Standard => publish_between(env, topics::DISBURSEMENT, topics::APPROVED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement.id.clone()),
+            EventVerbosity::Verbose => publish_between(env, topics::DISBURSEMENT, topics::APPROVED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement.clone()),
+        }
+    }
+
+    /// Move a pending disbursement to `Rejected`, free its milestone share
+    /// and announce it
+    fn mark_rejected(env: &Env, disbursement: &mut Disbursement, reason: String) {
+        disbursement.status = DisbursementStatus::Rejected;
+        disbursement.rejection_reason = Some(reason.clone());
+        Self::store(env, &DataKey::Disbursement(disbursement.id.clone()), disbursement);
+        Self::release_milestone_commitment(env, disbursement);
+
+        match Self::get_event_verbosity(env.clone()) {
+            EventVerbosity::Minimal => publish_between(env, topics::DISBURSEMENT, topics::REJECTED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement.id.clone()),
+            EventVerbosity::Standard => publish_between(env, topics::DISBURSEMENT, topics::REJECTED, disbursement.campaign_id.clone(), disbursement.id.clone(), (disbursement.id.clone(), reason)),
+            EventVerbosity::Verbose => publish_between(env, topics::DISBURSEMENT, topics::REJECTED, disbursement.campaign_id.clone(), disbursement.id.clone(), disbursement.clone()),
+        }
+    }
+
+    /// Return a withdrawn request's amount to its milestone's allocation
+    fn release_milestone_commitment(env: &Env, disbursement: &Disbursement) {
+        let key = DataKey::CampaignData(CampaignKey::MilestoneCommitted(disbursement.campaign_id.clone(), disbursement.milestone_index));
+        let committed: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage().persistent().set(&key, &(committed - disbursement.amount).max(0));
+    }
+
+    /// Mark a campaign inactive, record whether it met its goal and how it
+    /// ended, and drop it from the active count
+    fn deactivate(env: &Env, campaign: &mut Campaign) {
+        campaign.active = false;
+        campaign.goal_reached = campaign.current_amount >= campaign.goal_amount;
+        campaign.outcome = if campaign.suspended {
+            CampaignOutcome::Suspended
+        } else if campaign.goal_reached {
+            CampaignOutcome::Successful
+        } else if campaign.has_ended(env.ledger().timestamp()) {
+            CampaignOutcome::Unsuccessful
+        } else {
+            CampaignOutcome::ClosedEarly
+        };
+        Self::store(env, &DataKey::Campaign(campaign.id.clone()), campaign);
+        env.storage().persistent().set(
+            &DataKey::CampaignData(CampaignKey::EndedAt(campaign.id.clone())),
+            &env.ledger().timestamp(),
+        );
+
+        let active: u64 = env.storage().instance().get(&DataKey::ActiveCampaignCounter).unwrap_or(0);
+        env.storage().instance().set(&DataKey::ActiveCampaignCounter, &active.saturating_sub(1));
+        Self::update_breakdowns(env, campaign.category, |stats, is_period| {
+            if !is_period {
+                stats.active_campaigns = stats.active_campaigns.saturating_sub(1);
+            }
+        });
+    }
+
+    /// Read a persistent record, extending its TTL if it exists
+    fn load<V: TryFromVal<Env, Val>>(env: &Env, key: &DataKey) -> Option<V> {
+        let value = env.storage().persistent().get(key);
+        if value.is_some() {
+            Self::extend_record(env, key);
+        }
+        value
+    }
+
+    /// Write a persistent record and extend its TTL
+    fn store<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+        env.storage().persistent().set(key, value);
+        Self::extend_record(env, key);
+    }
+
+    /// Extend a persistent record's TTL per the configured policy
+    fn extend_record(env: &Env, key: &DataKey) {
+        let (threshold, extend_to) = Self::get_ttl_config(env.clone());
+        env.storage().persistent().extend_ttl(key, threshold, extend_to);
+    }
+
+    /// Last ledger of a recurring donation allowance: as late as the token
+    /// accepts, since it extends the entry one ledger past the expiration.
+    /// Schedules that outlive it need the donor to approve again.
+    fn allowance_expiration(env: &Env) -> u32 {
+        env.ledger().sequence() + env.storage().max_ttl() - 2
+    }
+
+    /// Page of a donor's donation records, as stored
+    fn donor_donations(env: &Env, donor: &Address, offset: u32, limit: u32) -> Vec<Donation> {
+        let index = IndexKey::DonorDonations(donor.clone());
+        let cursor = Self::cursor_at(env, &index, offset);
+        let (ids, _) = Self::index_page(env, &index, Some(cursor), limit).unwrap_or((Vec::new(env), None));
+
+        let mut donations = Vec::new(env);
+        for donation_id in ids.iter() {
+            if let Some(donation) = Self::load(env, &DataKey::Donation(donation_id)) {
+                donations.push_back(donation);
+            }
+        }
+        donations
+    }
+
+    /// Run every check `donate` makes before moving funds, returning the
+    /// campaign
+    fn check_donation(
+        env: &Env,
+        campaign_id: &BytesN<32>,
+        donor: &Address,
+        amount: i128,
+        privacy: Privacy,
+        message: &String,
+    ) -> Result<Campaign, SaviaError> {
+        Self::require_not_paused(env)?;
+
+        // Validate campaign exists and is active
+        let campaign: Campaign = Self::load(env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        Self::require_accepting(env, &campaign)?;
+
+        if amount <= 0 || amount < campaign.min_donation {
+            return Err(SaviaError::InvalidAmount);
+        }
+
+        if message.len() > MAX_DONATION_MESSAGE_LEN {
+            return Err(SaviaError::InvalidInput);
+        }
+
+        if !Self::is_donor_allowed(env.clone(), campaign_id.clone(), donor.clone()) {
+            return Err(SaviaError::NotWhitelisted);
+        }
+        Self::require_not_blacklisted(env, donor)?;
+
+        if campaign.max_donation_per_donor > 0 {
+            let given = Self::donor_campaign_total(env, campaign_id, donor);
+            if Self::checked_add(given, amount)? > campaign.max_donation_per_donor {
+                return Err(SaviaError::DonationCapExceeded);
+            }
+        }
+
+        Self::require_within_cap(env, &campaign, amount)?;
+
+        if privacy.hides_donor() && Self::disclosure_required(env, campaign_id, donor, amount) {
+            return Err(SaviaError::AnonymityNotAllowed);
+        }
+        Ok(campaign)
+    }
+
+    /// Record a donation whose funds are already in escrow, then release
+    /// waiting pledges and notify the campaign hook
+    fn complete_donation(
+        env: &Env,
+        campaign: &mut Campaign,
+        donor: Address,
+        amount: i128,
+        privacy: Privacy,
+        mint_nft: bool,
+        badge_recipient: Option<Address>,
+        referrer: Option<Address>,
+        message: String,
+    ) -> Result<BytesN<32>, SaviaError> {
+        let verbosity = Self::get_event_verbosity(env.clone());
+        let donation_id = Self::record_donation(
+            env,
+            campaign,
+            donor.clone(),
+            amount,
+            privacy,
+            mint_nft,
+            badge_recipient,
+            referrer,
+            message,
+            verbosity,
+        )?;
+
+        Self::apply_match(env, campaign, amount, verbosity)?;
+
+        // Release any pledges that were waiting on this donor
+        Self::trigger_challenges(env, campaign, &donor, amount, verbosity)?;
+
+        Self::notify_hook(env, &campaign.id, amount);
+
+        Ok(donation_id)
+    }
+
+    /// Draw the sponsor's share of a donation of `amount` from the campaign's
+    /// match pool and record it as a donation from the sponsor. Matches only
+    /// what is left in the pool and under the campaign's overfunding limit.
+    fn apply_match(env: &Env, campaign: &mut Campaign, amount: i128, verbosity: EventVerbosity) -> Result<(), SaviaError> {
+        let key = DataKey::CampaignData(CampaignKey::Match(campaign.id.clone()));
+        let Some(mut pool) = env.storage().persistent().get::<_, MatchPool>(&key) else {
+            return Ok(());
+        };
+        if !campaign.active {
+            return Ok(());
+        }
+
+        // Same overflow-safe basis point share as the fee split
+        let (share, _) = Self::split_fee(amount, pool.ratio_bps as u64);
+        let mut matched = share.min(pool.remaining);
+        if let Some(cap) = campaign.funding_cap() {
+            matched = matched.min(cap - campaign.current_amount);
+        }
+        if matched <= 0 {
+            return Ok(());
+        }
+
+        pool.remaining -= matched;
+        env.storage().persistent().set(&key, &pool);
+
+        let donation_id = Self::record_donation(
+            env,
+            campaign,
+            pool.sponsor.clone(),
+            matched,
+            Privacy::Public,
+            false,
+            None,
+            None,
+            String::from_str(env, ""),
+            verbosity,
+        )?;
+        publish_about(env, topics::CAMPAIGN, topics::MATCHED, campaign.id.clone(), (campaign.id.clone(), donation_id, matched, pool.remaining));
+        Ok(())
+    }
+
+    /// Fail if a donation of `amount` would take the campaign past its
+    /// overfunding limit
+    fn require_within_cap(env: &Env, campaign: &Campaign, amount: i128) -> Result<(), SaviaError> {
+        let (_, net) = Self::split_fee(amount, Self::campaign_fee_rate(env, campaign).0);
+        if let Some(cap) = campaign.funding_cap() {
+            if Self::checked_add(campaign.current_amount, net)? > cap {
+                return Err(SaviaError::GoalExceeded);
+            }
+        }
+        if Self::unverified_headroom(env, campaign).is_some_and(|headroom| net > headroom) {
+            return Err(SaviaError::VerificationRequired);
+        }
+        Ok(())
+    }
+
+    /// Net amount an unverified campaign may still raise, `None` if it is
+    /// verified or the cap is disabled
+    fn unverified_headroom(env: &Env, campaign: &Campaign) -> Option<i128> {
+        let cap = Self::get_unverified_raise_cap(env.clone());
+        if campaign.verified || cap == 0 {
+            return None;
+        }
+        Some((cap - campaign.current_amount).max(0))
+    }
+
+    /// Fail if the campaign has been suspended by the admin
+    fn require_not_suspended(env: &Env, campaign_id: &BytesN<32>) -> Result<(), SaviaError> {
+        let campaign: Campaign = Self::load(env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        if campaign.suspended {
+            return Err(SaviaError::CampaignSuspended);
+        }
+        Ok(())
+    }
+
+    /// Apply `update` to the stats of `category` and of the current period;
+    /// its second argument is whether the stats are the period's
+    fn update_breakdowns<F: Fn(&mut PlatformStats, bool)>(env: &Env, category: Category, update: F) {
+        let epoch = env.ledger().timestamp() / STATS_PERIOD;
+        for (key, is_period) in [
+            (DataKey::Config(ConfigKey::CategoryStats(category)), false),
+            (DataKey::Config(ConfigKey::PeriodStats(epoch)), true),
+        ] {
+            let mut stats: PlatformStats = Self::load(env, &key).unwrap_or_default();
+            update(&mut stats, is_period);
+            Self::store(env, &key, &stats);
+        }
+    }
+
+    /// Fail with `AddressBlocked` if the admin has blacklisted `addr`
+    fn require_not_blacklisted(env: &Env, addr: &Address) -> Result<(), SaviaError> {
+        if Self::is_blacklisted(env.clone(), addr.clone()) {
+            return Err(SaviaError::AddressBlocked);
+        }
+        Ok(())
+    }
+
+    /// Fail unless the campaign is active and has not ended
+    fn require_accepting(env: &Env, campaign: &Campaign) -> Result<(), SaviaError> {
+        if !campaign.active {
+            return Err(SaviaError::CampaignInactive);
+        }
+        if campaign.has_ended(env.ledger().timestamp()) {
+            return Err(SaviaError::CampaignEnded);
+        }
+        if Self::is_frozen(env.clone(), campaign.id.clone()) {
+            return Err(SaviaError::CampaignFrozen);
+        }
+        if Self::is_dispute_hold(env.clone(), campaign.id.clone()) {
+            return Err(SaviaError::CampaignLocked);
+        }
+        Ok(())
+    }
+
+    fn require_not_paused(env: &Env) -> Result<(), SaviaError> {
+        Self::require_initialized(env)?;
+        if Self::is_paused(env.clone()) {
+            return Err(SaviaError::ContractPaused);
+        }
+        Ok(())
+    }
+
+    /// Fail if `initialize` has not run
+    fn require_initialized(env: &Env) -> Result<(), SaviaError> {
+        if !Self::is_initialized(env.clone()) {
+            return Err(SaviaError::NotInitialized);
+        }
+        Ok(())
+    }
+
+    /// Require auth from the configured admin
+    fn require_admin(env: &Env) -> Result<Address, SaviaError> {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin)
+            .ok_or(SaviaError::NotInitialized)?;
+        admin.require_auth();
+        Ok(admin)
+    }
+
+    /// Slice `offset..offset + limit` out of an id index, with `limit` capped
+    /// at `MAX_PAGE_SIZE`
+    fn page(env: &Env, ids: &Vec<BytesN<32>>, offset: u32, limit: u32) -> Vec<BytesN<32>> {
+        let start = offset.min(ids.len());
+        let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(ids.len());
+        if start >= end {
+            return Vec::new(env);
+        }
+        ids.slice(start..end)
+    }
+
+    fn index_meta(env: &Env, index: &IndexKey) -> IndexMeta {
+        env.storage().persistent()
+            .get(&DataKey::IndexMeta(index.clone()))
+            .unwrap_or(IndexMeta { chunks: 0, generation: 0 })
+    }
+
+    /// Append an id to a chunked index, opening a new chunk when the last
+    /// one is full
+    fn index_push(env: &Env, index: IndexKey, id: BytesN<32>) {
+        let mut meta = Self::index_meta(env, &index);
+
+        let mut chunk: Vec<BytesN<32>> = match meta.chunks {
+            0 => Vec::new(env),
+            n => env.storage().persistent()
+                .get(&DataKey::IndexChunk(index.clone(), n - 1))
+                .unwrap_or(Vec::new(env)),
+        };
+        if meta.chunks == 0 || chunk.len() >= INDEX_CHUNK_SIZE {
+            chunk = Vec::new(env);
+            meta.chunks += 1;
+            env.storage().persistent().set(&DataKey::IndexMeta(index.clone()), &meta);
+        }
+
+        chunk.push_back(id);
+        env.storage().persistent().set(&DataKey::IndexChunk(index, meta.chunks - 1), &chunk);
+    }
+
+    /// Read up to `limit` ids (capped at `MAX_PAGE_SIZE`) from a chunked
+    /// index starting at `cursor`, returning the cursor after the last id
+    /// read unless the index is exhausted
+    fn index_page(
+        env: &Env,
+        index: &IndexKey,
+        cursor: Option<Cursor>,
+        limit: u32,
+    ) -> Result<(Vec<BytesN<32>>, Option<Cursor>), SaviaError> {
+        let meta = Self::index_meta(env, index);
+        let mut cursor = cursor.unwrap_or(Cursor { page: 0, offset: 0, generation: meta.generation });
+        if cursor.generation != meta.generation {
+            return Err(SaviaError::StaleCursor);
+        }
+
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let mut ids = Vec::new(env);
+        while ids.len() < limit && cursor.page < meta.chunks {
+            let chunk: Vec<BytesN<32>> = env.storage().persistent()
+                .get(&DataKey::IndexChunk(index.clone(), cursor.page))
+                .unwrap_or(Vec::new(env));
+
+            while ids.len() < limit && cursor.offset < chunk.len() {
+                ids.push_back(chunk.get_unchecked(cursor.offset));
+                cursor.offset += 1;
+            }
+            if cursor.offset >= chunk.len() {
+                cursor.page += 1;
+                cursor.offset = 0;
+            }
+        }
+
+        let next = if cursor.page < meta.chunks { Some(cursor) } else { None };
+        Ok((ids, next))
+    }
+
+    /// Cursor pointing at position `offset` of a chunked index. Every chunk
+    /// but the last is full, so this needs no reads beyond the index meta.
+    fn cursor_at(env: &Env, index: &IndexKey, offset: u32) -> Cursor {
+        Cursor {
+            page: offset / INDEX_CHUNK_SIZE,
+            offset: offset % INDEX_CHUNK_SIZE,
+            generation: Self::index_meta(env, index).generation,
+        }
+    }
+
+    /// Rewrite a chunked index keeping only the ids `keep` accepts. Bumps
+    /// the generation if anything was removed. Returns the number removed.
+    fn index_retain<F: Fn(&BytesN<32>) -> bool>(env: &Env, index: &IndexKey, keep: F) -> u32 {
+        let meta = Self::index_meta(env, index);
+
+        let mut kept = Vec::new(env);
+        let mut removed = 0;
+        for page in 0..meta.chunks {
+            let chunk: Vec<BytesN<32>> = env.storage().persistent()
+                .get(&DataKey::IndexChunk(index.clone(), page))
+                .unwrap_or(Vec::new(env));
+            for id in chunk.iter() {
+                if keep(&id) {
+                    kept.push_back(id);
+                } else {
+                    removed += 1;
+                }
+            }
+        }
+        if removed == 0 {
+            return 0;
+        }
+
+        let mut chunks = 0;
+        let mut start = 0;
+        while start < kept.len() {
+            let end = (start + INDEX_CHUNK_SIZE).min(kept.len());
+            env.storage().persistent().set(&DataKey::IndexChunk(index.clone(), chunks), &kept.slice(start..end));
+            chunks += 1;
+            start = end;
+        }
+        for page in chunks..meta.chunks {
+            env.storage().persistent().remove(&DataKey::IndexChunk(index.clone(), page));
+        }
+
+        let meta = IndexMeta { chunks, generation: meta.generation + 1 };
+        env.storage().persistent().set(&DataKey::IndexMeta(index.clone()), &meta);
+        removed
+    }
+
+    /// Delete a chunked index entirely
+    fn index_clear(env: &Env, index: &IndexKey) {
+        let meta = Self::index_meta(env, index);
+        for page in 0..meta.chunks {
+            env.storage().persistent().remove(&DataKey::IndexChunk(index.clone(), page));
+        }
+        env.storage().persistent().remove(&DataKey::IndexMeta(index.clone()));
+    }
+
+    /// Read an amount from instance storage, accepting the u64 form written
+    /// before amounts moved to i128
+    fn stored_amount(env: &Env, key: &DataKey) -> Option<i128> {
+        let raw: Val = env.storage().instance().get(key)?;
+        Self::amount_from_val(env, raw)
+    }
+
+    fn amount_from_val(env: &Env, raw: Val) -> Option<i128> {
+        i128::try_from_val(env, &raw).ok()
+            .or_else(|| u64::try_from_val(env, &raw).ok().map(|amount| amount as i128))
+    }
+
+    /// Read the disbursement limit table, accepting the u64 form written
+    /// before amounts moved to i128
+    fn stored_disbursement_limits(env: &Env) -> Option<Vec<(u32, i128)>> {
+        let raw: Vec<Val> = env.storage().instance().get(&DataKey::DisbursementLimits)?;
+        let mut limits = Vec::new(env);
+        for entry in raw.iter() {
+            let limit = <(u32, i128)>::try_from_val(env, &entry).ok()
+                .or_else(|| <(u32, u64)>::try_from_val(env, &entry).ok().map(|(level, amount)| (level, amount as i128)))?;
+            limits.push_back(limit);
+        }
+        Some(limits)
+    }
+
+    /// Rewrite a persistent record with `upgrade` if it is still in its
+    /// legacy layout `L`. Returns whether it was rewritten. A struct record
+    /// counts as legacy while its `amount_field` is still a u64; decoding a
+    /// struct with a different field set as `L` would trap, so the field is
+    /// checked first. Bare amounts ignore `amount_field`.
+    fn upgrade_record<L, N, F>(env: &Env, key: &DataKey, amount_field: &str, upgrade: F) -> bool
+    where
+        L: TryFromVal<Env, Val>,
+        N: IntoVal<Env, Val>,
+        F: Fn(L) -> N,
+    {
+        let raw: Val = match env.storage().persistent().get(key) {
+            Some(raw) => raw,
+            None => return false,
+        };
+        if let Ok(fields) = Map::<Symbol, Val>::try_from_val(env, &raw) {
+            let amount = fields.get(Symbol::new(env, amount_field));
+            if amount.is_none_or(|amount| u64::try_from_val(env, &amount).is_err()) {
+                return false;
+            }
+        }
+        match L::try_from_val(env, &raw) {
+            Ok(legacy) => {
+                env.storage().persistent().set(key, &upgrade(legacy));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Client for the token donations are escrowed in
+    fn token_client(env: &Env) -> Result<token::Client<'_>, SaviaError> {
+        let token_address: Address = env.storage().instance().get(&DataKey::Token)
+            .ok_or(SaviaError::NotInitialized)?;
+        Ok(token::Client::new(env, &token_address))
+    }
+
+    /// Client for the token a campaign is denominated in
+    fn campaign_token<'a>(env: &'a Env, campaign: &Campaign) -> token::Client<'a> {
+        token::Client::new(env, &campaign.token)
+    }
+
+    /// Note that something besides a donation was pledged to a campaign,
+    /// which fixes its token
+    fn mark_pledged(env: &Env, campaign_id: &BytesN<32>) {
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::Pledged(campaign_id.clone())), &true);
+    }
+
+    /// Record a donation whose tokens are already in escrow: take the platform
+    /// fee, credit the campaign, store the record and update trust and NFTs
+    fn record_donation(
+        env: &Env,
+        campaign: &mut Campaign,
+        donor: Address,
+        amount: i128,
+        privacy: Privacy,
+        mint_nft: bool,
+        badge_recipient: Option<Address>,
+        referrer: Option<Address>,
+        message: String,
+        verbosity: EventVerbosity,
+    ) -> Result<BytesN<32>, SaviaError> {
+        let campaign_id = campaign.id.clone();
+        let current_time = env.ledger().timestamp();
+        // A badge in a hidden donor's own name would show they gave
+        let mint_nft = mint_nft && (badge_recipient.is_some() || !privacy.hides_donor());
+
+        // Get platform fee, honouring any fee holiday in progress
+        let (platform_fee_rate, holiday) = Self::campaign_fee_rate(env, campaign);
+        if let Some(index) = holiday {
+            Self::mark_fee_holiday_used(env, index);
+        }
+        let (platform_fee, net_amount) = Self::split_fee(amount, platform_fee_rate);
+
+        let stats_key = DataKey::Config(ConfigKey::TokenStats(campaign.token.clone()));
+        let mut token_stats: TokenStats = Self::load(env, &stats_key).unwrap_or_default();
+        token_stats.total_donations += 1;
+        token_stats.total_raised = Self::checked_add(token_stats.total_raised, net_amount)?;
+        token_stats.total_fees_collected = Self::checked_add(token_stats.total_fees_collected, platform_fee)?;
+        Self::store(env, &stats_key, &token_stats);
+
+        // Platform-wide amounts, fee shares included, are in the platform
+        // token; fees in other tokens are held for `withdraw_token_fees`
+        let in_platform_token = env.storage().instance().get(&DataKey::Token) == Some(campaign.token.clone());
+        if in_platform_token {
+            let accrued_fees = Self::stored_amount(env, &DataKey::AccruedFees).unwrap_or(0);
+            env.storage().instance().set(&DataKey::AccruedFees, &Self::checked_add(accrued_fees, platform_fee)?);
+            let total_fees = Self::stored_amount(env, &DataKey::TotalFeesCollected).unwrap_or(0);
+            env.storage().instance().set(&DataKey::TotalFeesCollected, &Self::checked_add(total_fees, platform_fee)?);
+            Self::credit_fee_shares(env, platform_fee)?;
+            let total_raised: i128 = env.storage().instance().get(&DataKey::Config(ConfigKey::TotalRaised)).unwrap_or(0);
+            env.storage().instance().set(&DataKey::Config(ConfigKey::TotalRaised), &Self::checked_add(total_raised, net_amount)?);
+        } else {
+            let fees_key = DataKey::Config(ConfigKey::TokenFees(campaign.token.clone()));
+            let held: i128 = Self::load(env, &fees_key).unwrap_or(0);
+            Self::store(env, &fees_key, &Self::checked_add(held, platform_fee)?);
+        }
+        Self::update_breakdowns(env, campaign.category, |stats, _| {
+            stats.total_donations += 1;
+            if in_platform_token {
+                stats.total_fees_collected = stats.total_fees_collected.saturating_add(platform_fee);
+                stats.total_raised = stats.total_raised.saturating_add(net_amount);
+            }
+        });
+
+        Self::touch_first_seen(env, &donor);
+
+        // Track the donor's running total for the disclosure threshold
+        let given = Self::donor_campaign_total(env, &campaign_id, &donor);
+        let total_key = DataKey::DonorCampaignTotal(campaign_id.clone(), donor.clone());
+        let first_donation = !env.storage().persistent().has(&total_key);
+        env.storage().persistent().set(&total_key, &Self::checked_add(given, amount)?);
+
+        let mut stats = Self::get_campaign_stats(env.clone(), campaign_id.clone());
+        stats.donation_count += 1;
+        if first_donation {
+            stats.unique_donors += 1;
+        }
+        stats.largest_donation = stats.largest_donation.max(net_amount);
+        stats.total_fees = Self::checked_add(stats.total_fees, platform_fee)?;
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::Stats(campaign_id.clone())), &stats);
+
+        // Get and increment donation counter
+        let counter: u64 = env.storage().instance().get(&DataKey::DonationCounter).unwrap_or(0);
+        let new_counter = counter + 1;
+        env.storage().instance().set(&DataKey::DonationCounter, &new_counter);
+
+        // Generate donation ID
+        let mut hash_input = Bytes::new(env);
+        
+        // Convert to bytes properly
+        let campaign_bytes = Bytes::from_slice(env, campaign_id.to_array().as_slice());
+        let donor_bytes = donor.clone().to_xdr(env);
+        
+        hash_input.append(&campaign_bytes);
+        hash_input.append(&donor_bytes);
+        hash_input.append(&Bytes::from_slice(env, &amount.to_be_bytes()));
+        hash_input.append(&Bytes::from_slice(env, &current_time.to_be_bytes()));
+        hash_input.append(&Bytes::from_slice(env, &new_counter.to_be_bytes()));
+        
+        let donation_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+
+        // Create donation record
+        let donation = Donation {
+            id: donation_id.clone(),
+            campaign_id: campaign_id.clone(),
+            donor: donor.clone(),
+            amount: net_amount,
+            timestamp: current_time,
+            nft_minted: mint_nft,
+            privacy,
+            refunded: false,
+            message,
+            badge_recipient: badge_recipient.clone(),
+            referrer: referrer.clone(),
+            platform_fee,
+        };
+
+        // Update campaign progress
+        let previous_amount = campaign.current_amount;
+        campaign.current_amount = Self::checked_add(campaign.current_amount, net_amount)?;
+        if previous_amount < campaign.goal_amount && campaign.current_amount >= campaign.goal_amount {
+            publish_about(env, topics::CAMPAIGN, topics::GOAL_MET, campaign_id.clone(), (campaign_id.clone(), campaign.current_amount));
+            Self::push_recently_funded(env, &campaign_id);
+        }
+        if campaign.goal_reached_at.is_none() && campaign.current_amount >= campaign.goal_amount {
+            campaign.goal_reached_at = Some(current_time);
+            publish_about(env, topics::CAMPAIGN, topics::FUNDED, campaign_id.clone(), (campaign_id.clone(), campaign.current_amount, current_time));
+            if campaign.close_on_goal {
+                Self::deactivate(env, campaign);
+            }
+        }
+        Self::flag_if_at_risk(env, campaign);
+        Self::store(env, &DataKey::Campaign(campaign_id.clone()), campaign);
+
+        // Store donation
+        Self::store(env, &DataKey::Donation(donation_id.clone()), &donation);
+        Self::index_push(env, IndexKey::CampaignDonations(campaign_id.clone()), donation_id.clone());
+        Self::index_push(env, IndexKey::DonorDonations(donor.clone()), donation_id.clone());
+        Self::index_push(env, IndexKey::DonationsByDay(current_time / DAY), donation_id.clone());
+
+        // Only publish what the donor agreed to make public
+        let public_donor = if privacy.hides_donor() { None } else { Some(donor.clone()) };
+        let public_amount = if privacy.hides_amount() { None } else { Some(net_amount) };
+        // Hidden donors are filed under the contract's own address
+        let donor_topic = public_donor.clone().unwrap_or_else(|| env.current_contract_address());
+        match verbosity {
+            EventVerbosity::Minimal => publish_between(env, topics::DONATION, topics::MADE, campaign_id.clone(), donor_topic, donation_id.clone()),
+            EventVerbosity::Standard => publish_between(
+                env,
+                topics::DONATION,
+                topics::MADE,
+                campaign_id.clone(),
+                donor_topic,
+                (donation_id.clone(), campaign_id.clone(), public_donor, public_amount),
+            ),
+            EventVerbosity::Verbose => publish_between(
+                env,
+                topics::DONATION,
+                topics::MADE,
+                campaign_id.clone(),
+                donor_topic,
+                (donation_id.clone(), campaign_id.clone(), public_donor, public_amount, current_time, privacy, mint_nft),
+            ),
+        }
+
+        // Update trust score
+        Self::update_donor_trust_score(env.clone(), donor.clone(), net_amount, privacy.hides_donor())?;
+
+        // Badges reflect everything the donor has given this campaign
+        let net_key = DataKey::DonorNetTotal(campaign_id.clone(), donor.clone());
+        let net_total = Self::checked_add(env.storage().persistent().get(&net_key).unwrap_or(0), net_amount)?;
+        env.storage().persistent().set(&net_key, &net_total);
+
+        // Only fully public donations count toward the leaderboard
+        if privacy == Privacy::Public {
+            Self::update_leaderboard(env, &campaign_id, &donor, net_amount)?;
+        }
+
+        if let Some(referrer) = referrer {
+            Self::credit_referral(env, referrer, &donation_id, net_amount)?;
+        }
+
+        // A gift badge stands alone, at the tier of the gift itself
+        match (mint_nft, badge_recipient) {
+            (true, Some(recipient)) => {
+                Self::mint_donation_nft(env.clone(), recipient, campaign_id, donation_id.clone(), net_amount)?;
+            }
+            (true, None) => {
+                Self::award_donation_badge(env, donor, campaign_id, donation_id.clone(), net_total)?;
+            }
+            (false, _) => {}
+        }
+
+        Ok(donation_id)
+    }
+
+    /// Authorize as the campaign's beneficiary
+    fn require_beneficiary(env: &Env, campaign_id: &BytesN<32>) -> Result<Campaign, SaviaError> {
+        let campaign: Campaign = Self::load(env, &DataKey::Campaign(campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+        campaign.beneficiary.require_auth();
+        Ok(campaign)
+    }
+
+    /// The campaign's donor whitelist, empty if none was set
+    fn whitelist(env: &Env, campaign_id: &BytesN<32>) -> Map<Address, bool> {
+        env.storage().persistent()
+            .get(&DataKey::CampaignData(CampaignKey::Whitelist(campaign_id.clone())))
+            .unwrap_or(Map::new(env))
+    }
+
+    fn store_whitelist(env: &Env, campaign_id: &BytesN<32>, whitelist: &Map<Address, bool>) -> Result<(), SaviaError> {
+        if whitelist.len() > MAX_WHITELIST_SIZE {
+            return Err(SaviaError::InvalidInput);
+        }
+        Self::store(env, &DataKey::CampaignData(CampaignKey::Whitelist(campaign_id.clone())), whitelist);
+        Ok(())
+    }
+
+    /// Count a referred donation toward the referrer's stats and grant them
+    /// the referral trust bonus, up to its cap
+    fn credit_referral(env: &Env, referrer: Address, donation_id: &BytesN<32>, net_amount: i128) -> Result<(), SaviaError> {
+        let key = DataKey::Config(ConfigKey::ReferralStats(referrer.clone()));
+        let mut stats = Self::get_referral_stats(env.clone(), referrer.clone());
+        stats.referred_count += 1;
+        stats.referred_volume = Self::checked_add(stats.referred_volume, net_amount)?;
+        if stats.trust_bonus < MAX_REFERRAL_TRUST_BONUS {
+            stats.trust_bonus += REFERRAL_TRUST_BONUS;
+            Self::apply_trust_adjustment(env, &referrer, REFERRAL_TRUST_BONUS as i32, String::from_str(env, "referral"), TrustChangeReason::Referral);
+        }
+        Self::store(env, &key, &stats);
+
+        publish(env, topics::REFERRAL, topics::CREDITED, (referrer, donation_id.clone(), net_amount));
+        Ok(())
+    }
+
+    /// Add a donation to a registered fundraiser's tally, granting a trust
+    /// bonus and, once they pass the thresholds, an Ambassador badge.
+    /// Unregistered fundraisers are ignored.
+    fn credit_fundraiser(
+        env: &Env,
+        campaign_id: &BytesN<32>,
+        fundraiser: Address,
+        donation_id: &BytesN<32>,
+        net_amount: i128,
+    ) -> Result<(), SaviaError> {
+        let key = DataKey::CampaignData(CampaignKey::FundraiserStats(campaign_id.clone(), fundraiser.clone()));
+        let Some(mut stats) = Self::load::<FundraiserStats>(env, &key) else {
+            return Ok(());
+        };
+        stats.donation_count += 1;
+        stats.raised = Self::checked_add(stats.raised, net_amount)?;
+        if stats.trust_bonus < MAX_FUNDRAISER_TRUST_BONUS {
+            stats.trust_bonus += FUNDRAISER_TRUST_BONUS;
+            Self::apply_trust_adjustment(env, &fundraiser, FUNDRAISER_TRUST_BONUS as i32, String::from_str(env, "fundraising"), TrustChangeReason::Fundraising);
+        }
+
+        let thresholds = Self::get_ambassador_thresholds(env.clone());
+        if !stats.ambassador
+            && stats.raised >= thresholds.min_raised
+            && stats.donation_count >= thresholds.min_donations
+        {
+            Self::mint_badge(env.clone(), fundraiser.clone(), campaign_id.clone(), donation_id.clone(), stats.raised, BadgeTier::Ambassador)?;
+            stats.ambassador = true;
+        }
+        Self::store(env, &key, &stats);
+
+        publish_between(env, topics::FUNDRAISER, topics::CREDITED, campaign_id.clone(), fundraiser, (donation_id.clone(), net_amount, stats.raised));
+        Ok(())
+    }
+
+    /// Add a public donation to the donor's public total for the campaign and
+    /// re-rank them on its leaderboard. Ties keep the donor who got there
+    /// first ahead.
+    fn update_leaderboard(env: &Env, campaign_id: &BytesN<32>, donor: &Address, net_amount: i128) -> Result<(), SaviaError> {
+        let total_key = DataKey::CampaignData(CampaignKey::PublicDonorTotal(campaign_id.clone(), donor.clone()));
+        let total = Self::checked_add(env.storage().persistent().get(&total_key).unwrap_or(0), net_amount)?;
+        env.storage().persistent().set(&total_key, &total);
+
+        let mut board = Self::get_leaderboard(env.clone(), campaign_id.clone());
+        if let Some(current) = board.iter().position(|(address, _)| address == *donor) {
+            board.remove(current as u32);
+        }
+        let rank = board.iter().position(|(_, amount)| amount < total).unwrap_or(board.len() as usize) as u32;
+        if rank >= LEADERBOARD_SIZE {
+            return Ok(());
+        }
+        board.insert(rank, (donor.clone(), total));
+        if board.len() > LEADERBOARD_SIZE {
+            board.pop_back();
+        }
+
+        env.storage().persistent().set(&DataKey::CampaignData(CampaignKey::Leaderboard(campaign_id.clone())), &board);
+        Ok(())
+    }
+
+    /// Key claiming a campaign title for duplicate detection
+    fn title_key(env: &Env, title: &String) -> DataKey {
+        let title_bytes = title.to_string().into_bytes();
+        DataKey::TitleHash(env.crypto().sha256(&Bytes::from_slice(env, &title_bytes)).into())
+    }
+
+    /// Remember when an address first interacted with the platform
+    fn touch_first_seen(env: &Env, entity: &Address) {
+        let key = DataKey::FirstSeen(entity.clone());
+        if !env.storage().persistent().has(&key) {
+            env.storage().persistent().set(&key, &env.ledger().timestamp());
+        }
+    }
+
+    /// Weighted sum of the risk signals, each scaled to 0-100, capped at 100
+    fn risk_score(env: &Env, campaign: &Campaign) -> u32 {
+        let weights = Self::get_risk_weights(env.clone());
+        let current_time = env.ledger().timestamp();
+
+        let first_seen: u64 = env.storage().persistent()
+            .get(&DataKey::FirstSeen(campaign.beneficiary.clone()))
+            .unwrap_or(current_time);
+        let age = current_time.saturating_sub(first_seen).min(NEW_ACCOUNT_PERIOD);
+        let new_beneficiary = (NEW_ACCOUNT_PERIOD - age) * 100 / NEW_ACCOUNT_PERIOD;
+
+        let level = Self::get_trust_score(env.clone(), campaign.beneficiary.clone())
+            .map(|trust| trust.verification_level)
+            .unwrap_or(0);
+        let unverified = match level {
+            0 => 100,
+            1 => 50,
+            _ => 0,
+        };
+
+        // Full strength once the goal is twice the cap
+        let cap = Self::get_disbursement_limit(env.clone(), campaign.beneficiary.clone()).max(1);
+        let goal_over_cap = (campaign.goal_amount.saturating_sub(cap).max(0).saturating_mul(100) / cap).min(100) as u64;
+
+        let duplicate_title = if campaign.possible_duplicate { 100 } else { 0 };
+
+        let self_given = Self::donor_campaign_total(env, &campaign.id, &campaign.beneficiary);
+        let self_donations = (self_given.saturating_mul(100) / campaign.current_amount.max(1)).min(100) as u64;
+
+        let reports: u32 = env.storage().persistent()
+            .get(&DataKey::CampaignReports(campaign.id.clone()))
+            .unwrap_or(0);
+        let reports = reports.min(MAX_COUNTED_REPORTS) as u64 * 100 / MAX_COUNTED_REPORTS as u64;
+
+        let score = weights.new_beneficiary as u64 * new_beneficiary
+            + weights.unverified as u64 * unverified
+            + weights.goal_over_cap as u64 * goal_over_cap
+            + weights.duplicate_title as u64 * duplicate_title
+            + weights.self_donations as u64 * self_donations
+            + weights.reports as u64 * reports;
+        (score / 100).min(100) as u32
+    }
+
+    /// Whether a donation of `amount` would take the donor's total for the
+    /// campaign to or past the identity disclosure threshold
+    fn disclosure_required(env: &Env, campaign_id: &BytesN<32>, donor: &Address, amount: i128) -> bool {
+        let threshold = match Self::stored_amount(env, &DataKey::KycDisclosureThreshold) {
+            Some(threshold) => threshold,
+            None => return false,
+        };
+        Self::donor_campaign_total(env, campaign_id, donor).saturating_add(amount) >= threshold
+    }
+
+    /// What `donor` has given to a campaign so far, accepting the u64 form
+    /// written before amounts moved to i128
+    fn donor_campaign_total(env: &Env, campaign_id: &BytesN<32>, donor: &Address) -> i128 {
+        env.storage().persistent()
+            .get::<_, Val>(&DataKey::DonorCampaignTotal(campaign_id.clone(), donor.clone()))
+            .and_then(|raw| Self::amount_from_val(env, raw))
+            .unwrap_or(0)
+    }
+
+    /// Release open challenges on this campaign that name `donor` as the
+    /// counterpart and whose threshold `amount` meets
+    fn trigger_challenges(
+        env: &Env,
+        campaign: &mut Campaign,
+        donor: &Address,
+        amount: i128,
+        verbosity: EventVerbosity,
+    ) -> Result<(), SaviaError> {
+        let key = DataKey::OpenChallenges(campaign.id.clone(), donor.clone());
+        let open: Vec<BytesN<32>> = match env.storage().persistent().get(&key) {
+            Some(open) => open,
+            None => return Ok(()),
+        };
+
+        let current_time = env.ledger().timestamp();
+        let mut still_open = Vec::new(env);
+        for challenge_id in open.iter() {
+            let mut challenge: Challenge = env.storage().persistent().get(&DataKey::Challenge(challenge_id.clone()))
+                .ok_or(SaviaError::ChallengeNotFound)?;
+
+            if current_time > challenge.deadline || amount < challenge.min_counterpart_amount {
+                still_open.push_back(challenge_id);
+                continue;
+            }
+
+            let donation_id = Self::record_donation(
+                env,
+                campaign,
+                challenge.challenger.clone(),
+                challenge.amount,
+                Privacy::Public,
+                false,
+                None,
+                None,
+                String::from_str(env, ""),
+                verbosity,
+            )?;
+            challenge.status = ChallengeStatus::Met;
+            env.storage().persistent().set(&DataKey::Challenge(challenge_id.clone()), &challenge);
+
+            publish(env, topics::CHALLENGE, topics::TRIGGERED, (challenge_id, donation_id));
+        }
+
+        if still_open.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &still_open);
+        }
+        Ok(())
+    }
+
+    /// Call the campaign's donation hook, if any. A failing hook is reported
+    /// through a `hook_failed` event and never fails the donation.
+    fn notify_hook(env: &Env, campaign_id: &BytesN<32>, amount: i128) {
+        let hook: Address = match env.storage().persistent().get(&DataKey::CampaignHook(campaign_id.clone())) {
+            Some(hook) => hook,
+            None => return,
+        };
+
+        let args: Vec<Val> = (campaign_id.clone(), amount).into_val(env);
+        let result = env.try_invoke_contract::<Val, SaviaError>(
+            &hook,
+            &Symbol::new(env, "on_donation"),
+            args,
+        );
+        if !matches!(result, Ok(Ok(_))) {
+            publish_about(env, topics::CAMPAIGN, topics::HOOK_FAILED, campaign_id.clone(), (campaign_id.clone(), hook));
+        }
+    }
+
+    /// Platform fee rate in basis points at the current ledger time, with the
+    /// index of the fee holiday supplying it (if any)
+    fn effective_fee_rate(env: &Env) -> (u64, Option<u32>) {
+        let current_time = env.ledger().timestamp();
+        let holidays: Vec<FeeHoliday> = env.storage().instance().get(&DataKey::FeeHolidays).unwrap_or(Vec::new(env));
+        for (index, holiday) in holidays.iter().enumerate() {
+            if holiday.start <= current_time && current_time < holiday.end {
+                return (holiday.fee_bps, Some(index as u32));
+            }
+        }
+
+        // Nothing is charged before initialize sets the fee
+        (Self::get_platform_fee(env.clone()).unwrap_or(0), None)
+    }
+
+    /// Fee rate for a donation to `campaign` right now: its override if set,
+    /// else 0 if the verified waiver covers it, else `effective_fee_rate`
+    fn campaign_fee_rate(env: &Env, campaign: &Campaign) -> (u64, Option<u32>) {
+        if let Some(fee_bps) = Self::get_campaign_fee(env.clone(), campaign.id.clone()) {
+            return (fee_bps, None);
+        }
+        if campaign.verified
+            && campaign.trust_score >= FEE_WAIVER_MIN_TRUST_SCORE
+            && Self::get_verified_fee_waiver(env.clone())
+        {
+            return (0, None);
+        }
+        Self::effective_fee_rate(env)
+    }
+
+    /// Divide a collected fee between the fee recipients, if a split is set.
+    /// The first recipient takes what rounding leaves over.
+    fn credit_fee_shares(env: &Env, fee: i128) -> Result<(), SaviaError> {
+        let recipients = Self::get_fee_recipients(env.clone());
+        if recipients.is_empty() || fee == 0 {
+            return Ok(());
+        }
+
+        let storage = env.storage().instance();
+        let mut remaining = fee;
+        for (recipient, share_bps) in recipients.iter().skip(1) {
+            let (share, _) = Self::split_fee(fee, share_bps as u64);
+            remaining -= share;
+            let key = DataKey::Config(ConfigKey::FeeShare(recipient));
+            storage.set(&key, &Self::checked_add(storage.get(&key).unwrap_or(0), share)?);
+        }
+        if let Some((first, _)) = recipients.first() {
+            let key = DataKey::Config(ConfigKey::FeeShare(first));
+            storage.set(&key, &Self::checked_add(storage.get(&key).unwrap_or(0), remaining)?);
+        }
+
+        let unclaimed: i128 = storage.get(&DataKey::Config(ConfigKey::UnclaimedFeeShares)).unwrap_or(0);
+        storage.set(&DataKey::Config(ConfigKey::UnclaimedFeeShares), &Self::checked_add(unclaimed, fee)?);
+        Ok(())
+    }
+
+    /// Split a gross amount into (fee, net) at the given rate
+    fn split_fee(amount: i128, fee_bps: u64) -> (i128, i128) {
+        // Split the multiplication so amounts near i128::MAX don't overflow
+        // the intermediate product
+        let bps = fee_bps as i128;
+        let fee = amount / 10000 * bps + amount % 10000 * bps / 10000;
+        (fee, amount - fee)
+    }
+
+    /// `a + b`, or `Overflow` if the sum does not fit in an i128
+    fn checked_add(a: i128, b: i128) -> Result<i128, SaviaError> {
+        a.checked_add(b).ok_or(SaviaError::Overflow)
+    }
+
+    /// Flag a fee holiday as used, announcing it the first time
+    fn mark_fee_holiday_used(env: &Env, index: u32) {
+        let mut holidays: Vec<FeeHoliday> = env.storage().instance().get(&DataKey::FeeHolidays).unwrap_or(Vec::new(env));
+        if let Some(mut holiday) = holidays.get(index) {
+            if !holiday.used {
+                holiday.used = true;
+                holidays.set(index, holiday.clone());
+                env.storage().instance().set(&DataKey::FeeHolidays, &holidays);
+                publish(env, topics::FEE, topics::HOLIDAY, (holiday.start, holiday.end, holiday.fee_bps));
+            }
+        }
+    }
+
+    /// Public view of a donation: a hidden donor is replaced by the contract
+    /// address and a hidden amount by 0
+    fn redact_donation(env: &Env, mut donation: Donation) -> Donation {
+        if donation.privacy.hides_donor() {
+            donation.donor = env.current_contract_address();
+        }
+        if donation.privacy.hides_amount() {
+            donation.amount = 0;
+            donation.platform_fee = 0;
+        }
+        donation
+    }
+
+    /// Serialize a donation into its canonical attestation payload
+    fn attestation_payload(env: &Env, donation: &Donation) -> Bytes {
+        let mut payload = Bytes::new(env);
+
+        payload.push_back(ATTESTATION_VERSION);
+        payload.append(&env.current_contract_address().to_xdr(env));
+        payload.append(&Bytes::from_slice(env, env.ledger().network_id().to_array().as_slice()));
+        payload.append(&Bytes::from_slice(env, donation.id.to_array().as_slice()));
+        payload.append(&Bytes::from_slice(env, donation.campaign_id.to_array().as_slice()));
+        payload.append(&donation.donor.clone().to_xdr(env));
+        payload.append(&Bytes::from_slice(env, &donation.amount.to_be_bytes()));
+        payload.append(&Bytes::from_slice(env, &donation.timestamp.to_be_bytes()));
+        payload.push_back(donation.nft_minted as u8);
+        payload.push_back(donation.privacy as u8);
+        payload.push_back(donation.refunded as u8);
+
+        payload
+    }
+
+    /// Build the receipt for a donation, hash included
+    fn receipt(env: &Env, donation: Donation) -> Result<Receipt, SaviaError> {
+        let campaign: Campaign = Self::load(env, &DataKey::Campaign(donation.campaign_id.clone()))
+            .ok_or(SaviaError::CampaignNotFound)?;
+
+        let mut receipt = Receipt {
+            donation_id: donation.id,
+            campaign_id: donation.campaign_id,
+            campaign_title: campaign.title,
+            beneficiary: campaign.beneficiary,
+            donor: donation.donor,
+            gross_amount: Self::checked_add(donation.amount, donation.platform_fee)?,
+            fee: donation.platform_fee,
+            net_amount: donation.amount,
+            timestamp: donation.timestamp,
+            refunded: donation.refunded,
+            receipt_hash: BytesN::from_array(env, &[0; 32]),
+        };
+        receipt.receipt_hash = Self::receipt_hash(env, &receipt);
+        Ok(receipt)
+    }
+
+    /// Hash a receipt's canonical encoding, leaving out `receipt_hash`.
+    /// Layout (version 1): version byte, contract address XDR, network id,
+    /// donation id, campaign id, title XDR, beneficiary XDR, donor XDR,
+    /// gross, fee, net, timestamp, refunded byte. Integers are big-endian.
+    fn receipt_hash(env: &Env, receipt: &Receipt) -> BytesN<32> {
+        let mut payload = Bytes::new(env);
+
+        payload.push_back(RECEIPT_VERSION);
+        payload.append(&env.current_contract_address().to_xdr(env));
+        payload.append(&Bytes::from_slice(env, env.ledger().network_id().to_array().as_slice()));
+        payload.append(&Bytes::from_slice(env, receipt.donation_id.to_array().as_slice()));
+        payload.append(&Bytes::from_slice(env, receipt.campaign_id.to_array().as_slice()));
+        payload.append(&receipt.campaign_title.clone().to_xdr(env));
+        payload.append(&receipt.beneficiary.clone().to_xdr(env));
+        payload.append(&receipt.donor.clone().to_xdr(env));
+        payload.append(&Bytes::from_slice(env, &receipt.gross_amount.to_be_bytes()));
+        payload.append(&Bytes::from_slice(env, &receipt.fee.to_be_bytes()));
+        payload.append(&Bytes::from_slice(env, &receipt.net_amount.to_be_bytes()));
+        payload.append(&Bytes::from_slice(env, &receipt.timestamp.to_be_bytes()));
+        payload.push_back(receipt.refunded as u8);
+
+        env.crypto().sha256(&payload).into()
+    }
+
+    /// Publish a trust score change from `old_score` at the configured
+    /// verbosity; Minimal skips it
+    fn publish_trust_event(env: &Env, action: &str, old_score: u32, trust_score: &TrustScore, reason: TrustChangeReason) {
+        let entity = trust_score.entity.clone();
+        match Self::get_event_verbosity(env.clone()) {
+            EventVerbosity::Minimal => {}
+            EventVerbosity::Standard => publish_about(env, topics::TRUST, action, entity, (old_score, trust_score.score, reason)),
+            EventVerbosity::Verbose => publish_about(env, topics::TRUST, action, entity, (old_score, trust_score.score, reason, trust_score.clone())),
+        }
+    }
+
+    /// Flag a campaign that is under 25% funded with under 20% of its time
+    /// left, emitting `at_risk` once. Returns true if the flag was just set.
+    fn flag_if_at_risk(env: &Env, campaign: &mut Campaign) -> bool {
+        let current_time = env.ledger().timestamp();
+        if campaign.at_risk || campaign.perpetual || !campaign.active || campaign.has_ended(current_time) {
+            return false;
+        }
+
+        let funding_bps = match campaign.current_amount.checked_mul(10000) {
+            Some(scaled) => scaled / campaign.goal_amount,
+            None => campaign.current_amount / (campaign.goal_amount / 10000).max(1),
+        }.min(10000) as u64;
+        let seconds_remaining = campaign.end_time - current_time;
+        let duration = campaign.end_time - campaign.start_time;
+
+        if funding_bps >= 2500 || seconds_remaining * 5 >= duration {
+            return false;
+        }
+
+        campaign.at_risk = true;
+        publish_about(env, topics::CAMPAIGN, topics::AT_RISK, campaign.id.clone(), (campaign.id.clone(), funding_bps, seconds_remaining));
+        true
+    }
+
+    /// Whether the campaign is in its final window before `end_time` without
+    /// having reached its goal, so disbursements are locked
+    fn in_endgame(env: &Env, campaign: &Campaign) -> bool {
+        if campaign.perpetual || campaign.current_amount >= campaign.goal_amount {
+            return false;
+        }
+        let current_time = env.ledger().timestamp();
+        let window = Self::get_endgame_window(env.clone());
+        current_time <= campaign.end_time && current_time.saturating_add(window) >= campaign.end_time
+    }
+
+    /// Emit `endgame_locked` the first time the lock is seen to be engaged.
+    /// Returns true if the flag was just set.
+    fn flag_if_endgame_locked(env: &Env, campaign: &mut Campaign) -> bool {
+        if campaign.endgame_locked || !Self::in_endgame(env, campaign) {
+            return false;
+        }
+
+        campaign.endgame_locked = true;
+        publish_about(env, topics::CAMPAIGN, topics::ENDGAME_LOCKED, campaign.id.clone(), (campaign.id.clone(), campaign.end_time));
+        true
+    }
+
+    /// Helper function to determine badge type based on amount
+    fn get_badge_type(env: &Env, amount: i128) -> String {
+        String::from_str(env, BadgeTier::for_amount(amount).name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::boxed::Box;
+    use soroban_sdk::{testutils::{storage::Persistent as _, Address as _, Events, Ledger, MockAuth, MockAuthInvoke}, Address, Env, InvokeError, TryFromVal};
+
+    fn setup(env: &Env) -> SaviaContractClient<'_> {
+        let contract_id = env.register_contract(None, SaviaContract);
+        let client = SaviaContractClient::new(env, &contract_id);
+        env.mock_all_auths();
+        let token = env.register_stellar_asset_contract_v2(Address::generate(env));
+        client.initialize(&Address::generate(env), &token.address(), &200);
+        client
+    }
+
+    fn token_address(env: &Env, client: &SaviaContractClient) -> Address {
+        env.as_contract(&client.address, || env.storage().instance().get(&DataKey::Token).unwrap())
+    }
+
+    /// A fresh address holding `balance` of the donation token
+    fn funded_donor(env: &Env, client: &SaviaContractClient, balance: i128) -> Address {
+        let donor = Address::generate(env);
+        token::StellarAssetClient::new(env, &token_address(env, client)).mint(&donor, &balance);
+        donor
+    }
+
+    fn create_test_campaign(env: &Env, client: &SaviaContractClient, beneficiary: &Address) -> BytesN<32> {
+        client.create_campaign(
+            beneficiary,
+            &String::from_str(env, "Test Campaign"),
+            &String::from_str(env, "A test campaign"),
+            &10000,
+            &30,
+            &Category::Health,
+            &String::from_str(env, "Test City"),
+            &false,
+            &FundingMode::KeepItAll,
+        )
+    }
+
+    /// Data payloads of every event published under `(subject, action)`
+    fn events_for(env: &Env, subject: &str, action: &str) -> soroban_sdk::Vec<Val> {
+        let expected: soroban_sdk::Vec<Val> = (Symbol::new(env, subject), Symbol::new(env, action)).into_val(env);
+        let mut found = soroban_sdk::Vec::new(env);
+        for (_, event_topics, data) in env.events().all().iter() {
+            // Ids published as trailing topics don't affect the match
+            if event_topics.len() >= 2 && event_topics.slice(0..2) == expected {
+                found.push_back(data);
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn test_event_topics_frozen() {
+        let env = Env::default();
+        let frozen = [
+            (topics::CAMPAIGN, "campaign"),
+            (topics::DONATION, "donation"),
+            (topics::DISBURSEMENT, "disbursement"),
+            (topics::NFT, "nft"),
+            (topics::TRUST, "trust"),
+            (topics::PROFILE, "profile"),
+            (topics::CONFIG, "config"),
+            (topics::ADMIN, "admin"),
+            (topics::CHALLENGE, "challenge"),
+            (topics::FEE, "fee"),
+            (topics::REFERRAL, "referral"),
+            (topics::DISPUTE, "dispute"),
+            (topics::BUNDLE, "bundle"),
+            (topics::FUNDRAISER, "fundraiser"),
+            (topics::INITIALIZED, "initialized"),
+            (topics::CREATED, "created"),
+            (topics::VERIFIED, "verified"),
+            (topics::QUEUED, "queued"),
+            (topics::REPORTED, "reported"),
+            (topics::RISK, "risk"),
+            (topics::MADE, "made"),
+            (topics::MINTED, "minted"),
+            (topics::TRANSFERRED, "transferred"),
+            (topics::UPGRADED, "upgraded"),
+            (topics::MILESTONES, "milestones"),
+            (topics::VOTING, "voting"),
+            (topics::VOTED, "voted"),
+            (topics::TRANSFERABLE, "transferable"),
+            (topics::METADATA, "metadata"),
+            (topics::EXECUTED, "executed"),
+            (topics::UPDATED, "updated"),
+            (topics::SCHEDULED, "scheduled"),
+            (topics::HOLIDAY, "holiday"),
+            (topics::LIMITS, "limits"),
+            (topics::MATCH, "match"),
+            (topics::MATCHED, "matched"),
+            (topics::GOAL_MET, "goal_met"),
+            (topics::FUNDED, "funded"),
+            (topics::CLOSE_ON_GOAL, "close_on_goal"),
+            (topics::POSTED, "posted"),
+            (topics::BURNED, "burned"),
+            (topics::OVERRIDE, "override"),
+            (topics::WAIVER, "waiver"),
+            (topics::RECIPIENTS, "recipients"),
+            (topics::CLAIMED, "claimed"),
+            (topics::CREDITED, "credited"),
+            (topics::WHITELIST, "whitelist"),
+            (topics::BLACKLIST, "blacklist"),
+            (topics::APPROVERS, "approvers"),
+            (topics::SIGNED, "signed"),
+            (topics::VALIDITY, "validity"),
+            (topics::TOKEN, "token"),
+            (topics::OVERFUNDING, "overfunding"),
+            (topics::APPROVED, "approved"),
+            (topics::REJECTED, "rejected"),
+            (topics::MIGRATED, "migrated"),
+            (topics::WITHDRAWN, "withdrawn"),
+            (topics::VERBOSITY, "verbosity"),
+            (topics::KYC_THRESHOLD, "kyc_threshold"),
+            (topics::IMPORTED, "imported"),
+            (topics::ADJUSTED, "adjusted"),
+            (topics::PENALTY, "penalty"),
+            (topics::DECAY, "decay"),
+            (topics::DECAYED, "decayed"),
+            (topics::MIN_LEVEL, "min_level"),
+            (topics::BOOTSTRAP, "bootstrap"),
+            (topics::PROPOSED, "proposed"),
+            (topics::PAUSED, "paused"),
+            (topics::UNPAUSED, "unpaused"),
+            (topics::ACCEPTED, "accepted"),
+            (topics::CANCELLED, "cancelled"),
+            (topics::CLOSED, "closed"),
+            (topics::SUSPENDED, "suspended"),
+            (topics::FINALIZED, "finalized"),
+            (topics::COMPACTED, "compacted"),
+            (topics::REFUNDED, "refunded"),
+            (topics::TRIGGERED, "triggered"),
+            (topics::EXPIRED, "expired"),
+            (topics::AT_RISK, "at_risk"),
+            (topics::ENDGAME_LOCKED, "endgame_locked"),
+            (topics::ENDGAME, "endgame"),
+            (topics::TTL, "ttl"),
+            (topics::CATEGORY_ADDED, "category_added"),
+            (topics::CATEGORY_REMOVED, "category_removed"),
+            (topics::LABEL, "label"),
+            (topics::HOOK_REGISTERED, "hook_registered"),
+            (topics::HOOK_REMOVED, "hook_removed"),
+            (topics::HOOK_FAILED, "hook_failed"),
+            (topics::ACKNOWLEDGED, "acknowledged"),
+            (topics::REOPEN_REQUESTED, "reopen_requested"),
+            (topics::REOPENED, "reopened"),
+            (topics::CREATION_LIMITS, "creation_limits"),
+            (topics::EXEMPT, "exempt"),
+            (topics::UNVERIFIED_CAP, "unverified_cap"),
+            (topics::ARCHIVED, "archived"),
+            (topics::ARCHIVE_GRACE, "archive_grace"),
+            (topics::METADATA_BASE, "metadata_base"),
+            (topics::FILED, "filed"),
+            (topics::RESOLVED, "resolved"),
+            (topics::HELD, "held"),
+            (topics::RELEASED, "released"),
+            (topics::DISPUTE_THRESHOLD, "dispute_threshold"),
+            (topics::DONATED, "donated"),
+            (topics::REGISTERED, "registered"),
+            (topics::AMBASSADOR, "ambassador"),
+            (topics::FROZEN, "frozen"),
+            (topics::UNFROZEN, "unfrozen"),
+        ];
+
+        for (topic, expected) in frozen {
+            assert_eq!(topic, expected);
+            assert_eq!(Symbol::new(&env, topic), Symbol::new(&env, expected));
+        }
+    }
+
+    #[test]
+    fn test_initialize_contract() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, SaviaContract);
+        let client = SaviaContractClient::new(&env, &contract_id);
+
+        let result = client.try_initialize(&Address::generate(&env), &Address::generate(&env), &200);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_create_campaign() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, SaviaContract);
+        let client = SaviaContractClient::new(&env, &contract_id);
+        env.mock_all_auths();
+
+        client.initialize(&Address::generate(&env), &Address::generate(&env), &200);
+
+        let beneficiary = Address::generate(&env);
+        let campaign_id = client.create_campaign(
+            &beneficiary,
+            &String::from_str(&env, "Test Campaign"),
+            &String::from_str(&env, "A test campaign"),
+            &10000,
+            &30,
+            &Category::Health,
+            &String::from_str(&env, "Test City"),
+            &false,
+            &FundingMode::KeepItAll,
+        );
+
+        let campaign = client.get_campaign(&campaign_id);
+        assert!(campaign.is_some());
+    }
+
+    #[test]
+    fn test_donation_flow() {
+        let env = Env::default();
+        let client = setup(&env);
+
+        let beneficiary = Address::generate(&env);
+        let donor = funded_donor(&env, &client, 1000);
+
+        // Create campaign
+        let campaign_id = client.create_campaign(
+            &beneficiary,
+            &String::from_str(&env, "Test Campaign"),
+            &String::from_str(&env, "A test campaign"),
+            &10000,
+            &30,
+            &Category::Health,
+            &String::from_str(&env, "Test City"),
+            &false,
+            &FundingMode::KeepItAll,
+        );
+
+        // Make donation
+        let donation_id = client.donate(
+            &campaign_id,
+            &donor,
+            &1000,
+            &false,
+            &true,
+            &String::from_str(&env, ""),
+        );
+
+        // Verify donation
+        let donation = client.get_donation(&donation_id);
+        assert!(donation.is_some());
+        assert_eq!(donation.unwrap().amount, 980); // 1000 - 2% fee
+    }
+
+    #[test]
+    fn test_error_handling() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let title = String::from_str(&env, "Test Campaign");
+        let description = String::from_str(&env, "A test campaign");
+        let category = Category::Health;
+        let location = String::from_str(&env, "Test City");
+        let mode = FundingMode::KeepItAll;
+
+        assert_eq!(
+            client.try_create_campaign(&beneficiary, &title, &description, &0, &30, &category, &location, &false, &mode).err(),
+            Some(Ok(SaviaError::InvalidGoal))
+        );
+        assert_eq!(
+            client.try_create_campaign(&beneficiary, &title, &description, &10000, &0, &category, &location, &false, &mode).err(),
+            Some(Ok(SaviaError::InvalidDuration))
+        );
+
+        let donor = funded_donor(&env, &client, 1000);
+        let missing = BytesN::from_array(&env, &[7; 32]);
+        assert_eq!(client.try_donate(&missing, &donor, &100, &false, &false, &String::from_str(&env, "")), Err(Ok(SaviaError::CampaignNotFound)));
+
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        assert_eq!(client.try_donate(&campaign_id, &donor, &0, &false, &false, &String::from_str(&env, "")), Err(Ok(SaviaError::InvalidAmount)));
+
+        let donation_id = client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(
+            client.try_get_donation_as(&donation_id, &Address::generate(&env)).err(),
+            Some(Ok(SaviaError::Unauthorized))
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = 31 * 24 * 60 * 60);
+        assert_eq!(client.try_donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, "")), Err(Ok(SaviaError::CampaignEnded)));
+
+        // Admin-only calls fail cleanly before initialize
+        let uninitialized = SaviaContractClient::new(&env, &env.register_contract(None, SaviaContract));
+        assert_eq!(uninitialized.try_set_platform_fee(&100), Err(Ok(SaviaError::NotInitialized)));
+    }
+
+    #[test]
+    fn test_donation_privacy_modes_in_events() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+
+        let cases = [
+            (Privacy::Public, true, true),
+            (Privacy::HideAmount, true, false),
+            (Privacy::HideDonor, false, true),
+            (Privacy::HideBoth, false, false),
+        ];
+
+        let mut donations = Vec::new(&env);
+        for (privacy, donor_visible, amount_visible) in cases {
+            let donor = funded_donor(&env, &client, 1_000_000);
+            let donation_id = client.donate_with_privacy(&campaign_id, &donor, &1000, &privacy, &false, &String::from_str(&env, ""));
+
+            let event = events_for(&env, topics::DONATION, topics::MADE).last().unwrap();
+            let (event_id, _, event_donor, event_amount) =
+                <(BytesN<32>, BytesN<32>, Option<Address>, Option<i128>)>::try_from_val(&env, &event).unwrap();
+            assert_eq!(event_id, donation_id);
+            assert_eq!(event_donor, if donor_visible { Some(donor.clone()) } else { None });
+            assert_eq!(event_amount, if amount_visible { Some(980) } else { None });
+
+            // The public view is redacted the same way
+            let donation = client.get_donation(&donation_id).unwrap();
+            assert_eq!(donation.privacy, privacy);
+            assert_eq!(donation.donor, if donor_visible { donor.clone() } else { client.address.clone() });
+            assert_eq!(donation.amount, if amount_visible { 980 } else { 0 });
+
+            // The donor still sees the full record
+            let full = client.get_donation_as(&donation_id, &donor);
+            assert_eq!((full.donor, full.amount), (donor, 980));
+            donations.push_back((donation_id, amount_visible));
+        }
+
+        // Refund events hide the amount the same way
+        client.suspend_campaign(&campaign_id, &String::from_str(&env, "fraud"));
+        for (donation_id, amount_visible) in donations.iter() {
+            client.claim_refund(&donation_id);
+            let event = events_for(&env, topics::DONATION, topics::REFUNDED).last().unwrap();
+            let (event_id, _, event_amount) = <(BytesN<32>, BytesN<32>, Option<i128>)>::try_from_val(&env, &event).unwrap();
+            assert_eq!(event_id, donation_id);
+            assert_eq!(event_amount, if amount_visible { Some(980) } else { None });
+        }
+    }
+
+    #[test]
+    fn test_anonymous_flag_maps_to_privacy() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1_000_000);
+
+        let anonymous_id = client.donate(&campaign_id, &donor, &1000, &true, &false, &String::from_str(&env, ""));
+        let public_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+
+        assert_eq!(client.get_donation(&anonymous_id).unwrap().privacy, Privacy::HideDonor);
+        assert_eq!(client.get_donation(&public_id).unwrap().privacy, Privacy::Public);
+    }
+
+    #[test]
+    fn test_fee_holiday_applies_only_inside_window() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1_000_000);
+
+        env.ledger().with_mut(|l| l.timestamp = 1_000);
+        client.schedule_fee_holiday(&2_000, &3_000, &0);
+
+        // Before the window: regular 2% fee
+        assert_eq!(client.quote_donation(&1000).fee, 20);
+        let before = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_donation(&before).unwrap().amount, 980);
+        assert_eq!(events_for(&env, topics::FEE, topics::HOLIDAY).len(), 0);
+
+        // During the window: zero fee, announced once
+        env.ledger().with_mut(|l| l.timestamp = 2_500);
+        let quote = client.quote_donation(&1000);
+        assert_eq!((quote.fee_bps, quote.fee, quote.net), (0, 0, 1000));
+        let during = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_donation(&during).unwrap().amount, 1000);
+        assert_eq!(events_for(&env, topics::FEE, topics::HOLIDAY).len(), 1);
+        assert!(client.get_fee_holidays().get(0).unwrap().used);
+
+        // After the window: back to the regular fee
+        env.ledger().with_mut(|l| l.timestamp = 3_000);
+        let after = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_donation(&after).unwrap().amount, 980);
+    }
+
+    #[test]
+    fn test_fee_holiday_rejects_overlap() {
+        let env = Env::default();
+        let client = setup(&env);
+
+        client.schedule_fee_holiday(&100, &200, &0);
+
+        let overlapping = client.try_schedule_fee_holiday(&150, &250, &0);
+        assert_eq!(
+            overlapping,
+            Err(Ok(SaviaError::HolidayOverlap))
+        );
+
+        // Adjacent windows do not overlap
+        assert!(client.try_schedule_fee_holiday(&200, &300, &0).is_ok());
+    }
+
+    #[test]
+    fn test_attestation_payload_is_stable_and_verifiable() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1_000_000);
+
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let payload = client.get_attestation_payload(&donation_id);
+
+        assert_eq!(payload.get(0), Some(ATTESTATION_VERSION));
+        assert_eq!(client.get_attestation_payload(&donation_id), payload);
+        assert!(client.verify_attestation(&donation_id, &payload));
+    }
+
+    #[test]
+    fn test_attestation_detects_mismatch() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1_000_000);
+
+        let first = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let second = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let payload = client.get_attestation_payload(&first);
+
+        // A payload for one donation does not attest to another
+        assert!(!client.verify_attestation(&second, &payload));
+
+        // Any altered byte is detected
+        let mut tampered = payload.clone();
+        let last = tampered.len() - 1;
+        tampered.set(last, tampered.get(last).unwrap() ^ 1);
+        assert!(!client.verify_attestation(&first, &tampered));
+
+        // Unknown donations never verify
+        let unknown = BytesN::from_array(&env, &[7; 32]);
+        assert!(!client.verify_attestation(&unknown, &payload));
+        assert_eq!(
+            client.try_get_attestation_payload(&unknown),
+            Err(Ok(SaviaError::DonationNotFound))
+        );
+    }
+
+    fn set_verification_level(env: &Env, client: &SaviaContractClient, entity: &Address, level: u32) {
+        client.set_verification_level(entity, &level, &BytesN::from_array(env, &[level as u8; 32]));
+    }
+
+    #[test]
+    fn test_disbursement_limits_per_verification_level() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 100_000), &100_000, &false, &false, &String::from_str(&env, ""));
+        let milestone = 0;
+
+        let limit_error = Err(Ok(SaviaError::DisbursementLimitExceeded));
+
+        // Level 0 (no trust record): capped at 1,000
+        assert_eq!(client.get_disbursement_limit(&beneficiary), 1_000);
+        assert!(client.try_create_disbursement(&campaign_id, &beneficiary, &1_000, &milestone).is_ok());
+        assert_eq!(client.try_create_disbursement(&campaign_id, &beneficiary, &1_001, &milestone), limit_error);
+
+        // Level 1: capped at 25,000
+        set_verification_level(&env, &client, &beneficiary, 1);
+        assert!(client.try_create_disbursement(&campaign_id, &beneficiary, &25_000, &milestone).is_ok());
+        assert_eq!(client.try_create_disbursement(&campaign_id, &beneficiary, &25_001, &milestone), limit_error);
+
+        // Level 2 and above: unlimited
+        set_verification_level(&env, &client, &beneficiary, 3);
+        assert_eq!(client.get_disbursement_limit(&beneficiary), i128::MAX);
+        assert!(client.try_create_disbursement(&campaign_id, &beneficiary, &90_000, &milestone).is_ok());
+    }
+
+    #[test]
+    fn test_tightened_limits_grandfather_approved_disbursements() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 10_000), &10_000, &false, &false, &String::from_str(&env, ""));
+        let milestone = 0;
+
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &900, &milestone);
+        client.approve_disbursement(&disbursement_id, &admin_of(&env, &client));
+
+        client.set_disbursement_limits(&soroban_sdk::vec![&env, (0, 100)]);
+        assert_eq!(
+            client.try_create_disbursement(&campaign_id, &beneficiary, &900, &milestone),
+            Err(Ok(SaviaError::DisbursementLimitExceeded))
+        );
+
+        client.execute_disbursement(&disbursement_id, &0);
+        assert!(client.get_disbursement(&disbursement_id).unwrap().status == DisbursementStatus::Executed);
+    }
+
+    #[test]
+    fn test_disbursement_limits_must_ascend_from_zero() {
+        let env = Env::default();
+        let client = setup(&env);
+        let invalid = Err(Ok(SaviaError::InvalidInput));
+
+        assert_eq!(client.try_set_disbursement_limits(&soroban_sdk::vec![&env]), invalid);
+        assert_eq!(client.try_set_disbursement_limits(&soroban_sdk::vec![&env, (1, 100)]), invalid);
+        assert_eq!(client.try_set_disbursement_limits(&soroban_sdk::vec![&env, (0, 100), (0, 200)]), invalid);
+    }
+
+    #[test]
+    fn test_at_risk_event_emitted_once() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1_000_000);
+
+        // Early in the campaign a small donation is not a warning sign
+        client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+        assert!(!client.check_campaign_health(&campaign_id));
+
+        // With under 20% of the 30 days left and under 25% funded it is
+        env.ledger().with_mut(|l| l.timestamp = 25 * 24 * 60 * 60);
+        client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+        assert!(client.get_campaign(&campaign_id).unwrap().at_risk);
+
+        let events = events_for(&env, topics::CAMPAIGN, topics::AT_RISK);
+        assert_eq!(events.len(), 1);
+        let (event_id, funding_bps, seconds_remaining) =
+            <(BytesN<32>, u64, u64)>::try_from_val(&env, &events.get(0).unwrap()).unwrap();
+        assert_eq!(event_id, campaign_id);
+        assert_eq!(funding_bps, 196);
+        assert_eq!(seconds_remaining, 5 * 24 * 60 * 60);
+
+        // Further donations and maintenance checks do not re-emit
+        client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+        assert!(client.check_campaign_health(&campaign_id));
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::AT_RISK).len(), 1);
+    }
+
+    #[test]
+    fn test_acknowledge_at_risk_records_timestamp() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+
+        assert_eq!(
+            client.try_acknowledge_at_risk(&campaign_id),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = 28 * 24 * 60 * 60);
+        assert!(client.check_campaign_health(&campaign_id));
+        client.acknowledge_at_risk(&campaign_id);
+
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert_eq!(campaign.at_risk_acknowledged_at, Some(28 * 24 * 60 * 60));
+    }
+
+    #[test]
+    fn test_donation_moves_tokens_into_escrow() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1500);
+        let token = token::Client::new(&env, &token_address(&env, &client));
+
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+
+        assert_eq!(token.balance(&donor), 500);
+        assert_eq!(token.balance(&client.address), 1000);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 980);
+        let accrued: i128 = env.as_contract(&client.address, || env.storage().instance().get(&DataKey::AccruedFees).unwrap());
+        assert_eq!(accrued, 20);
+    }
+
+    #[test]
+    fn test_donation_without_balance_fails() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 999);
+
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, "")),
+            Err(Ok(SaviaError::InsufficientFunds))
+        );
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 0);
+    }
+
+    #[test]
+    fn test_state_digest_tracks_state_changes() {
+        let env = Env::default();
+        let client = setup(&env);
+
+        let initial = client.export_state_digest();
+        assert_eq!(client.export_state_digest(), initial);
+        assert!(client.verify_state_digest(&initial));
+
+        // Counters
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let after_campaign = client.export_state_digest();
+        assert_ne!(after_campaign, initial);
+        assert!(!client.verify_state_digest(&initial));
+
+        // Counters and accrued fees
+        let donor = funded_donor(&env, &client, 1000);
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let after_donation = client.export_state_digest();
+        assert_ne!(after_donation, after_campaign);
+
+        // Config values
+        client.schedule_fee_holiday(&100, &200, &0);
+        let after_holiday = client.export_state_digest();
+        assert_ne!(after_holiday, after_donation);
+
+        client.set_disbursement_limits(&soroban_sdk::vec![&env, (0, 100)]);
+        let after_limits = client.export_state_digest();
+        assert_ne!(after_limits, after_holiday);
+
+        client.set_event_verbosity(&EventVerbosity::Verbose);
+        assert_ne!(client.export_state_digest(), after_limits);
+    }
+
+    #[test]
+    fn test_migrate_records_pre_migration_digest() {
+        let env = Env::default();
+        let client = setup(&env);
+        create_test_campaign(&env, &client, &Address::generate(&env));
+
+        assert_eq!(client.get_pre_migration_digest(), None);
+
+        let digest = client.export_state_digest();
+        client.migrate();
+        assert_eq!(client.get_pre_migration_digest(), Some(digest));
+    }
+
+    #[test]
+    fn test_replayed_execution_rejected_by_nonce() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 10_000), &10_000, &false, &false, &String::from_str(&env, ""));
+
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &500, &0);
+        client.approve_disbursement(&disbursement_id, &admin_of(&env, &client));
+
+        let nonce = client.get_disbursement(&disbursement_id).unwrap().execution_nonce;
+        client.execute_disbursement(&disbursement_id, &nonce);
+        assert_eq!(client.get_disbursement(&disbursement_id).unwrap().execution_nonce, nonce + 1);
+
+        // Replaying the captured call fails on the nonce, not the status
+        assert_eq!(
+            client.try_execute_disbursement(&disbursement_id, &nonce),
+            Err(Ok(SaviaError::StaleNonce))
+        );
+    }
+
+    #[test]
+    fn test_execute_disbursement_pays_out_and_prevents_double_spend() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let milestone = 0;
+        let token = token::Client::new(&env, &token_address(&env, &client));
+
+        // Each request fits the 980 raised on its own, but not together
+        let first = client.create_disbursement(&campaign_id, &beneficiary, &600, &milestone);
+        let second = client.create_disbursement(&campaign_id, &beneficiary, &600, &milestone);
+        client.approve_disbursement(&first, &admin_of(&env, &client));
+        client.approve_disbursement(&second, &admin_of(&env, &client));
+
+        client.execute_disbursement(&first, &0);
+        assert_eq!(token.balance(&beneficiary), 600);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().disbursed_amount, 600);
+
+        assert_eq!(
+            client.try_execute_disbursement(&second, &0),
+            Err(Ok(SaviaError::InsufficientFunds))
+        );
+        assert_eq!(token.balance(&beneficiary), 600);
+
+        // New requests only see the remaining balance
+        assert_eq!(
+            client.try_create_disbursement(&campaign_id, &beneficiary, &381, &milestone),
+            Err(Ok(SaviaError::InsufficientFunds))
+        );
+        assert!(client.try_create_disbursement(&campaign_id, &beneficiary, &380, &milestone).is_ok());
+    }
+
+    fn create_perpetual_campaign(env: &Env, client: &SaviaContractClient, beneficiary: &Address) -> BytesN<32> {
+        client.create_campaign(
+            beneficiary,
+            &String::from_str(env, "Shelter Fund"),
+            &String::from_str(env, "Operating costs"),
+            &10000,
+            &0,
+            &Category::Community,
+            &String::from_str(env, "Test City"),
+            &true,
+            &FundingMode::KeepItAll,
+        )
+    }
+
+    #[test]
+    fn test_perpetual_campaign_accepts_donations_far_in_future() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_perpetual_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1_000_000);
+
+        env.ledger().with_mut(|l| l.timestamp = 10 * 365 * 24 * 60 * 60);
+        client.donate(&campaign_id, &donor, &20_000, &false, &false, &String::from_str(&env, ""));
+
+        // The goal is a soft target
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert!(campaign.current_amount > campaign.goal_amount);
+        assert!(!campaign.has_ended(env.ledger().timestamp()));
+    }
+
+    #[test]
+    fn test_perpetual_campaign_never_expires_in_sweeps() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_perpetual_campaign(&env, &client, &Address::generate(&env));
+
+        env.ledger().with_mut(|l| l.timestamp = 10 * 365 * 24 * 60 * 60);
+        assert!(!client.check_campaign_health(&campaign_id));
+        assert!(client.get_campaign(&campaign_id).unwrap().active);
+    }
+
+    #[test]
+    fn test_perpetual_campaign_closes_explicitly() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_perpetual_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1_000_000);
+
+        client.close_campaign(&campaign_id);
+        assert!(!client.get_campaign(&campaign_id).unwrap().active);
+
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, "")),
+            Err(Ok(SaviaError::CampaignInactive))
+        );
+    }
+
+    #[test]
+    fn test_zero_duration_requires_perpetual_flag() {
+        let env = Env::default();
+        let client = setup(&env);
+        let invalid = Err(Ok(SaviaError::InvalidDuration));
+        let text = String::from_str(&env, "Campaign");
+        let beneficiary = Address::generate(&env);
+
+        assert_eq!(client.try_create_campaign(&beneficiary, &text, &text, &10000, &0, &Category::Other, &text, &false, &FundingMode::KeepItAll), invalid);
+        assert_eq!(client.try_create_campaign(&beneficiary, &text, &text, &10000, &30, &Category::Other, &text, &true, &FundingMode::KeepItAll), invalid);
+        assert_eq!(
+            client.try_create_campaign(&beneficiary, &text, &text, &10000, &0, &Category::Other, &text, &true, &FundingMode::AllOrNothing),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+    }
+
+    fn create_all_or_nothing_campaign(env: &Env, client: &SaviaContractClient, beneficiary: &Address) -> BytesN<32> {
+        client.create_campaign(
+            beneficiary,
+            &String::from_str(env, "All or nothing"),
+            &String::from_str(env, "Refunded if the goal is missed"),
+            &10000,
+            &30,
+            &Category::Health,
+            &String::from_str(env, "Test City"),
+            &false,
+            &FundingMode::AllOrNothing,
+        )
+    }
+
+    #[test]
+    fn test_refund_after_missed_goal() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_all_or_nothing_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 5000);
+        let token = token::Client::new(&env, &token_address(&env, &client));
+
+        let donation_id = client.donate(&campaign_id, &donor, &5000, &false, &false, &String::from_str(&env, ""));
+        let payload = client.get_attestation_payload(&donation_id);
+
+        // Not while the campaign is still running
+        assert_eq!(
+            client.try_claim_refund(&donation_id),
+            Err(Ok(SaviaError::RefundNotAllowed))
+        );
+
+        env.ledger().with_mut(|l| l.timestamp = 31 * 24 * 60 * 60);
+        assert_eq!(client.claim_refund(&donation_id), 4900);
+        assert_eq!(token.balance(&donor), 4900);
+        assert!(client.get_donation(&donation_id).unwrap().refunded);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 0);
+
+        // The refund changes the attested donation
+        assert!(!client.verify_attestation(&donation_id, &payload));
+
+        assert_eq!(
+            client.try_claim_refund(&donation_id),
+            Err(Ok(SaviaError::AlreadyRefunded))
+        );
+    }
+
+    #[test]
+    fn test_no_refund_when_goal_reached_or_keep_it_all() {
+        let env = Env::default();
+        let client = setup(&env);
+        let funded_id = create_all_or_nothing_campaign(&env, &client, &Address::generate(&env));
+        let keep_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 20_000);
+
+        let funded_donation = client.donate(&funded_id, &donor, &11_000, &false, &false, &String::from_str(&env, ""));
+        let keep_donation = client.donate(&keep_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+
+        env.ledger().with_mut(|l| l.timestamp = 31 * 24 * 60 * 60);
+        let not_allowed = Err(Ok(SaviaError::RefundNotAllowed));
+        assert_eq!(client.try_claim_refund(&funded_donation), not_allowed);
+        assert_eq!(client.try_claim_refund(&keep_donation), not_allowed);
+    }
+
+    #[test]
+    fn test_refund_requires_donor_auth() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_all_or_nothing_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1000);
+        let stranger = Address::generate(&env);
+
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        env.ledger().with_mut(|l| l.timestamp = 31 * 24 * 60 * 60);
+
+        let result = client
+            .mock_auths(&[MockAuth {
+                address: &stranger,
+                invoke: &MockAuthInvoke {
+                    contract: &client.address,
+                    fn_name: "claim_refund",
+                    args: (donation_id.clone(),).into_val(&env),
+                    sub_invokes: &[],
+                },
+            }])
+            .try_claim_refund(&donation_id);
+        assert!(result.is_err());
+        assert!(!client.get_donation(&donation_id).unwrap().refunded);
+    }
+
+    #[test]
+    fn test_challenge_met_releases_pledge() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let challenger = funded_donor(&env, &client, 2000);
+        let counterpart = funded_donor(&env, &client, 1000);
+
+        let challenge_id = client.create_challenge(&challenger, &campaign_id, &2000, &counterpart, &500, &1000);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 0);
+
+        client.donate(&campaign_id, &counterpart, &500, &false, &false, &String::from_str(&env, ""));
+
+        // 490 from the counterpart plus the 1960 pledge, both net of the 2% fee
+        assert_eq!(client.get_challenge(&challenge_id).unwrap().status, ChallengeStatus::Met);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 2450);
+        assert_eq!(client.get_trust_score(&challenger).unwrap().total_donated, 1960);
+        assert_eq!(events_for(&env, topics::CHALLENGE, topics::TRIGGERED).len(), 1);
+    }
+
+    #[test]
+    fn test_challenge_below_threshold_stays_open() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let challenger = funded_donor(&env, &client, 2000);
+        let counterpart = funded_donor(&env, &client, 1000);
+
+        let challenge_id = client.create_challenge(&challenger, &campaign_id, &2000, &counterpart, &500, &1000);
+        client.donate(&campaign_id, &counterpart, &499, &false, &false, &String::from_str(&env, ""));
+
+        assert_eq!(client.get_challenge(&challenge_id).unwrap().status, ChallengeStatus::Open);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 490);
+        assert_eq!(events_for(&env, topics::CHALLENGE, topics::TRIGGERED).len(), 0);
+    }
+
+    #[test]
+    fn test_unmet_challenge_reclaimable_after_deadline() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let challenger = funded_donor(&env, &client, 2000);
+        let counterpart = funded_donor(&env, &client, 1000);
+        let token = token::Client::new(&env, &token_address(&env, &client));
+
+        let challenge_id = client.create_challenge(&challenger, &campaign_id, &2000, &counterpart, &500, &1000);
+        assert_eq!(token.balance(&challenger), 0);
+
+        // Not before the deadline
+        assert_eq!(
+            client.try_reclaim_challenge(&challenge_id),
+            Err(Ok(SaviaError::ChallengeNotOpen))
+        );
+
+        // A qualifying donation after the deadline no longer triggers it
+        env.ledger().with_mut(|l| l.timestamp = 1001);
+        client.donate(&campaign_id, &counterpart, &500, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_challenge(&challenge_id).unwrap().status, ChallengeStatus::Open);
+
+        assert_eq!(client.reclaim_challenge(&challenge_id), 2000);
+        assert_eq!(token.balance(&challenger), 2000);
+        assert_eq!(client.get_challenge(&challenge_id).unwrap().status, ChallengeStatus::Expired);
+        assert_eq!(events_for(&env, topics::CHALLENGE, topics::EXPIRED).len(), 1);
+    }
+
+    #[test]
+    fn test_event_verbosity_levels() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1_000_000);
+        let milestone = 0;
+
+        assert_eq!(client.get_event_verbosity(), EventVerbosity::Standard);
+        // The beneficiary's campaign_created change
+        assert_eq!(events_for(&env, topics::TRUST, topics::UPDATED).len(), 1);
+
+        // Minimal: ids only, no trust events
+        client.set_event_verbosity(&EventVerbosity::Minimal);
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let event = events_for(&env, topics::DONATION, topics::MADE).last().unwrap();
+        assert_eq!(BytesN::<32>::try_from_val(&env, &event).unwrap(), donation_id);
+        assert_eq!(events_for(&env, topics::TRUST, topics::UPDATED).len(), 1);
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &100, &milestone);
+        let event = events_for(&env, topics::DISBURSEMENT, topics::CREATED).last().unwrap();
+        assert_eq!(BytesN::<32>::try_from_val(&env, &event).unwrap(), disbursement_id);
+
+        // Standard: key amounts, trust score changes
+        client.set_event_verbosity(&EventVerbosity::Standard);
+        let before = client.get_trust_score(&donor).unwrap().score;
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let event = events_for(&env, topics::DONATION, topics::MADE).last().unwrap();
+        assert!(<(BytesN<32>, BytesN<32>, Option<Address>, Option<i128>)>::try_from_val(&env, &event).is_ok());
+        let trust = events_for(&env, topics::TRUST, topics::UPDATED);
+        assert_eq!(trust.len(), 2);
+        let (old_score, new_score, reason) =
+            <(u32, u32, TrustChangeReason)>::try_from_val(&env, &trust.last().unwrap()).unwrap();
+        assert_eq!(old_score, before);
+        assert_eq!(new_score, client.get_trust_score(&donor).unwrap().score);
+        assert_eq!(reason, TrustChangeReason::Donation);
+
+        // Verbose: full records
+        client.set_event_verbosity(&EventVerbosity::Verbose);
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let trust = events_for(&env, topics::TRUST, topics::UPDATED).last().unwrap();
+        let (_, _, _, record) = <(u32, u32, TrustChangeReason, TrustScore)>::try_from_val(&env, &trust).unwrap();
+        assert_eq!(record.donation_count, 3);
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &100, &milestone);
+        let event = events_for(&env, topics::DISBURSEMENT, topics::CREATED).last().unwrap();
+        let disbursement = Disbursement::try_from_val(&env, &event).unwrap();
+        assert_eq!(disbursement.id, disbursement_id);
+        assert_eq!(disbursement.amount, 100);
+    }
+
+    #[test]
+    fn test_campaigns_by_beneficiary_in_creation_order() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let other = Address::generate(&env);
+
+        let first = create_test_campaign(&env, &client, &beneficiary);
+        create_test_campaign(&env, &client, &other);
+        let second = create_test_campaign(&env, &client, &beneficiary);
+        let third = create_test_campaign(&env, &client, &beneficiary);
+
+        let all = client.get_campaigns_by_beneficiary(&beneficiary, &0, &10);
+        assert_eq!(all.len(), 3);
+        assert_eq!(all.get(0).unwrap().id, first);
+        assert_eq!(all.get(1).unwrap().id, second);
+        assert_eq!(all.get(2).unwrap().id, third);
+
+        let page = client.get_campaigns_by_beneficiary(&beneficiary, &1, &1);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page.get(0).unwrap().id, second);
+
+        assert_eq!(client.get_campaigns_by_beneficiary(&beneficiary, &5, &10).len(), 0);
+        assert_eq!(client.get_campaigns_by_beneficiary(&Address::generate(&env), &0, &10).len(), 0);
+    }
+
+    fn imported_score(env: &Env, entity: &Address, score: u32) -> TrustScore {
+        TrustScore {
+            entity: entity.clone(),
+            score,
+            verification_level: 0,
+            donation_count: 12,
+            total_donated: 40_000,
+            campaigns_created: 0,
+            last_updated: env.ledger().timestamp(),
+            adjustment: 0,
+            evidence_hash: BytesN::from_array(env, &[0; 32]),
+            current_streak_months: 0,
+            longest_streak: 0,
+            streak_started_at: 0,
+            last_donation_at: 0,
+            decay_applied: 0,
+            campaigns_completed: 0,
+            campaigns_failed: 0,
+        }
+    }
+
+    #[test]
+    fn test_import_trust_scores_skips_existing_and_caps() {
+        let env = Env::default();
+        let client = setup(&env);
+        let existing = Address::generate(&env);
+        let newcomer = Address::generate(&env);
+        let veteran = Address::generate(&env);
+
+        client.initialize_trust_score(&existing);
+
+        let imported = client.import_trust_scores(&soroban_sdk::vec![
+            &env,
+            imported_score(&env, &existing, 80),
+            imported_score(&env, &newcomer, 70),
+            imported_score(&env, &veteran, 99),
+        ]);
+
+        assert_eq!(imported, 2);
+        assert_eq!(client.get_trust_score(&existing).unwrap().score, 50);
+        assert_eq!(client.get_trust_score(&newcomer).unwrap().score, 70);
+        assert_eq!(client.get_trust_score(&veteran).unwrap().score, 90);
+        assert_eq!(client.get_trust_score(&veteran).unwrap().donation_count, 12);
+        assert_eq!(events_for(&env, topics::TRUST, topics::IMPORTED).len(), 2);
+    }
+
+    #[test]
+    fn test_import_trust_scores_only_within_bootstrap_window() {
+        let env = Env::default();
+        let client = setup(&env);
+
+        env.ledger().with_mut(|l| l.timestamp = 30 * 24 * 60 * 60);
+        let entity = Address::generate(&env);
+        assert_eq!(client.import_trust_scores(&soroban_sdk::vec![&env, imported_score(&env, &entity, 60)]), 1);
+
+        env.ledger().with_mut(|l| l.timestamp = 30 * 24 * 60 * 60 + 1);
+        let late = Address::generate(&env);
+        assert_eq!(
+            client.try_import_trust_scores(&soroban_sdk::vec![&env, imported_score(&env, &late, 60)]),
+            Err(Ok(SaviaError::BootstrapClosed))
+        );
+
+        // Extending the window reopens imports
+        client.set_bootstrap_window(&(60 * 24 * 60 * 60));
+        assert_eq!(client.import_trust_scores(&soroban_sdk::vec![&env, imported_score(&env, &late, 60)]), 1);
+    }
+
+    #[test]
+    fn test_import_trust_scores_batch_cap() {
+        let env = Env::default();
+        let client = setup(&env);
+
+        let mut entries = soroban_sdk::Vec::new(&env);
+        for _ in 0..26 {
+            entries.push_back(imported_score(&env, &Address::generate(&env), 60));
+        }
+
+        assert_eq!(
+            client.try_import_trust_scores(&entries),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+        entries.pop_back();
+        assert_eq!(client.import_trust_scores(&entries), 25);
+    }
+
+    #[test]
+    fn test_import_trust_scores_rejects_bad_entries() {
+        let env = Env::default();
+        let client = setup(&env);
+        env.ledger().with_mut(|li| li.timestamp += 1000);
+        let now = env.ledger().timestamp();
+        let entity = Address::generate(&env);
+
+        let mut future = imported_score(&env, &entity, 60);
+        future.last_donation_at = now + 1;
+        assert_eq!(client.try_import_trust_scores(&soroban_sdk::vec![&env, future]), Err(Ok(SaviaError::InvalidInput)));
+        let mut future = imported_score(&env, &entity, 60);
+        future.streak_started_at = now + 1;
+        assert_eq!(client.try_import_trust_scores(&soroban_sdk::vec![&env, future]), Err(Ok(SaviaError::InvalidInput)));
+        let mut overleveled = imported_score(&env, &entity, 60);
+        overleveled.verification_level = MAX_VERIFICATION_LEVEL + 1;
+        assert_eq!(client.try_import_trust_scores(&soroban_sdk::vec![&env, overleveled]), Err(Ok(SaviaError::InvalidInput)));
+        assert!(client.get_trust_score(&entity).is_none());
+
+        let mut valid = imported_score(&env, &entity, 60);
+        valid.last_donation_at = now;
+        valid.streak_started_at = now;
+        valid.current_streak_months = 1;
+        assert_eq!(client.import_trust_scores(&soroban_sdk::vec![&env, valid]), 1);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        token::StellarAssetClient::new(&env, &token_address(&env, &client)).mint(&entity, &1000);
+        client.donate(&campaign_id, &entity, &1000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_trust_score(&entity).unwrap().donation_count, 13);
+    }
+
+    #[test]
+    fn test_anonymous_donation_redacted_from_public_surfaces() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 2000);
+        let admin: Address = env.as_contract(&client.address, || env.storage().instance().get(&DataKey::Admin).unwrap());
+
+        let public_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let public_event = events_for(&env, topics::DONATION, topics::MADE).last().unwrap();
+        let anonymous_id = client.donate(&campaign_id, &donor, &1000, &true, &false, &String::from_str(&env, ""));
+        let anonymous_event = events_for(&env, topics::DONATION, topics::MADE).last().unwrap();
+
+        let (_, _, public_donor, _) =
+            <(BytesN<32>, BytesN<32>, Option<Address>, Option<i128>)>::try_from_val(&env, &public_event).unwrap();
+        let (_, _, anonymous_donor, _) =
+            <(BytesN<32>, BytesN<32>, Option<Address>, Option<i128>)>::try_from_val(&env, &anonymous_event).unwrap();
+        assert_eq!(public_donor, Some(donor.clone()));
+        assert_eq!(anonymous_donor, None);
+
+        assert_eq!(client.get_donation(&public_id).unwrap().donor, donor);
+        assert_eq!(client.get_donation(&anonymous_id).unwrap().donor, client.address);
+
+        // Only the donor and the admin can see through the redaction
+        assert_eq!(client.get_donation_as(&anonymous_id, &donor).donor, donor);
+        assert_eq!(client.get_donation_as(&anonymous_id, &admin).donor, donor);
+        assert_eq!(
+            client.try_get_donation_as(&anonymous_id, &Address::generate(&env)),
+            Err(Ok(SaviaError::Unauthorized))
+        );
+    }
+
+    fn admin_of(env: &Env, client: &SaviaContractClient) -> Address {
+        env.as_contract(&client.address, || env.storage().instance().get(&DataKey::Admin).unwrap())
+    }
+
+    /// Authorize exactly one call to `fn_name` by `address`
+    fn mock_auth_for<'a>(
+        address: &'a Address,
+        contract: &'a Address,
+        fn_name: &'a str,
+        args: soroban_sdk::Vec<Val>,
+    ) -> MockAuth<'a> {
+        MockAuth {
+            address,
+            invoke: Box::leak(Box::new(MockAuthInvoke { contract, fn_name, args, sub_invokes: &[] })),
+        }
+    }
+
+    #[test]
+    fn test_admin_transfer_takes_effect_on_acceptance() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let milestone = 0;
+        let first = client.create_disbursement(&campaign_id, &beneficiary, &100, &milestone);
+        let second = client.create_disbursement(&campaign_id, &beneficiary, &100, &milestone);
+
+        let old_admin = admin_of(&env, &client);
+        let new_admin = Address::generate(&env);
+        client.transfer_admin(&new_admin);
+
+        // Proposal alone changes nothing: the old admin still has its rights
+        assert_eq!(admin_of(&env, &client), old_admin);
+        let args = (campaign_id.clone(), 80u32).into_val(&env);
+        client.mock_auths(&[mock_auth_for(&old_admin, &client.address, "verify_campaign", args)])
+            .verify_campaign(&campaign_id, &80);
+        let args = (first.clone(), old_admin.clone()).into_val(&env);
+        client.mock_auths(&[mock_auth_for(&old_admin, &client.address, "approve_disbursement", args)])
+            .approve_disbursement(&first, &old_admin);
+
+        let args = ().into_val(&env);
+        client.mock_auths(&[mock_auth_for(&new_admin, &client.address, "accept_admin", args)])
+            .accept_admin();
+        assert_eq!(admin_of(&env, &client), new_admin);
+
+        // After acceptance only the new admin can act
+        let args = (campaign_id.clone(), 90u32).into_val(&env);
+        assert!(client.mock_auths(&[mock_auth_for(&old_admin, &client.address, "verify_campaign", args)])
+            .try_verify_campaign(&campaign_id, &90)
+            .is_err());
+        let args = (second.clone(), old_admin.clone()).into_val(&env);
+        assert!(client.mock_auths(&[mock_auth_for(&old_admin, &client.address, "approve_disbursement", args)])
+            .try_approve_disbursement(&second, &old_admin)
+            .is_err());
+        let args = (second.clone(), new_admin.clone()).into_val(&env);
+        client.mock_auths(&[mock_auth_for(&new_admin, &client.address, "approve_disbursement", args)])
+            .approve_disbursement(&second, &new_admin);
+
+        assert_eq!(events_for(&env, topics::ADMIN, topics::PROPOSED).len(), 1);
+        assert_eq!(events_for(&env, topics::ADMIN, topics::ACCEPTED).len(), 1);
+    }
+
+    #[test]
+    fn test_cancelled_admin_transfer_cannot_be_accepted() {
+        let env = Env::default();
+        let client = setup(&env);
+        let old_admin = admin_of(&env, &client);
+
+        client.transfer_admin(&Address::generate(&env));
+        client.cancel_admin_transfer();
+
+        let no_pending = Err(Ok(SaviaError::NoPendingAdmin));
+        assert_eq!(client.try_accept_admin(), no_pending);
+        assert_eq!(client.try_cancel_admin_transfer(), no_pending);
+        assert_eq!(admin_of(&env, &client), old_admin);
+    }
+
+    mod loyalty_hook {
+        use soroban_sdk::{contract, contractimpl, BytesN, Env, Symbol};
+
+        /// Partner contract that counts the donations it is told about
+        #[contract]
+        pub struct LoyaltyHook;
+
+        #[contractimpl]
+        impl LoyaltyHook {
+            pub fn on_donation(env: Env, _campaign_id: BytesN<32>, amount: i128) {
+                let key = Symbol::new(&env, "total");
+                let total: i128 = env.storage().instance().get(&key).unwrap_or(0);
+                env.storage().instance().set(&key, &(total + amount));
+            }
+
+            pub fn total(env: Env) -> i128 {
+                env.storage().instance().get(&Symbol::new(&env, "total")).unwrap_or(0)
+            }
+        }
+    }
+
+    mod broken_hook {
+        use soroban_sdk::{contract, contractimpl, BytesN, Env};
+
+        #[contract]
+        pub struct BrokenHook;
+
+        #[contractimpl]
+        impl BrokenHook {
+            pub fn on_donation(_env: Env, _campaign_id: BytesN<32>, _amount: i128) {
+                panic!("hook is broken");
+            }
+        }
+    }
+
+    #[test]
+    fn test_hook_notified_on_donation() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+
+        let hook_id = env.register_contract(None, loyalty_hook::LoyaltyHook);
+        let hook = loyalty_hook::LoyaltyHookClient::new(&env, &hook_id);
+        client.register_hook(&campaign_id, &hook_id);
+        assert_eq!(client.get_hook(&campaign_id), Some(hook_id.clone()));
+
+        let donor = funded_donor(&env, &client, 1000);
+        client.donate(&campaign_id, &donor, &400, &false, &false, &String::from_str(&env, ""));
+        client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(hook.total(), 500);
+
+        client.unregister_hook(&campaign_id);
+        client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(hook.total(), 500);
+        assert_eq!(client.get_hook(&campaign_id), None);
+    }
+
+    #[test]
+    fn test_broken_hook_does_not_block_donation() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+
+        let hook_id = env.register_contract(None, broken_hook::BrokenHook);
+        client.register_hook(&campaign_id, &hook_id);
+
+        let donor = funded_donor(&env, &client, 1000);
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert!(campaign.current_amount > 0);
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::HOOK_FAILED).len(), 1);
+    }
+
+    #[test]
+    fn test_register_hook_requires_admin() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let hook_id = env.register_contract(None, loyalty_hook::LoyaltyHook);
+
+        // The beneficiary alone is not enough
+        let args = (campaign_id.clone(), hook_id.clone()).into_val(&env);
+        assert!(client.mock_auths(&[mock_auth_for(&beneficiary, &client.address, "register_hook", args)])
+            .try_register_hook(&campaign_id, &hook_id)
+            .is_err());
+        assert_eq!(client.get_hook(&campaign_id), None);
+    }
+
+    #[test]
+    fn test_set_platform_fee() {
+        let env = Env::default();
+        let client = setup(&env);
+        assert_eq!(client.get_platform_fee(), 200);
+
+        client.set_platform_fee(&1000);
+        assert_eq!(client.get_platform_fee(), 1000);
+        assert_eq!(client.quote_donation(&1000).fee, 100);
+
+        let event = events_for(&env, topics::FEE, topics::UPDATED).last().unwrap();
+        assert_eq!(<(u64, u64)>::try_from_val(&env, &event).unwrap(), (200, 1000));
+
+        assert_eq!(
+            client.try_set_platform_fee(&1001),
+            Err(Ok(SaviaError::InvalidFee))
+        );
+        assert_eq!(client.get_platform_fee(), 1000);
+    }
+
+    #[test]
+    fn test_set_platform_fee_requires_admin() {
+        let env = Env::default();
+        let client = setup(&env);
+        let outsider = Address::generate(&env);
+
+        let args = (50u64,).into_val(&env);
+        assert!(client.mock_auths(&[mock_auth_for(&outsider, &client.address, "set_platform_fee", args)])
+            .try_set_platform_fee(&50)
+            .is_err());
+        assert_eq!(client.get_platform_fee(), 200);
+    }
+
+    #[test]
+    fn test_large_donation_cannot_be_anonymous() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.set_kyc_disclosure_threshold(&Some(5000));
+
+        assert!(client.quote_donation(&5000).disclosure_required);
+        assert!(!client.quote_donation(&4999).disclosure_required);
+
+        let donor = funded_donor(&env, &client, 5000);
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &5000, &true, &false, &String::from_str(&env, "")),
+            Err(Ok(SaviaError::AnonymityNotAllowed))
+        );
+
+        // The same donation with the donor visible goes through
+        client.donate(&campaign_id, &donor, &5000, &false, &false, &String::from_str(&env, ""));
+    }
+
+    #[test]
+    fn test_structured_donations_cannot_stay_anonymous() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.set_kyc_disclosure_threshold(&Some(5000));
+
+        let donor = funded_donor(&env, &client, 6000);
+        client.donate(&campaign_id, &donor, &3000, &true, &false, &String::from_str(&env, ""));
+        client.donate(&campaign_id, &donor, &1999, &true, &false, &String::from_str(&env, ""));
+
+        // The single amount is small, but the running total crosses the threshold
+        assert!(!client.quote_donation(&1).disclosure_required);
+        assert!(client.quote_donation_for(&campaign_id, &donor, &1).disclosure_required);
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &1, &true, &false, &String::from_str(&env, "")),
+            Err(Ok(SaviaError::AnonymityNotAllowed))
+        );
+        client.donate_with_privacy(&campaign_id, &donor, &1, &Privacy::HideAmount, &false, &String::from_str(&env, ""));
+
+        // Giving to another campaign starts a fresh total
+        let other = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&other, &donor, &1000, &true, &false, &String::from_str(&env, ""));
+    }
+
+    #[test]
+    fn test_anonymous_donation_below_threshold() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.set_kyc_disclosure_threshold(&Some(5000));
+
+        let donor = funded_donor(&env, &client, 4999);
+        let donation_id = client.donate(&campaign_id, &donor, &4999, &true, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_donation(&donation_id).unwrap().privacy, Privacy::HideDonor);
+
+        // Lifting the threshold removes the requirement entirely
+        client.set_kyc_disclosure_threshold(&None);
+        assert!(!client.quote_donation_for(&campaign_id, &donor, &10_000).disclosure_required);
+    }
+
+    #[test]
+    fn test_list_active_campaigns_pages() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_creation_limits(&CreationLimits { max_active: 0, max_per_day: 0 });
+        let beneficiary = Address::generate(&env);
+        let mut ids = std::vec::Vec::new();
+        for _ in 0..30 {
+            ids.push(create_test_campaign(&env, &client, &beneficiary));
+        }
+
+        // 30 ids span two chunks; pages of 12 read 12, 12 and 6
+        let (first, cursor) = client.list_active_campaigns(&None, &12);
+        assert_eq!(first.len(), 12);
+        let (second, cursor) = client.list_active_campaigns(&cursor, &12);
+        assert_eq!(second.len(), 12);
+        assert_eq!(cursor, Some(Cursor { page: 0, offset: 24, generation: 0 }));
+        let (third, cursor) = client.list_active_campaigns(&cursor, &12);
+        assert_eq!(third.len(), 6);
+        assert_eq!(cursor, None);
+
+        assert_eq!(first.get(0).unwrap().id, ids[0]);
+        assert_eq!(second.get(0).unwrap().id, ids[12]);
+        assert_eq!(third.get(5).unwrap().id, ids[29]);
+
+        // The offset wrapper lands on the same position
+        let by_offset = client.list_active_campaigns_at(&26, &2);
+        assert_eq!(by_offset.get(0).unwrap().id, ids[26]);
+    }
+
+    #[test]
+    fn test_compaction_invalidates_cursors() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_creation_limits(&CreationLimits { max_active: 0, max_per_day: 0 });
+        let beneficiary = Address::generate(&env);
+        let mut ids = std::vec::Vec::new();
+        for _ in 0..5 {
+            ids.push(create_test_campaign(&env, &client, &beneficiary));
+        }
+
+        let (_, cursor) = client.list_active_campaigns(&None, &2);
+        client.close_campaign(&ids[3]);
+        assert_eq!(client.compact_active_campaigns(), 1);
+
+        assert_eq!(
+            client.try_list_active_campaigns(&cursor, &2).err(),
+            Some(Ok(SaviaError::StaleCursor))
+        );
+
+        // Restarting from the beginning sees the compacted index
+        let (campaigns, cursor) = client.list_active_campaigns(&None, &10);
+        assert_eq!(campaigns.len(), 4);
+        assert_eq!(cursor, None);
+
+        // Nothing left to remove: the generation and cursors stay valid
+        let (_, cursor) = client.list_active_campaigns(&None, &2);
+        assert_eq!(client.compact_active_campaigns(), 0);
+        assert_eq!(client.list_active_campaigns(&cursor, &2).0.len(), 2);
+    }
+
+    #[test]
+    fn test_donations_and_nfts_listings() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_creation_limits(&CreationLimits { max_active: 0, max_per_day: 0 });
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+
+        let donor = funded_donor(&env, &client, 1000);
+        for _ in 0..3 {
+            client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+        }
+        client.donate(&campaign_id, &donor, &100, &true, &false, &String::from_str(&env, ""));
+        // One badge per campaign
+        for _ in 0..3 {
+            let other = create_test_campaign(&env, &client, &beneficiary);
+            client.donate(&other, &donor, &100, &false, &true, &String::from_str(&env, ""));
+        }
+
+        let (donations, cursor) = client.get_donations_by_campaign(&campaign_id, &None, &3);
+        assert_eq!(donations.len(), 3);
+        let (rest, cursor) = client.get_donations_by_campaign(&campaign_id, &cursor, &3);
+        assert_eq!(rest.len(), 1);
+        assert_eq!(cursor, None);
+        // Listings are redacted the same way as get_donation
+        assert_ne!(rest.get(0).unwrap().donor, donor);
+
+        let (badges, _) = client.get_nfts_by_owner(&donor, &None, &10);
+        assert_eq!(badges.len(), 3);
+        assert_eq!(client.get_nfts_by_owner_at(&donor, &2, &10).len(), 1);
+        assert_eq!(client.get_donations_by_campaign_at(&campaign_id, &1, &10).len(), 3);
+    }
+
+    #[test]
+    fn test_withdraw_accrued_fees() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1000);
+        let token = token::Client::new(&env, &token_address(&env, &client));
+        let treasury = Address::generate(&env);
+
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_accrued_fees(), 20);
+
+        assert_eq!(
+            client.try_withdraw_fees(&treasury, &21),
+            Err(Ok(SaviaError::InsufficientFunds))
+        );
+
+        client.withdraw_fees(&treasury, &15);
+        assert_eq!(client.get_accrued_fees(), 5);
+        assert_eq!(token.balance(&treasury), 15);
+        assert_eq!(token.balance(&client.address), 985);
+
+        // Withdrawals do not reduce the lifetime total
+        let stats = client.get_platform_stats();
+        assert_eq!(stats.total_fees_collected, 20);
+        assert_eq!(stats.total_campaigns, 1);
+        assert_eq!(stats.total_donations, 1);
+    }
+
+    #[test]
+    fn test_withdraw_fees_requires_admin() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let outsider = Address::generate(&env);
+
+        let args = (outsider.clone(), 20u64).into_val(&env);
+        assert!(client.mock_auths(&[mock_auth_for(&outsider, &client.address, "withdraw_fees", args)])
+            .try_withdraw_fees(&outsider, &20)
+            .is_err());
+        assert_eq!(client.get_accrued_fees(), 20);
+    }
+
+    #[test]
+    fn test_low_risk_campaign_skips_review() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+
+        // An established, fully verified beneficiary
+        let earlier = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&earlier, &funded_donor(&env, &client, 100), &100, &false, &false, &String::from_str(&env, ""));
+        set_verification_level(&env, &client, &beneficiary, 2);
+        env.ledger().with_mut(|l| l.timestamp += 60 * 24 * 60 * 60);
+
+        let campaign_id = client.create_campaign(
+            &beneficiary,
+            &String::from_str(&env, "Library books"),
+            &String::from_str(&env, "Books for the village library"),
+            &10000,
+            &30,
+            &Category::Education,
+            &String::from_str(&env, "Oaxaca"),
+            &false,
+            &FundingMode::KeepItAll,
+        );
+
+        assert_eq!(client.get_risk_score(&campaign_id), 0);
+        assert!(client.get_review_queue().first_index_of(&campaign_id).is_none());
+    }
+
+    #[test]
+    fn test_high_risk_campaign_joins_review_queue() {
+        let env = Env::default();
+        let client = setup(&env);
+        create_test_campaign(&env, &client, &Address::generate(&env));
+
+        // New, unverified beneficiary; goal ten times the level 0 cap; reused title
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        assert!(client.get_campaign(&campaign_id).unwrap().possible_duplicate);
+        assert_eq!(client.get_risk_score(&campaign_id), 75);
+        assert_eq!(client.get_review_queue().first_index_of(&campaign_id), Some(1));
+        let event = events_for(&env, topics::CAMPAIGN, topics::QUEUED).last().unwrap();
+        assert_eq!(<(BytesN<32>, u32)>::try_from_val(&env, &event).unwrap(), (campaign_id.clone(), 75));
+
+        // Self-donations and reports push the score further
+        token::StellarAssetClient::new(&env, &token_address(&env, &client)).mint(&beneficiary, &500);
+        client.donate(&campaign_id, &beneficiary, &500, &false, &false, &String::from_str(&env, ""));
+        let reporter = Address::generate(&env);
+        assert_eq!(client.report_campaign(&reporter, &campaign_id), 1);
+        assert_eq!(
+            client.try_report_campaign(&reporter, &campaign_id),
+            Err(Ok(SaviaError::AlreadyReported))
+        );
+        assert_eq!(client.get_risk_score(&campaign_id), 92);
+
+        client.verify_campaign(&campaign_id, &50);
+        assert!(client.get_review_queue().first_index_of(&campaign_id).is_none());
+    }
+
+    #[test]
+    fn test_risk_weights_are_configurable() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        assert_eq!(client.get_risk_score(&campaign_id), 60);
+
+        let weights = RiskWeights {
+            new_beneficiary: 0,
+            unverified: 0,
+            goal_over_cap: 0,
+            duplicate_title: 50,
+            self_donations: 0,
+            reports: 50,
+        };
+        client.set_risk_weights(&weights);
+        assert_eq!(client.get_risk_weights(), weights);
+        assert_eq!(client.get_risk_score(&campaign_id), 0);
+
+        let too_heavy = RiskWeights { reports: 51, ..weights };
+        assert_eq!(
+            client.try_set_risk_weights(&too_heavy),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+    }
+
+    #[test]
+    fn test_reject_disbursement() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let milestone = 0;
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &500, &milestone);
+
+        let reason = String::from_str(&env, "Invoice missing");
+        client.reject_disbursement(&disbursement_id, &reason);
+
+        let disbursement = client.get_disbursement(&disbursement_id).unwrap();
+        assert!(disbursement.status == DisbursementStatus::Rejected);
+        assert_eq!(disbursement.rejection_reason, Some(reason.clone()));
+        let event = events_for(&env, topics::DISBURSEMENT, topics::REJECTED).last().unwrap();
+        assert_eq!(<(BytesN<32>, String)>::try_from_val(&env, &event).unwrap(), (disbursement_id.clone(), reason));
+
+        // A rejected request can be neither approved nor executed
+        assert_eq!(
+            client.try_approve_disbursement(&disbursement_id, &admin_of(&env, &client)),
+            Err(Ok(SaviaError::DisbursementNotPending))
+        );
+        assert_eq!(
+            client.try_execute_disbursement(&disbursement_id, &0),
+            Err(Ok(SaviaError::NotApproved))
+        );
+    }
+
+    #[test]
+    fn test_reject_approved_disbursement_fails() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let milestone = 0;
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &500, &milestone);
+        client.approve_disbursement(&disbursement_id, &admin_of(&env, &client));
+
+        assert_eq!(
+            client.try_reject_disbursement(&disbursement_id, &String::from_str(&env, "Too late")),
+            Err(Ok(SaviaError::DisbursementNotPending))
+        );
+        assert_eq!(client.get_disbursement(&disbursement_id).unwrap().rejection_reason, None);
+    }
+
+    #[test]
+    fn test_cancel_pending_disbursement() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let milestone = 0;
+        let typo = client.create_disbursement(&campaign_id, &beneficiary, &900, &milestone);
+
+        client.cancel_disbursement(&typo);
+        assert!(client.get_disbursement(&typo).unwrap().status == DisbursementStatus::Cancelled);
+        assert_eq!(
+            client.try_approve_disbursement(&typo, &admin_of(&env, &client)),
+            Err(Ok(SaviaError::DisbursementNotPending))
+        );
+
+        // The full balance is still available for a corrected request
+        let fixed = client.create_disbursement(&campaign_id, &beneficiary, &980, &milestone);
+        client.approve_disbursement(&fixed, &admin_of(&env, &client));
+        client.execute_disbursement(&fixed, &0);
+
+        // Approved or executed requests can no longer be cancelled
+        assert_eq!(
+            client.try_cancel_disbursement(&fixed),
+            Err(Ok(SaviaError::DisbursementNotPending))
+        );
+    }
+
+    #[test]
+    fn test_cancel_disbursement_after_approval_fails() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &500, &0);
+        client.approve_disbursement(&disbursement_id, &admin_of(&env, &client));
+
+        assert_eq!(
+            client.try_cancel_disbursement(&disbursement_id),
+            Err(Ok(SaviaError::DisbursementNotPending))
+        );
+        assert!(client.get_disbursement(&disbursement_id).unwrap().status == DisbursementStatus::Approved);
+    }
+
+    #[test]
+    fn test_cancel_disbursement_requires_beneficiary() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &500, &0);
+        let stranger = Address::generate(&env);
+
+        let args = (disbursement_id.clone(),).into_val(&env);
+        assert!(client.mock_auths(&[mock_auth_for(&stranger, &client.address, "cancel_disbursement", args)])
+            .try_cancel_disbursement(&disbursement_id)
+            .is_err());
+        assert!(client.get_disbursement(&disbursement_id).unwrap().status == DisbursementStatus::Pending);
+    }
+
+    #[test]
+    fn test_endgame_locks_underfunded_disbursements() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let end_time = client.get_campaign(&campaign_id).unwrap().end_time;
+        let milestone = 0;
+
+        // Just before the 48h window
+        env.ledger().with_mut(|l| l.timestamp = end_time - 48 * 60 * 60 - 1);
+        client.create_disbursement(&campaign_id, &beneficiary, &100, &milestone);
+        client.check_campaign_health(&campaign_id);
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::ENDGAME_LOCKED).len(), 0);
+
+        // Inside the window
+        env.ledger().with_mut(|l| l.timestamp = end_time - 48 * 60 * 60);
+        assert_eq!(
+            client.try_create_disbursement(&campaign_id, &beneficiary, &100, &milestone),
+            Err(Ok(SaviaError::EndgameLocked))
+        );
+
+        // The maintenance pass announces the lock once
+        client.check_campaign_health(&campaign_id);
+        client.check_campaign_health(&campaign_id);
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::ENDGAME_LOCKED).len(), 1);
+        assert!(client.get_campaign(&campaign_id).unwrap().endgame_locked);
+    }
+
+    #[test]
+    fn test_endgame_exempts_funded_campaigns() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        set_verification_level(&env, &client, &beneficiary, 2);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 20000), &20000, &false, &false, &String::from_str(&env, ""));
+        let end_time = client.get_campaign(&campaign_id).unwrap().end_time;
+        let milestone = 0;
+
+        env.ledger().with_mut(|l| l.timestamp = end_time - 48 * 60 * 60 - 1);
+        client.create_disbursement(&campaign_id, &beneficiary, &100, &milestone);
+
+        env.ledger().with_mut(|l| l.timestamp = end_time - 60);
+        client.create_disbursement(&campaign_id, &beneficiary, &100, &milestone);
+        client.check_campaign_health(&campaign_id);
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::ENDGAME_LOCKED).len(), 0);
+    }
+
+    #[test]
+    fn test_category_labels_in_two_languages() {
+        let env = Env::default();
+        let client = setup(&env);
+        let health = Category::Health;
+        let education = Category::Education;
+        let es = Symbol::new(&env, "es");
+        let en = Symbol::new(&env, "en");
+
+        client.add_category(&health);
+        client.add_category(&education);
+        client.set_category_label(&health, &es, &String::from_str(&env, "Salud"));
+        client.set_category_label(&health, &en, &String::from_str(&env, "Health care"));
+        client.set_category_label(&education, &es, &String::from_str(&env, "Educación"));
+
+        assert_eq!(client.get_category_label(&health, &es), Some(String::from_str(&env, "Salud")));
+        assert_eq!(client.get_category_label(&education, &en), None);
+
+        let spanish = client.get_categories_localized(&es);
+        assert_eq!(spanish.get(0).unwrap(), (health, String::from_str(&env, "Salud")));
+        assert_eq!(spanish.get(1).unwrap(), (education, String::from_str(&env, "Educación")));
+
+        // Missing labels fall back to the category name
+        let english = client.get_categories_localized(&en);
+        assert_eq!(english.get(1).unwrap(), (education, String::from_str(&env, "Education")));
+
+        let too_long = String::from_str(&env, "A label that is far too long to fit on the screen!");
+        assert_eq!(
+            client.try_set_category_label(&health, &en, &too_long),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+    }
+
+    #[test]
+    fn test_removing_category_cleans_up_labels() {
+        let env = Env::default();
+        let client = setup(&env);
+        let health = Category::Health;
+        let es = Symbol::new(&env, "es");
+
+        client.add_category(&health);
+        client.set_category_label(&health, &es, &String::from_str(&env, "Salud"));
+        client.remove_category(&health);
+
+        assert_eq!(client.get_category_label(&health, &es), None);
+        assert_eq!(client.get_categories_localized(&es).len(), 0);
+        assert_eq!(
+            client.try_set_category_label(&health, &es, &String::from_str(&env, "Salud")),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+
+        // Re-adding the category starts without labels
+        client.add_category(&health);
+        assert_eq!(client.get_category_label(&health, &es), None);
+    }
+
+    #[test]
+    fn test_category_allowlist_restricts_campaigns() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        client.add_category(&Category::Health);
+
+        assert_eq!(
+            client.try_create_campaign(
+                &beneficiary,
+                &String::from_str(&env, "Bake sale"),
+                &String::from_str(&env, "Cakes"),
+                &1000,
+                &30,
+                &Category::Emergency,
+                &String::from_str(&env, "Puebla"),
+                &false,
+                &FundingMode::KeepItAll,
+            ),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+    }
+
+    #[test]
+    fn test_amounts_beyond_u64_end_to_end() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        set_verification_level(&env, &client, &beneficiary, 2);
+        let token = token::Client::new(&env, &token_address(&env, &client));
+
+        let goal = 4 * u64::MAX as i128;
+        let campaign_id = client.create_campaign(
+            &beneficiary,
+            &String::from_str(&env, "Dam repair"),
+            &String::from_str(&env, "Rebuild the regional dam"),
+            &goal,
+            &30,
+            &Category::Health,
+            &String::from_str(&env, "Chiapas"),
+            &false,
+            &FundingMode::KeepItAll,
+        );
+        client.verify_campaign(&campaign_id, &80);
+
+        let amount = 3 * u64::MAX as i128;
+        let donor = funded_donor(&env, &client, amount);
+        let donation_id = client.donate(&campaign_id, &donor, &amount, &false, &false, &String::from_str(&env, ""));
+
+        let fee = amount * 200 / 10000;
+        assert_eq!(client.get_donation(&donation_id).unwrap().amount, amount - fee);
+        assert_eq!(client.get_accrued_fees(), fee);
+        assert_eq!(client.get_platform_stats().total_fees_collected, fee);
+        assert_eq!(client.quote_donation(&amount).fee, fee);
+
+        let payout = 2 * u64::MAX as i128;
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &payout, &0);
+        client.approve_disbursement(&disbursement_id, &admin_of(&env, &client));
+        client.execute_disbursement(&disbursement_id, &0);
+        assert_eq!(token.balance(&beneficiary), payout);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().disbursed_amount, payout);
+    }
+
+    #[test]
+    fn test_fee_split_near_i128_max() {
+        let env = Env::default();
+        let client = setup(&env);
+
+        let quote = client.quote_donation(&i128::MAX);
+        assert_eq!(quote.fee, (i128::MAX / 10000) * 200 + (i128::MAX % 10000) * 200 / 10000);
+        assert_eq!(quote.fee + quote.net, i128::MAX);
+    }
+
+    #[test]
+    fn test_non_positive_amounts_rejected() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+        let invalid_amount = Err(Ok(SaviaError::InvalidAmount));
+
+        assert_eq!(client.try_donate(&campaign_id, &donor, &-5, &false, &false, &String::from_str(&env, "")), invalid_amount);
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.try_create_disbursement(&campaign_id, &beneficiary, &-1, &0), invalid_amount);
+        assert_eq!(client.try_withdraw_fees(&beneficiary, &0).err(), invalid_amount.err());
+    }
+
+    #[test]
+    fn test_donation_overflow_returns_error() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        client.verify_campaign(&campaign_id, &80);
+        let donor = funded_donor(&env, &client, 1000);
+
+        // No real token supply can get a campaign here, so plant the total
+        env.as_contract(&client.address, || {
+            let key = DataKey::Campaign(campaign_id.clone());
+            let mut campaign: Campaign = env.storage().persistent().get(&key).unwrap();
+            campaign.current_amount = i128::MAX - 10;
+            env.storage().persistent().set(&key, &campaign);
+        });
+
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, "")),
+            Err(Ok(SaviaError::Overflow))
+        );
+        assert_eq!(client.get_platform_stats().total_donations, 0);
+    }
+
+    #[test]
+    fn test_migrate_amounts_upgrades_u64_records() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+
+        // Put records and config back into their u64 layout
+        env.as_contract(&client.address, || {
+            let storage = env.storage().persistent();
+            let campaign: Campaign = storage.get(&DataKey::Campaign(campaign_id.clone())).unwrap();
+            storage.set(&DataKey::Campaign(campaign_id.clone()), &LegacyCampaign {
+                id: campaign.id,
+                title: campaign.title,
+                description: campaign.description,
+                beneficiary: campaign.beneficiary,
+                goal_amount: campaign.goal_amount as u64,
+                current_amount: campaign.current_amount as u64,
+                disbursed_amount: campaign.disbursed_amount as u64,
+                start_time: campaign.start_time,
+                end_time: campaign.end_time,
+                verified: campaign.verified,
+                trust_score: campaign.trust_score,
+                category: String::from_str(&env, campaign.category.name()),
+                location: campaign.location,
+                at_risk: campaign.at_risk,
+                at_risk_acknowledged_at: campaign.at_risk_acknowledged_at,
+                perpetual: campaign.perpetual,
+                active: campaign.active,
+                funding_mode: campaign.funding_mode,
+                possible_duplicate: campaign.possible_duplicate,
+                endgame_locked: campaign.endgame_locked,
+            });
+            let donation: Donation = storage.get(&DataKey::Donation(donation_id.clone())).unwrap();
+            storage.set(&DataKey::Donation(donation_id.clone()), &LegacyDonation {
+                id: donation.id,
+                campaign_id: donation.campaign_id,
+                donor: donation.donor,
+                amount: donation.amount as u64,
+                timestamp: donation.timestamp,
+                nft_minted: donation.nft_minted,
+                privacy: donation.privacy,
+                refunded: donation.refunded,
+            });
+            storage.set(&DataKey::DonorCampaignTotal(campaign_id.clone(), donor.clone()), &1000u64);
+            env.storage().instance().set(&DataKey::AccruedFees, &20u64);
+            env.storage().instance().set(&DataKey::DisbursementLimits, &soroban_sdk::vec![&env, (0u32, 5_000u64)]);
+        });
+
+        // Config reads accept the old form, and migrate keeps the digest stable
+        assert_eq!(client.get_accrued_fees(), 20);
+        assert_eq!(client.get_disbursement_limit(&beneficiary), 5_000);
+        let digest = client.export_state_digest();
+        client.migrate();
+        assert!(client.verify_state_digest(&digest));
+
+        let keys = soroban_sdk::vec![
+            &env,
+            DataKey::Campaign(campaign_id.clone()),
+            DataKey::Donation(donation_id.clone()),
+            DataKey::DonorCampaignTotal(campaign_id.clone(), donor.clone()),
+        ];
+        assert_eq!(client.migrate_amounts(&keys), 3);
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert_eq!(campaign.current_amount, 980);
+        assert_eq!(campaign.category, Category::Health);
+        assert_eq!(campaign.legacy_category, Some(String::from_str(&env, "Health")));
+        assert_eq!(client.get_donation(&donation_id).unwrap().amount, 980);
+
+        // Running it again finds nothing left to upgrade
+        assert_eq!(client.migrate_amounts(&keys), 0);
+    }
+
+    /// Ledgers left before the persistent entry under `key` is archived
+    fn ttl_of(env: &Env, client: &SaviaContractClient, key: &DataKey) -> u32 {
+        env.as_contract(&client.address, || env.storage().persistent().get_ttl(key))
+    }
+
+    #[test]
+    fn test_records_survive_past_default_ttl() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1000);
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let key = DataKey::Campaign(campaign_id.clone());
+        assert_eq!(ttl_of(&env, &client, &key), DEFAULT_TTL_EXTEND_TO);
+
+        // Well past the network's minimum TTL, the records are still live
+        env.ledger().with_mut(|l| l.sequence_number = 100_000);
+        assert!(client.get_campaign(&campaign_id).is_some());
+        assert!(client.get_donation(&donation_id).is_some());
+        assert_eq!(ttl_of(&env, &client, &key), DEFAULT_TTL_EXTEND_TO - 100_000);
+
+        // Once under the threshold, a read extends the record again
+        env.ledger().with_mut(|l| l.sequence_number = 450_000);
+        assert!(client.get_campaign(&campaign_id).is_some());
+        assert_eq!(ttl_of(&env, &client, &key), DEFAULT_TTL_EXTEND_TO);
+    }
+
+    #[test]
+    fn test_bump_campaign_with_custom_ttl_config() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_ttl_config(&5_000, &10_000);
+        assert_eq!(client.get_ttl_config(), (5_000, 10_000));
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let key = DataKey::Campaign(campaign_id.clone());
+        assert_eq!(ttl_of(&env, &client, &key), 10_000);
+
+        env.ledger().with_mut(|l| l.sequence_number = 6_000);
+        assert_eq!(ttl_of(&env, &client, &key), 4_000);
+        client.bump_campaign(&campaign_id);
+        assert_eq!(ttl_of(&env, &client, &key), 10_000);
+
+        let missing = BytesN::from_array(&env, &[9; 32]);
+        assert_eq!(client.try_bump_campaign(&missing), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_set_ttl_config(&10_000, &5_000), Err(Ok(SaviaError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_finalize_expired_campaign() {
+        let env = Env::default();
+        let client = setup(&env);
+        let funded = create_test_campaign(&env, &client, &Address::generate(&env));
+        let unfunded = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 20_000);
+        client.donate(&funded, &donor, &20_000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_platform_stats().active_campaigns, 2);
+
+        // Not before the end time
+        assert_eq!(client.try_finalize_campaign(&funded), Err(Ok(SaviaError::CampaignNotEnded)));
+
+        env.ledger().with_mut(|l| l.timestamp = 31 * 24 * 60 * 60);
+        assert!(client.finalize_campaign(&funded));
+        assert_eq!(client.get_platform_stats().active_campaigns, 1);
+        let event = events_for(&env, topics::CAMPAIGN, topics::FINALIZED).last().unwrap();
+        assert_eq!(<(BytesN<32>, bool)>::try_from_val(&env, &event).unwrap(), (funded.clone(), true));
+
+        assert!(!client.finalize_campaign(&unfunded));
+        let campaign = client.get_campaign(&unfunded).unwrap();
+        assert!(!campaign.active);
+        assert!(!campaign.goal_reached);
+        assert_eq!(client.get_platform_stats().active_campaigns, 0);
+
+        // Only once
+        assert_eq!(client.try_finalize_campaign(&funded), Err(Ok(SaviaError::CampaignInactive)));
+    }
+
+    #[test]
+    fn test_nfts_by_owner() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 10_000);
+        assert_eq!(client.get_nft_count(&donor), 0);
+
+        let mut campaigns = soroban_sdk::vec![&env];
+        for amount in [100, 1000, 5000] {
+            let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+            client.donate(&campaign_id, &donor, &amount, &false, &true, &String::from_str(&env, ""));
+            campaigns.push_back(campaign_id);
+        }
+        client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+
+        assert_eq!(client.get_nft_count(&donor), 3);
+        let badges = client.get_nfts_by_owner_at(&donor, &0, &10);
+        assert_eq!(badges.len(), 3);
+        for (badge, campaign_id) in badges.iter().zip(campaigns.iter()) {
+            assert_eq!(badge.owner, donor);
+            assert_eq!(badge.campaign_id, campaign_id);
+        }
+        assert_eq!(client.get_nft_count(&Address::generate(&env)), 0);
+    }
+
+    #[test]
+    fn test_transfer_nft_moves_owner_index() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_badge_transferable(&String::from_str(&env, "Bronze Supporter"), &true);
+        let donor = funded_donor(&env, &client, 1000);
+        for _ in 0..2 {
+            let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+            client.donate(&campaign_id, &donor, &100, &false, &true, &String::from_str(&env, ""));
+        }
+        let nft_id = client.get_nfts_by_owner_at(&donor, &0, &1).get(0).unwrap().id;
+        assert!(!client.get_nft(&nft_id).unwrap().soulbound);
+
+        let friend = Address::generate(&env);
+        client.transfer_nft(&nft_id, &donor, &friend);
+        assert_eq!(client.get_nft(&nft_id).unwrap().owner, friend);
+        assert_eq!(client.get_nft_count(&donor), 1);
+        assert_eq!(client.get_nft_count(&friend), 1);
+        assert_eq!(client.get_nfts_by_owner_at(&friend, &0, &10).get(0).unwrap().id, nft_id);
+        assert!(client.get_nfts_by_owner_at(&donor, &0, &10).iter().all(|badge| badge.id != nft_id));
+        let event = events_for(&env, topics::NFT, topics::TRANSFERRED).last().unwrap();
+        assert_eq!(<(BytesN<32>, Address, Address)>::try_from_val(&env, &event).unwrap(), (nft_id.clone(), donor.clone(), friend.clone()));
+
+        // The previous owner can no longer move it
+        assert_eq!(client.try_transfer_nft(&nft_id, &donor, &donor), Err(Ok(SaviaError::Unauthorized)));
+        assert_eq!(
+            client.try_transfer_nft(&BytesN::from_array(&env, &[9; 32]), &friend, &donor),
+            Err(Ok(SaviaError::NFTNotFound))
+        );
+    }
+
+    #[test]
+    fn test_soulbound_badge_cannot_be_transferred() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1000);
+        client.donate(&campaign_id, &donor, &100, &false, &true, &String::from_str(&env, ""));
+        let nft_id = client.get_nfts_by_owner_at(&donor, &0, &1).get(0).unwrap().id;
+
+        assert!(client.get_nft(&nft_id).unwrap().soulbound);
+        assert_eq!(
+            client.try_transfer_nft(&nft_id, &donor, &Address::generate(&env)),
+            Err(Ok(SaviaError::NonTransferable))
+        );
+        assert_eq!(client.get_nft_count(&donor), 1);
+    }
+
+    #[test]
+    fn test_badge_metadata_per_tier() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let gold = String::from_str(&env, "Gold Supporter");
+        let gold_uri = String::from_str(&env, "https://savia.org/nft/gold");
+        assert_eq!(client.get_badge_metadata(&gold), None);
+
+        client.set_badge_metadata(&gold, &gold_uri);
+        assert_eq!(client.get_badge_metadata(&gold), Some(gold_uri.clone()));
+
+        let donor = funded_donor(&env, &client, 10_000);
+        client.donate(&campaign_id, &donor, &7000, &false, &true, &String::from_str(&env, ""));
+        let other_campaign = create_test_campaign(&env, &client, &Address::generate(&env));
+        client.donate(&other_campaign, &donor, &100, &false, &true, &String::from_str(&env, ""));
+        let badges = client.get_nfts_by_owner_at(&donor, &0, &10);
+        assert_eq!(badges.get(0).unwrap().badge_type, gold);
+        assert_eq!(badges.get(0).unwrap().metadata_uri, gold_uri);
+        // Unconfigured tiers get a URI of their own under the default base
+        let bronze = badges.get(1).unwrap();
+        assert_eq!(bronze.metadata_uri, expected_token_uri(&env, DEFAULT_METADATA_BASE_URI, &bronze.id));
+
+        assert_eq!(client.try_set_badge_metadata(&gold, &String::from_str(&env, "")), Err(Ok(SaviaError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_repeat_donations_upgrade_one_badge() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 10_000);
+        let silver_uri = String::from_str(&env, "https://savia.org/nft/silver");
+        client.set_badge_metadata(&String::from_str(&env, "Silver Supporter"), &silver_uri);
+
+        // Each donation alone is Bronze; together they pass the Silver line
+        for _ in 0..3 {
+            client.donate(&campaign_id, &donor, &900, &false, &true, &String::from_str(&env, ""));
+        }
+
+        assert_eq!(client.get_nft_count(&donor), 1);
+        let badge = client.get_nfts_by_owner_at(&donor, &0, &10).get(0).unwrap();
+        assert_eq!(badge.badge_type, String::from_str(&env, "Silver Supporter"));
+        assert_eq!(badge.metadata_uri, silver_uri);
+        assert_eq!(events_for(&env, topics::NFT, topics::MINTED).len(), 1);
+        let event = events_for(&env, topics::NFT, topics::UPGRADED).last().unwrap();
+        assert_eq!(
+            <(BytesN<32>, Address, String)>::try_from_val(&env, &event).unwrap(),
+            (badge.id, donor, String::from_str(&env, "Silver Supporter"))
+        );
+    }
+
+    #[test]
+    fn test_get_config_matches_initialization() {
+        let env = Env::default();
+        let client = SaviaContractClient::new(&env, &env.register_contract(None, SaviaContract));
+        assert_eq!(client.get_admin(), None);
+        assert_eq!(client.try_get_config().err(), Some(Ok(SaviaError::NotInitialized)));
+
+        let admin = Address::generate(&env);
+        let token = Address::generate(&env);
+        client.initialize(&admin, &token, &150);
+
+        assert_eq!(client.get_admin(), Some(admin.clone()));
+        assert_eq!(
+            client.get_config(),
+            Config {
+                admin,
+                token,
+                platform_fee: 150,
+                campaign_count: 0,
+                donation_count: 0,
+                nft_count: 0,
+                disbursement_count: 0,
+            }
+        );
+
+        env.mock_all_auths();
+        create_test_campaign(&env, &client, &Address::generate(&env));
+        assert_eq!(client.get_config().campaign_count, 1);
+    }
+
+    #[test]
+    fn test_list_campaigns_with_filters() {
+        let env = Env::default();
+        let client = setup(&env);
+        let mut ids = std::vec::Vec::new();
+        for _ in 0..6 {
+            ids.push(create_test_campaign(&env, &client, &Address::generate(&env)));
+        }
+        client.close_campaign(&ids[1]);
+        client.close_campaign(&ids[4]);
+        client.verify_campaign(&ids[2], &50);
+        assert_eq!(client.get_campaign_count(), 6);
+
+        let listed = |offset: u32, limit: u32, only_active: bool, only_verified: bool| -> std::vec::Vec<BytesN<32>> {
+            client.list_campaigns(&offset, &limit, &only_active, &only_verified).iter().map(|c| c.id).collect()
+        };
+        assert_eq!(listed(0, 10, false, false), ids);
+        assert_eq!(listed(0, 10, true, false), [ids[0].clone(), ids[2].clone(), ids[3].clone(), ids[5].clone()]);
+        assert_eq!(listed(1, 2, true, false), [ids[2].clone(), ids[3].clone()]);
+        assert_eq!(listed(0, 10, true, true), [ids[2].clone()]);
+        assert_eq!(listed(1, 10, false, true).len(), 0);
+    }
+
+    #[test]
+    fn test_campaigns_by_category() {
+        let env = Env::default();
+        let client = setup(&env);
+        let first = create_test_campaign(&env, &client, &Address::generate(&env));
+        let school = client.create_campaign(
+            &Address::generate(&env),
+            &String::from_str(&env, "School roof"),
+            &String::from_str(&env, "Fix the roof"),
+            &5000,
+            &30,
+            &Category::Education,
+            &String::from_str(&env, "Oaxaca"),
+            &false,
+            &FundingMode::KeepItAll,
+        );
+        let second = create_test_campaign(&env, &client, &Address::generate(&env));
+
+        let health = client.get_campaigns_by_category(&Category::Health, &0, &10);
+        assert_eq!(health.len(), 2);
+        assert_eq!(health.get(0).unwrap().id, first);
+        assert_eq!(health.get(1).unwrap().id, second);
+        assert_eq!(client.get_campaigns_by_category(&Category::Health, &1, &10).get(0).unwrap().id, second);
+
+        let education = client.get_campaigns_by_category(&Category::Education, &0, &10);
+        assert_eq!(education.len(), 1);
+        assert_eq!(education.get(0).unwrap().id, school);
+        assert_eq!(education.get(0).unwrap().legacy_category, None);
+        assert_eq!(client.get_campaigns_by_category(&Category::Emergency, &0, &10).len(), 0);
+    }
+
+    #[test]
+    fn test_campaign_stats() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        assert_eq!(client.get_campaign_stats(&campaign_id), CampaignStats::default());
+
+        let alice = funded_donor(&env, &client, 10_000);
+        let bob = funded_donor(&env, &client, 10_000);
+        client.donate(&campaign_id, &alice, &1000, &false, &false, &String::from_str(&env, ""));
+        client.donate(&campaign_id, &bob, &5000, &false, &false, &String::from_str(&env, ""));
+        client.donate(&campaign_id, &alice, &2000, &false, &false, &String::from_str(&env, ""));
+
+        assert_eq!(
+            client.get_campaign_stats(&campaign_id),
+            CampaignStats { donation_count: 3, unique_donors: 2, largest_donation: 4900, total_fees: 160 }
+        );
+    }
+
+    #[test]
+    fn test_milestone_plan_caps_disbursements() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        set_verification_level(&env, &client, &beneficiary, 2);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let milestone = |title: &str, percentage: u32| Milestone { title: String::from_str(&env, title), percentage };
+
+        assert_eq!(
+            client.try_set_milestones(&campaign_id, &soroban_sdk::vec![&env, milestone("Equipment", 40), milestone("Delivery", 50)]),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+        let plan = soroban_sdk::vec![&env, milestone("Equipment", 40), milestone("Delivery", 60)];
+        client.set_milestones(&campaign_id, &plan);
+        assert_eq!(client.get_milestones(&campaign_id), plan);
+
+        let donor = funded_donor(&env, &client, 20_000);
+        client.donate(&campaign_id, &donor, &20_000, &false, &false, &String::from_str(&env, ""));
+        // The plan is fixed once money has come in
+        assert_eq!(client.try_set_milestones(&campaign_id, &plan), Err(Ok(SaviaError::InvalidInput)));
+
+        // Equipment gets 40% of the 10_000 goal
+        let exceeded = Err(Ok(SaviaError::MilestoneAllocationExceeded));
+        assert_eq!(client.try_create_disbursement(&campaign_id, &beneficiary, &4_001, &0), exceeded);
+        let first = client.create_disbursement(&campaign_id, &beneficiary, &3_000, &0);
+        assert_eq!(client.get_disbursement(&first).unwrap().milestone, String::from_str(&env, "Equipment"));
+        assert_eq!(client.try_create_disbursement(&campaign_id, &beneficiary, &1_001, &0), exceeded);
+        client.create_disbursement(&campaign_id, &beneficiary, &1_000, &0);
+        client.create_disbursement(&campaign_id, &beneficiary, &6_000, &1);
+        assert_eq!(
+            client.try_create_disbursement(&campaign_id, &beneficiary, &1, &2),
+            Err(Ok(SaviaError::MilestoneNotFound))
+        );
+
+        // A rejected request frees its share again
+        client.reject_disbursement(&first, &String::from_str(&env, "Quote too high"));
+        assert!(client.try_create_disbursement(&campaign_id, &beneficiary, &3_000, &0).is_ok());
+    }
+
+    /// Campaign with donor voting and two donors giving 70% and 30% of it
+    fn voting_campaign(env: &Env, client: &SaviaContractClient) -> (BytesN<32>, Address, Address, Address) {
+        let beneficiary = Address::generate(env);
+        set_verification_level(env, client, &beneficiary, 2);
+        let campaign_id = create_test_campaign(env, client, &beneficiary);
+        client.enable_donor_voting(&campaign_id, &(3 * 24 * 60 * 60));
+
+        let major = funded_donor(env, client, 7000);
+        let minor = funded_donor(env, client, 3000);
+        client.donate(&campaign_id, &major, &7000, &false, &false, &String::from_str(env, ""));
+        client.donate(&campaign_id, &minor, &3000, &false, &false, &String::from_str(env, ""));
+        (campaign_id, beneficiary, major, minor)
+    }
+
+    #[test]
+    fn test_donor_majority_approves_disbursement() {
+        let env = Env::default();
+        let client = setup(&env);
+        let (campaign_id, beneficiary, major, minor) = voting_campaign(&env, &client);
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &1000, &0);
+
+        // Votes replace admin approval
+        assert_eq!(client.try_approve_disbursement(&disbursement_id, &admin_of(&env, &client)), Err(Ok(SaviaError::Unauthorized)));
+
+        client.vote_disbursement(&disbursement_id, &minor, &true);
+        assert_eq!(client.try_vote_disbursement(&disbursement_id, &minor, &true), Err(Ok(SaviaError::AlreadyVoted)));
+        assert_eq!(
+            client.try_vote_disbursement(&disbursement_id, &Address::generate(&env), &true),
+            Err(Ok(SaviaError::Unauthorized))
+        );
+        assert_eq!(client.try_tally_disbursement(&disbursement_id), Err(Ok(SaviaError::InvalidInput)));
+
+        client.vote_disbursement(&disbursement_id, &major, &true);
+        let tally = client.get_vote_tally(&disbursement_id).unwrap();
+        assert_eq!((tally.approve_weight, tally.reject_weight), (9800, 0));
+        assert_eq!(client.tally_disbursement(&disbursement_id), DisbursementStatus::Approved);
+        client.execute_disbursement(&disbursement_id, &0);
+    }
+
+    #[test]
+    fn test_donor_minority_cannot_approve_disbursement() {
+        let env = Env::default();
+        let client = setup(&env);
+        let (campaign_id, beneficiary, major, minor) = voting_campaign(&env, &client);
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &1000, &0);
+
+        client.vote_disbursement(&disbursement_id, &minor, &true);
+        client.vote_disbursement(&disbursement_id, &major, &false);
+
+        env.ledger().with_mut(|l| l.timestamp = 3 * 24 * 60 * 60 + 1);
+        assert_eq!(client.try_vote_disbursement(&disbursement_id, &major, &true), Err(Ok(SaviaError::InvalidInput)));
+        assert_eq!(client.tally_disbursement(&disbursement_id), DisbursementStatus::Rejected);
+        assert_eq!(
+            client.try_tally_disbursement(&disbursement_id),
+            Err(Ok(SaviaError::DisbursementNotPending))
+        );
+    }
+
+    #[test]
+    fn test_pause_blocks_donations_until_unpaused() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+        let donation_id = client.donate(&campaign_id, &donor, &500, &false, &false, &String::from_str(&env, ""));
+
+        client.pause();
+        assert!(client.is_paused());
+        assert_eq!(events_for(&env, topics::ADMIN, topics::PAUSED).len(), 1);
+        let paused = Err(Ok(SaviaError::ContractPaused));
+        assert_eq!(client.try_donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, "")), paused);
+        assert_eq!(client.try_create_disbursement(&campaign_id, &beneficiary, &100, &0), paused);
+        assert_eq!(
+            client.try_create_campaign(
+                &beneficiary,
+                &String::from_str(&env, "Clinic"),
+                &String::from_str(&env, "New clinic"),
+                &1000,
+                &30,
+                &Category::Health,
+                &String::from_str(&env, "Tabasco"),
+                &false,
+                &FundingMode::KeepItAll,
+            ),
+            paused
+        );
+        // Reads keep working
+        assert_eq!(client.get_donation(&donation_id).unwrap().amount, 490);
+
+        client.unpause();
+        assert!(!client.is_paused());
+        assert_eq!(events_for(&env, topics::ADMIN, topics::UNPAUSED).len(), 1);
+        client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+    }
+
+    #[test]
+    fn test_upgrade_requires_admin_and_version_reads_back() {
+        let env = Env::default();
+        let client = setup(&env);
+        assert_eq!(client.version(), CONTRACT_VERSION);
+
+        let outsider = Address::generate(&env);
+        let hash = BytesN::from_array(&env, &[7; 32]);
+        let args = (hash.clone(),).into_val(&env);
+        assert!(client.mock_auths(&[mock_auth_for(&outsider, &client.address, "upgrade", args)])
+            .try_upgrade(&hash)
+            .is_err());
+        assert_eq!(client.version(), CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn test_preview_donation_matches_donation() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 20000);
+
+        for fee in [0u64, 200, 1000] {
+            client.set_platform_fee(&fee);
+            let preview = client.preview_donation(&campaign_id, &3333);
+            assert_eq!(preview.gross, 3333);
+            assert_eq!(preview.fee + preview.net, 3333);
+
+            let donation_id = client.donate(&campaign_id, &donor, &3333, &false, &false, &String::from_str(&env, ""));
+            assert_eq!(client.get_donation(&donation_id).unwrap().amount, preview.net);
+        }
+
+        client.set_platform_fee(&0);
+        let raised = client.get_campaign(&campaign_id).unwrap().current_amount;
+        assert!(!client.preview_donation(&campaign_id, &(9999 - raised)).completes_goal);
+        let preview = client.preview_donation(&campaign_id, &5000);
+        assert!(preview.completes_goal);
+        assert_eq!(preview.badge_type, String::from_str(&env, "Gold Supporter"));
+
+        assert_eq!(client.try_preview_donation(&campaign_id, &0), Err(Ok(SaviaError::InvalidAmount)));
+        let missing = BytesN::from_array(&env, &[9; 32]);
+        assert_eq!(client.try_preview_donation(&missing, &100), Err(Ok(SaviaError::CampaignNotFound)));
+        env.ledger().with_mut(|li| li.timestamp += 31 * 24 * 60 * 60);
+        assert_eq!(client.try_preview_donation(&campaign_id, &100), Err(Ok(SaviaError::CampaignEnded)));
+    }
+
+    #[test]
+    fn test_donation_limits() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 5000);
+
+        assert_eq!(client.try_set_donation_limits(&campaign_id, &-1, &0), Err(Ok(SaviaError::InvalidAmount)));
+        assert_eq!(client.try_set_donation_limits(&campaign_id, &500, &100), Err(Ok(SaviaError::InvalidInput)));
+        client.set_donation_limits(&campaign_id, &100, &1000);
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::LIMITS).len(), 1);
+
+        assert_eq!(client.try_donate(&campaign_id, &donor, &99, &false, &false, &String::from_str(&env, "")), Err(Ok(SaviaError::InvalidAmount)));
+        client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+        client.donate(&campaign_id, &donor, &800, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &101, &false, &false, &String::from_str(&env, "")),
+            Err(Ok(SaviaError::DonationCapExceeded))
+        );
+        client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+
+        // The cap is per donor
+        let other = funded_donor(&env, &client, 1000);
+        client.donate(&campaign_id, &other, &1000, &false, &false, &String::from_str(&env, ""));
+
+        // Limits are fixed once donations arrived
+        assert_eq!(client.try_set_donation_limits(&campaign_id, &0, &0), Err(Ok(SaviaError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_overfunding_limit_caps_donations() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_platform_fee(&0);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 20000);
+
+        assert_eq!(client.try_set_overfunding_limit(&campaign_id, &9999), Err(Ok(SaviaError::InvalidInput)));
+        client.set_overfunding_limit(&campaign_id, &12000);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().funding_cap(), Some(12000));
+
+        client.donate(&campaign_id, &donor, &6000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::GOAL_MET).len(), 0);
+        assert_eq!(client.try_donate(&campaign_id, &donor, &6001, &false, &false, &String::from_str(&env, "")), Err(Ok(SaviaError::GoalExceeded)));
+        assert_eq!(client.try_preview_donation(&campaign_id, &6001), Err(Ok(SaviaError::GoalExceeded)));
+
+        // The donation that exactly fills the cap is accepted
+        client.donate(&campaign_id, &donor, &6000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 12000);
+        assert_eq!(client.try_donate(&campaign_id, &donor, &1, &false, &false, &String::from_str(&env, "")), Err(Ok(SaviaError::GoalExceeded)));
+
+        // Goal met is announced once, on the donation that crossed the goal
+        let events = events_for(&env, topics::CAMPAIGN, topics::GOAL_MET);
+        assert_eq!(events.len(), 1);
+        let event = <(BytesN<32>, i128)>::try_from_val(&env, &events.last().unwrap()).unwrap();
+        assert_eq!(event, (campaign_id, 12000));
+    }
+
+    #[test]
+    fn test_withdraw_remaining_after_end() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+
+        let wallet = Address::generate(&env);
+        assert_eq!(
+            client.try_withdraw_remaining(&campaign_id, &wallet),
+            Err(Ok(SaviaError::CampaignNotEnded))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += 31 * 24 * 60 * 60);
+        client.pause();
+        assert_eq!(
+            client.try_withdraw_remaining(&campaign_id, &wallet),
+            Err(Ok(SaviaError::ContractPaused))
+        );
+        client.unpause();
+        assert_eq!(client.withdraw_remaining(&campaign_id, &wallet), 980);
+        assert_eq!(token::Client::new(&env, &token_address(&env, &client)).balance(&wallet), 980);
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert_eq!(campaign.disbursed_amount, campaign.current_amount);
+        let event = events_for(&env, topics::CAMPAIGN, topics::WITHDRAWN).last().unwrap();
+        assert_eq!(<(BytesN<32>, Address, i128)>::try_from_val(&env, &event).unwrap(), (campaign_id.clone(), wallet.clone(), 980));
+
+        assert_eq!(
+            client.try_withdraw_remaining(&campaign_id, &wallet),
+            Err(Ok(SaviaError::InsufficientFunds))
+        );
+    }
+
+    #[test]
+    fn test_donation_message_round_trips() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+
+        let message = String::from_str(&env, "Get well soon!");
+        let donation_id = client.donate(&campaign_id, &donor, &500, &true, &false, &message);
+
+        // Anonymous donations keep the message but hide the donor
+        let donation = client.get_donation(&donation_id).unwrap();
+        assert_eq!(donation.message, message);
+        assert_eq!(donation.donor, client.address);
+        let (listed, _) = client.get_donations_by_campaign(&campaign_id, &None, &10);
+        assert_eq!(listed.get(0).unwrap().message, message);
+    }
+
+    #[test]
+    fn test_donation_message_length_capped() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+
+        let too_long = String::from_bytes(&env, &[b'a'; MAX_DONATION_MESSAGE_LEN as usize + 1]);
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &500, &false, &false, &too_long),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+        let longest = String::from_bytes(&env, &[b'a'; MAX_DONATION_MESSAGE_LEN as usize]);
+        client.donate(&campaign_id, &donor, &500, &false, &false, &longest);
+    }
+
+    #[test]
+    fn test_suspend_campaign_blocks_disbursements_and_allows_refunds() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        set_verification_level(&env, &client, &beneficiary, 2);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &500, &0);
+        client.approve_disbursement(&disbursement_id, &admin_of(&env, &client));
+
+        let reason = String::from_str(&env, "Fraudulent beneficiary");
+        client.suspend_campaign(&campaign_id, &reason);
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert!(campaign.suspended);
+        assert!(!campaign.active);
+        let event = events_for(&env, topics::CAMPAIGN, topics::SUSPENDED).last().unwrap();
+        assert_eq!(<(BytesN<32>, String)>::try_from_val(&env, &event).unwrap(), (campaign_id.clone(), reason.clone()));
+        assert_eq!(client.try_suspend_campaign(&campaign_id, &reason), Err(Ok(SaviaError::CampaignSuspended)));
+
+        assert_eq!(client.try_execute_disbursement(&disbursement_id, &0), Err(Ok(SaviaError::CampaignSuspended)));
+        assert_eq!(
+            client.try_create_disbursement(&campaign_id, &beneficiary, &100, &0),
+            Err(Ok(SaviaError::CampaignSuspended))
+        );
+
+        // Refunds open up even though the campaign is keep-it-all and running
+        assert_eq!(client.claim_refund(&donation_id), 980);
+        assert_eq!(token::Client::new(&env, &token_address(&env, &client)).balance(&donor), 980);
+    }
+
+    #[test]
+    fn test_adjust_trust_score_clamps_and_requires_admin() {
+        let env = Env::default();
+        let client = setup(&env);
+        let entity = Address::generate(&env);
+        let reason = String::from_str(&env, "Review");
+
+        assert_eq!(client.adjust_trust_score(&entity, &80, &reason), 100);
+        assert_eq!(client.adjust_trust_score(&entity, &-30, &reason), 70);
+        assert_eq!(client.adjust_trust_score(&entity, &-500, &reason), 0);
+        let score = client.get_trust_score(&entity).unwrap();
+        assert_eq!((score.score, score.adjustment), (0, -50));
+        let event = events_for(&env, topics::TRUST, topics::ADJUSTED).last().unwrap();
+        assert_eq!(
+            <(Address, i32, u32, String)>::try_from_val(&env, &event).unwrap(),
+            (entity.clone(), -70, 0, reason.clone())
+        );
+
+        let outsider = Address::generate(&env);
+        let args = (entity.clone(), 10i32, reason.clone()).into_val(&env);
+        assert!(client.mock_auths(&[mock_auth_for(&outsider, &client.address, "adjust_trust_score", args)])
+            .try_adjust_trust_score(&entity, &10, &reason)
+            .is_err());
+    }
+
+    #[test]
+    fn test_suspension_penalizes_beneficiary() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        // Creating the campaign started the beneficiary's record
+        assert_eq!(client.get_trust_score(&beneficiary).unwrap().campaigns_created, 1);
+
+        client.set_suspension_penalty(&35);
+        client.suspend_campaign(&campaign_id, &String::from_str(&env, "Fraud"));
+        assert_eq!(client.get_trust_score(&beneficiary).unwrap().score, 15);
+    }
+
+    #[test]
+    fn test_trust_score_decays_after_inactivity() {
+        let env = Env::default();
+        let client = setup(&env);
+        let entity = Address::generate(&env);
+        client.adjust_trust_score(&entity, &45, &String::from_str(&env, "Long-time donor"));
+        client.set_trust_decay(&TrustDecay { grace: 90 * 24 * 60 * 60, period: 30 * 24 * 60 * 60, points: 2 });
+
+        // Within the grace period nothing changes
+        env.ledger().with_mut(|li| li.timestamp += 90 * 24 * 60 * 60);
+        assert_eq!(client.get_trust_score(&entity).unwrap().score, 95);
+
+        // A year of inactivity is 12 whole periods
+        env.ledger().with_mut(|li| li.timestamp += 275 * 24 * 60 * 60);
+        assert_eq!(client.get_trust_score(&entity).unwrap().score, 71);
+        assert_eq!(events_for(&env, topics::TRUST, topics::DECAYED).len(), 0);
+
+        assert_eq!(client.refresh_trust_score(&entity), 71);
+        assert_eq!(client.refresh_trust_score(&entity), 71);
+        assert_eq!(events_for(&env, topics::TRUST, topics::DECAYED).len(), 1);
+        assert_eq!(client.get_trust_score(&entity).unwrap().score, 71);
+
+        // Decay stops at neutral
+        env.ledger().with_mut(|li| li.timestamp += 3 * 365 * 24 * 60 * 60);
+        assert_eq!(client.get_trust_score(&entity).unwrap().score, NEUTRAL_TRUST_SCORE);
+    }
+
+    #[test]
+    fn test_verification_level_adds_bonus_and_gates_creation() {
+        let env = Env::default();
+        let client = setup(&env);
+        let verified = Address::generate(&env);
+        let evidence = BytesN::from_array(&env, &[4; 32]);
+
+        assert_eq!(client.try_set_verification_level(&verified, &4, &evidence), Err(Ok(SaviaError::InvalidInput)));
+        client.set_verification_level(&verified, &2, &evidence);
+        let trust = client.get_trust_score(&verified).unwrap();
+        assert_eq!((trust.verification_level, trust.score), (2, 50 + 2 * VERIFICATION_LEVEL_BONUS));
+        assert_eq!(trust.evidence_hash, evidence);
+
+        // The bonus survives recalculation on donation
+        let unverified = funded_donor(&env, &client, 100);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &unverified, &100, &false, &false, &String::from_str(&env, ""));
+        let base = client.get_trust_score(&unverified).unwrap().score;
+        token::StellarAssetClient::new(&env, &token_address(&env, &client)).mint(&verified, &100);
+        client.donate(&campaign_id, &verified, &100, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_trust_score(&verified).unwrap().score, base + 2 * VERIFICATION_LEVEL_BONUS);
+
+        client.set_min_creator_level(&2);
+        assert_eq!(
+            client.try_create_campaign(
+                &unverified,
+                &String::from_str(&env, "Clinic"),
+                &String::from_str(&env, "New clinic"),
+                &1000,
+                &30,
+                &Category::Health,
+                &String::from_str(&env, "Tabasco"),
+                &false,
+                &FundingMode::KeepItAll,
+            ),
+            Err(Ok(SaviaError::VerificationRequired))
+        );
+        create_test_campaign(&env, &client, &verified);
+    }
+
+    #[test]
+    fn test_recurring_donation_runs_on_schedule() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+        let month = 30 * 24 * 60 * 60;
+
+        let recurring_id = client.create_recurring(&campaign_id, &donor, &100, &month, &3);
+        let token = token::Client::new(&env, &token_address(&env, &client));
+        assert_eq!(token.allowance(&donor, &client.address), 300);
+
+        client.execute_recurring(&recurring_id);
+        assert_eq!(client.try_execute_recurring(&recurring_id), Err(Ok(SaviaError::InvalidInput)));
+
+        env.ledger().with_mut(|li| li.timestamp += month);
+        let donation_id = client.execute_recurring(&recurring_id);
+        assert_eq!(client.get_donation(&donation_id).unwrap().donor, donor);
+        assert_eq!(token.balance(&donor), 800);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 196);
+
+        let recurring = client.get_recurring(&recurring_id).unwrap();
+        assert_eq!(recurring.remaining_executions, 1);
+        assert_eq!(recurring.next_execution, env.ledger().timestamp() + month);
+        let (schedules, _) = client.get_recurring_by_donor(&donor, &None, &10);
+        assert_eq!(schedules, soroban_sdk::vec![&env, recurring]);
+    }
+
+    #[test]
+    fn test_cancel_recurring_stops_runs() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+
+        let recurring_id = client.create_recurring(&campaign_id, &donor, &100, &60, &5);
+        client.execute_recurring(&recurring_id);
+        client.cancel_recurring(&recurring_id);
+
+        let token = token::Client::new(&env, &token_address(&env, &client));
+        assert_eq!(token.allowance(&donor, &client.address), 0);
+        env.ledger().with_mut(|li| li.timestamp += 60);
+        assert_eq!(client.try_execute_recurring(&recurring_id), Err(Ok(SaviaError::InvalidInput)));
+        assert_eq!(token.balance(&donor), 900);
+    }
+
+    #[test]
+    fn test_match_pool_matches_until_exhausted() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_platform_fee(&0);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let sponsor = funded_donor(&env, &client, 500);
+        let donor = funded_donor(&env, &client, 1000);
+
+        assert_eq!(client.try_create_match(&campaign_id, &sponsor, &10000, &0), Err(Ok(SaviaError::InvalidAmount)));
+        client.create_match(&campaign_id, &sponsor, &10000, &500);
+        assert_eq!(client.get_match(&campaign_id).unwrap().remaining, 500);
+
+        // Only the 500 left in the pool is matched
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 1500);
+        assert_eq!(client.get_match(&campaign_id).unwrap().remaining, 0);
+        assert_eq!(client.get_campaign_stats(&campaign_id).donation_count, 2);
+
+        let event = events_for(&env, topics::CAMPAIGN, topics::MATCHED).last().unwrap();
+        let (_, donation_id, matched, remaining) = <(BytesN<32>, BytesN<32>, i128, i128)>::try_from_val(&env, &event).unwrap();
+        assert_eq!((matched, remaining), (500, 0));
+        assert_eq!(client.get_donation(&donation_id).unwrap().donor, sponsor);
+    }
+
+    #[test]
+    fn test_donate_batch() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let first = create_test_campaign(&env, &client, &beneficiary);
+        let second = create_test_campaign(&env, &client, &beneficiary);
+        let third = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+
+        let batch = soroban_sdk::vec![&env, (first.clone(), 100i128), (second.clone(), 200), (third.clone(), 300)];
+        let ids = client.donate_batch(&donor, &batch, &false, &false);
+        assert_eq!(ids.len(), 3);
+        assert_eq!(client.get_donation(&ids.get(2).unwrap()).unwrap().campaign_id, third);
+        assert_eq!(client.get_campaign(&second).unwrap().current_amount, 196);
+        assert_eq!(token::Client::new(&env, &token_address(&env, &client)).balance(&donor), 400);
+
+        // One closed campaign fails the whole batch
+        client.close_campaign(&second);
+        let batch = soroban_sdk::vec![&env, (first.clone(), 100i128), (second.clone(), 100)];
+        assert_eq!(client.try_donate_batch(&donor, &batch, &false, &false), Err(Ok(SaviaError::CampaignInactive)));
+        assert_eq!(client.get_campaign(&first).unwrap().current_amount, 98);
+        assert_eq!(client.get_campaign_stats(&first).donation_count, 1);
+        assert_eq!(token::Client::new(&env, &token_address(&env, &client)).balance(&donor), 400);
+
+        let mut oversized = Vec::new(&env);
+        for _ in 0..=MAX_DONATION_BATCH {
+            oversized.push_back((first.clone(), 1i128));
+        }
+        assert_eq!(client.try_donate_batch(&donor, &oversized, &false, &false), Err(Ok(SaviaError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_donations_by_donor() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let first = create_test_campaign(&env, &client, &beneficiary);
+        let second = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+        let no_message = String::from_str(&env, "");
+
+        client.donate(&first, &donor, &100, &false, &false, &no_message);
+        client.donate(&second, &donor, &200, &false, &false, &no_message);
+        let hidden = client.donate(&second, &donor, &300, &true, &false, &no_message);
+
+        let public = client.get_donations_by_donor(&donor, &0, &10);
+        assert_eq!(public.len(), 2);
+        assert_eq!(public.get(0).unwrap().campaign_id, first);
+        assert_eq!(public.get(1).unwrap().campaign_id, second);
+
+        let own = client.get_donations_by_donor_as(&donor, &0, &10);
+        assert_eq!(own.len(), 3);
+        assert_eq!(own.get(2).unwrap().id, hidden);
+        assert_eq!(own.get(2).unwrap().donor, donor);
+        assert_eq!(client.get_donations_by_donor_as(&donor, &2, &10).len(), 1);
+
+        // The campaign's public listing does not reveal the anonymous donor
+        let (listed, _) = client.get_donations_by_campaign(&second, &None, &10);
+        assert_eq!(listed.get(1).unwrap().donor, client.address);
+
+        assert_eq!(client.get_donor_total(&donor), 98 + 196 + 294);
+    }
+
+    #[test]
+    fn test_leaderboard_ranks_public_donors() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_platform_fee(&0);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let no_message = String::from_str(&env, "");
+
+        let mut donors = std::vec::Vec::new();
+        for i in 0..LEADERBOARD_SIZE {
+            let donor = funded_donor(&env, &client, 1000);
+            client.donate(&campaign_id, &donor, &(100 + i as i128), &false, &false, &no_message);
+            donors.push(donor);
+        }
+        let board = client.get_leaderboard(&campaign_id);
+        assert_eq!(board.len(), LEADERBOARD_SIZE);
+        assert_eq!(board.get(0).unwrap(), (donors[9].clone(), 109));
+        assert_eq!(board.get(9).unwrap(), (donors[0].clone(), 100));
+
+        // A newcomer tying the last place does not displace it
+        let tied = funded_donor(&env, &client, 1000);
+        client.donate(&campaign_id, &tied, &100, &false, &false, &no_message);
+        assert!(!client.get_leaderboard(&campaign_id).iter().any(|(address, _)| address == tied));
+
+        // A newcomer above it does, and a donor on the board moves up
+        let newcomer = funded_donor(&env, &client, 1000);
+        client.donate(&campaign_id, &newcomer, &105, &false, &false, &no_message);
+        client.donate(&campaign_id, &donors[1], &50, &false, &false, &no_message);
+        let board = client.get_leaderboard(&campaign_id);
+        assert_eq!(board.len(), LEADERBOARD_SIZE);
+        assert!(!board.iter().any(|(address, _)| address == donors[0]));
+        assert_eq!(board.get(0).unwrap(), (donors[1].clone(), 151));
+        let newcomer_rank = board.iter().position(|(address, _)| address == newcomer).unwrap();
+        assert_eq!(board.get(newcomer_rank as u32 - 1).unwrap(), (donors[5].clone(), 105));
+
+        // Anonymous gifts never show up or count
+        let anonymous = funded_donor(&env, &client, 1000);
+        client.donate(&campaign_id, &anonymous, &900, &true, &false, &no_message);
+        assert!(!client.get_leaderboard(&campaign_id).iter().any(|(address, _)| address == anonymous));
+        client.donate(&campaign_id, &donors[2], &500, &true, &false, &no_message);
+        assert!(client.get_leaderboard(&campaign_id).contains((donors[2].clone(), 102)));
+    }
+
+    #[test]
+    fn test_funded_event_fires_once() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_platform_fee(&0);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 20000);
+        let no_message = String::from_str(&env, "");
+
+        client.donate(&campaign_id, &donor, &9999, &false, &false, &no_message);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().goal_reached_at, None);
+        client.donate(&campaign_id, &donor, &1, &false, &false, &no_message);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().goal_reached_at, Some(env.ledger().timestamp()));
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::FUNDED).len(), 1);
+
+        client.donate(&campaign_id, &donor, &500, &false, &false, &no_message);
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::FUNDED).len(), 1);
+        assert!(client.get_campaign(&campaign_id).unwrap().active);
+    }
+
+    #[test]
+    fn test_close_on_goal_stops_donations() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_platform_fee(&0);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 20000);
+        let no_message = String::from_str(&env, "");
+
+        client.set_close_on_goal(&campaign_id, &true);
+        client.donate(&campaign_id, &donor, &10000, &false, &false, &no_message);
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert!(!campaign.active);
+        assert!(campaign.goal_reached);
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &1, &false, &false, &no_message),
+            Err(Ok(SaviaError::CampaignInactive))
+        );
+    }
+
+    #[test]
+    fn test_update_campaign_before_first_donation() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+
+        let title = String::from_str(&env, "Fixed title");
+        let uri = String::from_str(&env, "ipfs://campaign");
+        client.update_campaign(&campaign_id, &Some(title.clone()), &None, &None, &Some(uri.clone()));
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert_eq!(campaign.title, title);
+        assert_eq!(campaign.metadata_uri, Some(uri));
+        assert_eq!(campaign.location, String::from_str(&env, "Test City"));
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::UPDATED).len(), 1);
+
+        let empty = Some(String::from_str(&env, ""));
+        assert_eq!(
+            client.try_update_campaign(&campaign_id, &None, &empty, &None, &None),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+
+        let donor = funded_donor(&env, &client, 100);
+        client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(
+            client.try_update_campaign(&campaign_id, &Some(title), &None, &None, &None),
+            Err(Ok(SaviaError::CampaignLocked))
+        );
+    }
+
+    #[test]
+    fn test_campaign_updates_round_trip() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+
+        let first = String::from_str(&env, "Week 1");
+        let second = String::from_str(&env, "Week 2");
+        let uri = String::from_str(&env, "ipfs://report");
+        assert_eq!(client.post_update(&campaign_id, &first, &uri), 0);
+        env.ledger().with_mut(|li| li.timestamp += 7 * 24 * 60 * 60);
+        assert_eq!(client.post_update(&campaign_id, &second, &uri), 1);
+
+        let updates = client.get_updates(&campaign_id, &0, &10);
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates.get(0).unwrap().title, first);
+        assert_eq!(updates.get(1).unwrap().title, second);
+        assert!(updates.get(1).unwrap().posted_at > updates.get(0).unwrap().posted_at);
+        assert_eq!(client.get_updates(&campaign_id, &1, &10).len(), 1);
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::POSTED).len(), 2);
+    }
+
+    #[test]
+    fn test_post_update_requires_beneficiary() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let title = String::from_str(&env, "Fake report");
+        let uri = String::from_str(&env, "ipfs://fake");
+
+        let intruder = Address::generate(&env);
+        let args = (campaign_id.clone(), title.clone(), uri.clone()).into_val(&env);
+        assert!(client.mock_auths(&[mock_auth_for(&intruder, &client.address, "post_update", args)])
+            .try_post_update(&campaign_id, &title, &uri)
+            .is_err());
+        assert_eq!(client.get_updates(&campaign_id, &0, &10).len(), 0);
+    }
+
+    #[test]
+    fn test_enumerate_nfts_by_index() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        assert_eq!(client.total_nft_supply(), 0);
+
+        let gifts = [(500i128, BadgeTier::Bronze), (2000, BadgeTier::Silver), (7000, BadgeTier::Gold)];
+        let mut donors = Vec::new(&env);
+        for (amount, _) in gifts {
+            let donor = funded_donor(&env, &client, amount);
+            client.donate(&campaign_id, &donor, &amount, &false, &true, &String::from_str(&env, ""));
+            donors.push_back(donor);
+        }
+
+        assert_eq!(client.total_nft_supply(), 3);
+        for (index, (amount, tier)) in gifts.into_iter().enumerate() {
+            let badge = client.get_nft_by_index(&(index as u64)).unwrap();
+            assert_eq!(badge.owner, donors.get(index as u32).unwrap());
+            assert_eq!(badge.tier, tier);
+            assert_eq!(badge.badge_type, String::from_str(&env, tier.name()));
+            assert_eq!(badge.donation_amount, amount - amount * 200 / 10000);
+            assert_eq!(badge.serial, index as u64 + 1);
+        }
+        assert!(client.get_nft_by_index(&3).is_none());
+    }
+
+    #[test]
+    fn test_burn_nft() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 2000);
+        client.donate(&campaign_id, &donor, &1000, &false, &true, &String::from_str(&env, ""));
+        let badge = client.get_nfts_by_owner_at(&donor, &0, &10).get(0).unwrap();
+        assert_eq!(client.get_platform_stats().total_nfts, 1);
+
+        let stranger = Address::generate(&env);
+        let args = (badge.id.clone(),).into_val(&env);
+        assert!(client.mock_auths(&[mock_auth_for(&stranger, &client.address, "burn_nft", args)])
+            .try_burn_nft(&badge.id)
+            .is_err());
+
+        client.burn_nft(&badge.id);
+        assert!(client.get_nft(&badge.id).is_none());
+        assert!(client.get_nft_by_index(&0).is_none());
+        assert_eq!(client.get_nft_count(&donor), 0);
+        assert_eq!(client.get_platform_stats().total_nfts, 0);
+        assert_eq!(events_for(&env, topics::NFT, topics::BURNED).len(), 1);
+        assert_eq!(client.try_burn_nft(&badge.id), Err(Ok(SaviaError::NFTNotFound)));
+
+        // The next donation mints a fresh badge, which the admin can remove
+        client.donate(&campaign_id, &donor, &1000, &false, &true, &String::from_str(&env, ""));
+        let fresh = client.get_nfts_by_owner_at(&donor, &0, &10).get(0).unwrap();
+        assert_ne!(fresh.id, badge.id);
+        client.admin_burn_nft(&fresh.id);
+        assert_eq!(client.get_nft_count(&donor), 0);
+    }
+
+    #[test]
+    fn test_refund_burns_badge() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_all_or_nothing_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 5000);
+        let donation_id = client.donate(&campaign_id, &donor, &5000, &false, &true, &String::from_str(&env, ""));
+        assert_eq!(client.get_nft_count(&donor), 1);
+
+        env.ledger().with_mut(|l| l.timestamp = 31 * 24 * 60 * 60);
+        client.claim_refund(&donation_id);
+        assert_eq!(client.get_nft_count(&donor), 0);
+        assert_eq!(client.get_platform_stats().total_nfts, 0);
+    }
+
+    #[test]
+    fn test_gift_donation_badge_goes_to_recipient() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 2000);
+        let honoree = Address::generate(&env);
+
+        let donation_id = client.donate_as_gift(&campaign_id, &donor, &honoree, &2000, &true, &String::from_str(&env, "For Ana"));
+
+        let badge = client.get_nfts_by_owner_at(&honoree, &0, &10).get(0).unwrap();
+        assert_eq!(badge.owner, honoree);
+        assert_eq!(badge.tier, BadgeTier::Silver);
+        assert_eq!(client.get_nft_count(&donor), 0);
+
+        assert_eq!(client.get_trust_score(&donor).unwrap().donation_count, 1);
+        assert!(client.get_trust_score(&honoree).is_none());
+
+        // Anonymous: the donor is hidden, the honoree is not
+        let public = client.get_donation(&donation_id).unwrap();
+        assert_eq!(public.donor, client.address);
+        assert_eq!(public.badge_recipient, Some(honoree));
+
+        assert_eq!(
+            client.try_donate_as_gift(&campaign_id, &donor, &donor, &100, &false, &String::from_str(&env, "")),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+    }
+
+    #[test]
+    fn test_campaign_fee_override() {
+        let env = Env::default();
+        let client = setup(&env);
+        let standard = create_test_campaign(&env, &client, &Address::generate(&env));
+        let relief = create_test_campaign(&env, &client, &Address::generate(&env));
+        client.set_campaign_fee(&relief, &0);
+        assert_eq!(client.get_campaign_fee(&relief), Some(0));
+        assert_eq!(client.preview_donation(&relief, &1000).net, 1000);
+
+        let donor = funded_donor(&env, &client, 2000);
+        let standard_donation = client.donate(&standard, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let relief_donation = client.donate(&relief, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_donation(&standard_donation).unwrap().amount, 980);
+        assert_eq!(client.get_donation(&relief_donation).unwrap().amount, 1000);
+
+        client.clear_campaign_fee(&relief);
+        assert_eq!(client.preview_donation(&relief, &1000).net, 980);
+        assert_eq!(client.try_set_campaign_fee(&relief, &1001), Err(Ok(SaviaError::InvalidFee)));
+    }
+
+    #[test]
+    fn test_verified_fee_waiver() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        client.verify_campaign(&campaign_id, &90);
+        assert_eq!(client.preview_donation(&campaign_id, &1000).fee, 20);
+
+        client.set_verified_fee_waiver(&true);
+        assert_eq!(client.preview_donation(&campaign_id, &1000).fee, 0);
+        let donor = Address::generate(&env);
+        assert_eq!(client.quote_donation_for(&campaign_id, &donor, &1000).net, 1000);
+
+        // Below the trust bar the platform fee applies
+        client.verify_campaign(&campaign_id, &89);
+        assert_eq!(client.preview_donation(&campaign_id, &1000).fee, 20);
+    }
+
+    #[test]
+    fn test_fee_split_between_recipients() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let token = token::Client::new(&env, &token_address(&env, &client));
+        let operations = Address::generate(&env);
+        let community = Address::generate(&env);
+        let reserve = Address::generate(&env);
+        client.set_fee_recipients(&soroban_sdk::vec![
+            &env,
+            (operations.clone(), 5000u32),
+            (community.clone(), 3000u32),
+            (reserve.clone(), 2000u32),
+        ]);
+
+        // 2% of 1550 is 31, which does not divide evenly
+        let donor = funded_donor(&env, &client, 1550);
+        client.donate(&campaign_id, &donor, &1550, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_accrued_fees(), 31);
+        assert_eq!(client.get_fee_share(&operations), 16);
+        assert_eq!(client.get_fee_share(&community), 9);
+        assert_eq!(client.get_fee_share(&reserve), 6);
+
+        // Recipients' shares are not the admin's to withdraw
+        assert_eq!(client.try_withdraw_fees(&operations, &1), Err(Ok(SaviaError::InsufficientFunds)));
+
+        assert_eq!(client.claim_fee_share(&community), 9);
+        assert_eq!(token.balance(&community), 9);
+        assert_eq!(client.get_accrued_fees(), 22);
+        assert_eq!(client.try_claim_fee_share(&community), Err(Ok(SaviaError::InsufficientFunds)));
+
+        let uneven = soroban_sdk::vec![&env, (operations.clone(), 5000u32), (community, 4000u32)];
+        assert_eq!(client.try_set_fee_recipients(&uneven), Err(Ok(SaviaError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_referral_stats_accumulate_across_campaigns() {
+        let env = Env::default();
+        let client = setup(&env);
+        let first = create_test_campaign(&env, &client, &Address::generate(&env));
+        let second = create_test_campaign(&env, &client, &Address::generate(&env));
+        let referrer = Address::generate(&env);
+        let no_message = String::from_str(&env, "");
+
+        let donor = funded_donor(&env, &client, 1000);
+        let donation_id = client.donate_with_referral(&first, &donor, &1000, &false, &false, &no_message, &referrer);
+        assert_eq!(client.get_donation(&donation_id).unwrap().referrer, Some(referrer.clone()));
+        for campaign_id in [&first, &second, &second] {
+            let donor = funded_donor(&env, &client, 500);
+            client.donate_with_referral(campaign_id, &donor, &500, &true, &false, &no_message, &referrer);
+        }
+
+        let stats = client.get_referral_stats(&referrer);
+        assert_eq!(stats.referred_count, 4);
+        assert_eq!(stats.referred_volume, 980 + 3 * 490);
+        assert_eq!(stats.trust_bonus, 4);
+        assert_eq!(client.get_trust_score(&referrer).unwrap().score, NEUTRAL_TRUST_SCORE + 4);
+        assert_eq!(events_for(&env, topics::REFERRAL, topics::CREDITED).len(), 4);
+    }
+
+    #[test]
+    fn test_referral_bonus_capped_and_self_referral_rejected() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let referrer = Address::generate(&env);
+        let no_message = String::from_str(&env, "");
+
+        let donor = funded_donor(&env, &client, 100);
+        assert_eq!(
+            client.try_donate_with_referral(&campaign_id, &donor, &100, &false, &false, &no_message, &donor),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+        assert_eq!(client.get_referral_stats(&donor), ReferralStats::default());
+
+        for _ in 0..=MAX_REFERRAL_TRUST_BONUS {
+            let donor = funded_donor(&env, &client, 100);
+            client.donate_with_referral(&campaign_id, &donor, &100, &false, &false, &no_message, &referrer);
+        }
+        let stats = client.get_referral_stats(&referrer);
+        assert_eq!(stats.referred_count, MAX_REFERRAL_TRUST_BONUS + 1);
+        assert_eq!(stats.trust_bonus, MAX_REFERRAL_TRUST_BONUS);
+        assert_eq!(client.get_trust_score(&referrer).unwrap().score, NEUTRAL_TRUST_SCORE + MAX_REFERRAL_TRUST_BONUS);
+    }
+
+    #[test]
+    fn test_donor_whitelist() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let employee = funded_donor(&env, &client, 1000);
+        let colleague = funded_donor(&env, &client, 1000);
+        let outsider = funded_donor(&env, &client, 1000);
+        let no_message = String::from_str(&env, "");
+
+        client.set_donor_whitelist(&campaign_id, &soroban_sdk::vec![&env, employee.clone()], &true);
+        client.donate(&campaign_id, &employee, &100, &false, &false, &no_message);
+        assert_eq!(
+            client.try_donate(&campaign_id, &outsider, &100, &false, &false, &no_message),
+            Err(Ok(SaviaError::NotWhitelisted))
+        );
+
+        client.add_to_whitelist(&campaign_id, &soroban_sdk::vec![&env, colleague.clone()]);
+        client.donate(&campaign_id, &colleague, &100, &false, &false, &no_message);
+        client.remove_from_whitelist(&campaign_id, &soroban_sdk::vec![&env, employee.clone()]);
+        assert!(!client.is_donor_allowed(&campaign_id, &employee));
+
+        // Disabling the whitelist opens the campaign to everyone again
+        client.set_donor_whitelist(&campaign_id, &Vec::new(&env), &false);
+        client.donate(&campaign_id, &outsider, &100, &false, &false, &no_message);
+        assert!(client.is_donor_allowed(&campaign_id, &employee));
+    }
+
+    #[test]
+    fn test_blacklist_blocks_and_restores_access() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 2000);
+        let no_message = String::from_str(&env, "");
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &no_message);
+        let approved = client.create_disbursement(&campaign_id, &beneficiary, &100, &0);
+        client.approve_disbursement(&approved, &admin_of(&env, &client));
+
+        client.set_blacklist(&beneficiary, &true);
+        assert!(client.is_blacklisted(&beneficiary));
+        assert_eq!(
+            client.try_create_campaign(
+                &beneficiary,
+                &String::from_str(&env, "Another"),
+                &String::from_str(&env, "Blocked"),
+                &10000,
+                &30,
+                &Category::Health,
+                &String::from_str(&env, "Test City"),
+                &false,
+                &FundingMode::KeepItAll,
+            ),
+            Err(Ok(SaviaError::AddressBlocked))
+        );
+        assert_eq!(client.try_create_disbursement(&campaign_id, &beneficiary, &100, &0), Err(Ok(SaviaError::AddressBlocked)));
+        assert_eq!(client.try_execute_disbursement(&approved, &0), Err(Ok(SaviaError::AddressBlocked)));
+
+        client.set_blacklist(&donor, &true);
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &100, &false, &false, &no_message),
+            Err(Ok(SaviaError::AddressBlocked))
+        );
+        assert_eq!(events_for(&env, topics::ADMIN, topics::BLACKLIST).len(), 2);
+
+        client.set_blacklist(&beneficiary, &false);
+        client.set_blacklist(&donor, &false);
+        client.donate(&campaign_id, &donor, &100, &false, &false, &no_message);
+        client.execute_disbursement(&approved, &0);
+        assert!(client.try_create_disbursement(&campaign_id, &beneficiary, &100, &0).is_ok());
+    }
+
+    #[test]
+    fn test_blacklist_blocks_withdraw_remaining() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        client.close_campaign(&campaign_id);
+
+        let wallet = Address::generate(&env);
+        client.set_blacklist(&beneficiary, &true);
+        assert_eq!(client.try_withdraw_remaining(&campaign_id, &wallet), Err(Ok(SaviaError::AddressBlocked)));
+        client.set_blacklist(&beneficiary, &false);
+
+        // Nor may the remainder be sent to a blacklisted wallet
+        client.set_blacklist(&wallet, &true);
+        assert_eq!(client.try_withdraw_remaining(&campaign_id, &wallet), Err(Ok(SaviaError::AddressBlocked)));
+        client.set_blacklist(&wallet, &false);
+
+        assert_eq!(client.withdraw_remaining(&campaign_id, &wallet), 980);
+    }
+
+    #[test]
+    fn test_multisig_disbursement_approval() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let signers = [Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+        client.set_approvers(&Vec::from_array(&env, signers.clone()), &2);
+
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &500, &0);
+        assert_eq!(
+            client.try_approve_disbursement(&disbursement_id, &admin_of(&env, &client)),
+            Err(Ok(SaviaError::Unauthorized))
+        );
+
+        // A repeated signature counts once
+        client.approve_disbursement(&disbursement_id, &signers[0]);
+        client.approve_disbursement(&disbursement_id, &signers[0]);
+        assert_eq!(client.get_disbursement(&disbursement_id).unwrap().status, DisbursementStatus::Pending);
+        client.approve_disbursement(&disbursement_id, &signers[1]);
+        assert_eq!(client.get_disbursement(&disbursement_id).unwrap().status, DisbursementStatus::Approved);
+
+        // A removed approver's earlier signature no longer counts
+        let next = client.create_disbursement(&campaign_id, &beneficiary, &100, &0);
+        client.approve_disbursement(&next, &signers[0]);
+        client.set_approvers(&Vec::from_array(&env, [signers[1].clone(), signers[2].clone()]), &2);
+        client.approve_disbursement(&next, &signers[1]);
+        assert_eq!(client.get_disbursement(&next).unwrap().status, DisbursementStatus::Pending);
+        client.approve_disbursement(&next, &signers[2]);
+        assert_eq!(client.get_disbursement(&next).unwrap().status, DisbursementStatus::Approved);
+
+        assert_eq!(client.try_set_approvers(&Vec::from_array(&env, signers), &4), Err(Ok(SaviaError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_disbursement_expiry() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let milestone = |title: &str, percentage: u32| Milestone { title: String::from_str(&env, title), percentage };
+        client.set_milestones(&campaign_id, &soroban_sdk::vec![&env, milestone("Supplies", 10), milestone("Rest", 90)]);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 2000), &2000, &false, &false, &String::from_str(&env, ""));
+        client.set_disbursement_validity(&(7 * 24 * 60 * 60));
+        let admin = admin_of(&env, &client);
+
+        // Executing on the last second of validity works
+        let on_time = client.create_disbursement(&campaign_id, &beneficiary, &300, &0);
+        client.approve_disbursement(&on_time, &admin);
+        let expires_at = client.get_disbursement(&on_time).unwrap().expires_at;
+        assert_eq!(client.try_expire_disbursement(&on_time), Err(Ok(SaviaError::InvalidInput)));
+        env.ledger().with_mut(|l| l.timestamp = expires_at);
+        client.execute_disbursement(&on_time, &0);
+
+        // The Supplies milestone holds 1000, of which 700 remain
+        let late = client.create_disbursement(&campaign_id, &beneficiary, &700, &0);
+        client.approve_disbursement(&late, &admin);
+        let expires_at = client.get_disbursement(&late).unwrap().expires_at;
+        env.ledger().with_mut(|l| l.timestamp = expires_at + 1);
+        assert_eq!(client.try_execute_disbursement(&late, &0), Err(Ok(SaviaError::DisbursementExpired)));
+        assert_eq!(
+            client.try_create_disbursement(&campaign_id, &beneficiary, &700, &0),
+            Err(Ok(SaviaError::MilestoneAllocationExceeded))
+        );
+
+        client.expire_disbursement(&late);
+        assert_eq!(client.get_disbursement(&late).unwrap().status, DisbursementStatus::Expired);
+        assert_eq!(events_for(&env, topics::DISBURSEMENT, topics::EXPIRED).len(), 1);
+        assert!(client.try_create_disbursement(&campaign_id, &beneficiary, &700, &0).is_ok());
+        assert_eq!(client.try_expire_disbursement(&late), Err(Ok(SaviaError::DisbursementNotPending)));
+        assert_eq!(client.try_execute_disbursement(&late, &0), Err(Ok(SaviaError::DisbursementExpired)));
+    }
+
+    #[test]
+    fn test_batch_approve_disbursements() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        let first = client.create_disbursement(&campaign_id, &beneficiary, &100, &0);
+        let second = client.create_disbursement(&campaign_id, &beneficiary, &200, &0);
+        let bogus = BytesN::from_array(&env, &[7; 32]);
+
+        let ids = soroban_sdk::vec![&env, first.clone(), bogus.clone(), second.clone()];
+        let results = client.approve_disbursements(&ids, &admin_of(&env, &client));
+        assert_eq!(results, soroban_sdk::vec![&env, (first.clone(), true), (bogus, false), (second.clone(), true)]);
+        assert_eq!(client.get_disbursement(&first).unwrap().status, DisbursementStatus::Approved);
+        assert_eq!(client.get_disbursement(&second).unwrap().status, DisbursementStatus::Approved);
+        assert_eq!(events_for(&env, topics::DISBURSEMENT, topics::APPROVED).len(), 2);
+
+        let mut oversized = Vec::new(&env);
+        for _ in 0..=MAX_APPROVAL_BATCH {
+            oversized.push_back(first.clone());
+        }
+        assert_eq!(
+            client.try_approve_disbursements(&oversized, &admin_of(&env, &client)),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+    }
+
+    #[test]
+    fn test_stats_by_category_and_period() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 3000);
+        let no_message = String::from_str(&env, "");
+
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &no_message);
+        env.ledger().with_mut(|l| l.timestamp += STATS_PERIOD);
+        client.donate(&campaign_id, &donor, &2000, &false, &false, &no_message);
+
+        let first = client.get_period_stats(&0);
+        assert_eq!((first.total_campaigns, first.total_donations, first.total_raised), (1, 1, 980));
+        let second = client.get_period_stats(&1);
+        assert_eq!((second.total_campaigns, second.total_donations, second.total_raised), (0, 1, 1960));
+        assert_eq!(second.total_fees_collected, 40);
+
+        let health = client.get_category_stats(&Category::Health);
+        assert_eq!((health.total_campaigns, health.active_campaigns, health.total_raised), (1, 1, 2940));
+        assert_eq!(client.get_category_stats(&Category::Education), PlatformStats::default());
+        assert_eq!(client.get_platform_stats().total_raised, 2940);
+    }
+
+    #[test]
+    fn test_donation_streak_tracking() {
+        let env = Env::default();
+        let client = setup(&env);
+        let donor = funded_donor(&env, &client, 5000);
+        let no_message = String::from_str(&env, "");
+        // Test campaigns run 30 days, so each month donates to a fresh one
+        let donate = || {
+            let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+            client.donate(&campaign_id, &donor, &500, &false, &false, &no_message);
+        };
+
+        for _ in 0..3 {
+            donate();
+            env.ledger().with_mut(|l| l.timestamp += STREAK_MONTH);
+        }
+        let trust = client.get_trust_score(&donor).unwrap();
+        assert_eq!((trust.current_streak_months, trust.longest_streak), (3, 3));
+
+        // A second donation within the same month doesn't extend the streak
+        env.ledger().with_mut(|l| l.timestamp -= STREAK_MONTH - 1);
+        donate();
+        assert_eq!(client.get_trust_score(&donor).unwrap().current_streak_months, 3);
+
+        env.ledger().with_mut(|l| l.timestamp += 4 * STREAK_MONTH);
+        assert_eq!(client.get_trust_score(&donor).unwrap().current_streak_months, 0);
+        donate();
+        let trust = client.get_trust_score(&donor).unwrap();
+        assert_eq!((trust.current_streak_months, trust.longest_streak), (1, 3));
+    }
+
+    #[test]
+    fn test_score_breakdown_math() {
+        let env = Env::default();
+        let mut trust = imported_score(&env, &Address::generate(&env), 0);
+        trust.donation_count = 8;
+        trust.total_donated = 50_000;
+        trust.current_streak_months = 3;
+        trust.verification_level = 2;
+        trust.adjustment = -4;
+        trust.decay_applied = 5;
+
+        let breakdown = SaviaContract::score_breakdown(&trust);
+        assert_eq!((breakdown.base, breakdown.donation_points, breakdown.amount_points), (50, 2, 10));
+        assert_eq!((breakdown.streak_bonus, breakdown.verification_bonus), (1, 10));
+        assert_eq!(breakdown.total(), 64);
+
+        trust.adjustment = 60;
+        assert_eq!(SaviaContract::score_breakdown(&trust).total(), 100);
+    }
+
+    #[test]
+    fn test_explain_trust_score_sums_to_score() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 20_000);
+        let no_message = String::from_str(&env, "");
+        assert_eq!(client.explain_trust_score(&donor), None);
+
+        client.donate(&campaign_id, &donor, &9000, &false, &false, &no_message);
+        set_verification_level(&env, &client, &donor, 1);
+        client.adjust_trust_score(&donor, &7, &String::from_str(&env, "community work"));
+
+        let breakdown = client.explain_trust_score(&donor).unwrap();
+        assert_eq!(breakdown.verification_bonus, VERIFICATION_LEVEL_BONUS);
+        assert_eq!(breakdown.adjustment, 7);
+        assert_eq!(breakdown.score, client.get_trust_score(&donor).unwrap().score);
+        assert_eq!(breakdown.total(), breakdown.score);
+
+        // Decay shows up as its own component
+        let before = breakdown.score;
+        let decay = client.get_trust_decay();
+        env.ledger().with_mut(|l| l.timestamp += decay.grace + decay.period);
+        let breakdown = client.explain_trust_score(&donor).unwrap();
+        assert!(breakdown.decay > 0);
+        assert_eq!(breakdown.decay, before - breakdown.score);
+        assert_eq!(breakdown.total(), breakdown.score);
+        assert_eq!(breakdown.score, client.get_trust_score(&donor).unwrap().score);
+    }
+
+    #[test]
+    fn test_beneficiary_score_reflects_campaign_outcomes() {
+        let env = Env::default();
+        let client = setup(&env);
+        let donor = funded_donor(&env, &client, 50_000);
+        let no_message = String::from_str(&env, "");
+        let mixed = Address::generate(&env);
+        let reliable = Address::generate(&env);
+
+        let mut campaigns = soroban_sdk::Vec::new(&env);
+        for (beneficiary, amount) in [(&mixed, 10_300), (&mixed, 1000), (&reliable, 10_300), (&reliable, 10_300)] {
+            let campaign_id = client.create_campaign(
+                beneficiary,
+                &String::from_str(&env, "Outcome"),
+                &String::from_str(&env, "A campaign"),
+                &10000,
+                &30,
+                &Category::Health,
+                &String::from_str(&env, "Test City"),
+                &false,
+                &FundingMode::KeepItAll,
+            );
+            client.donate(&campaign_id, &donor, &amount, &false, &false, &no_message);
+            campaigns.push_back(campaign_id);
+        }
+
+        env.ledger().with_mut(|l| l.timestamp += 31 * 24 * 60 * 60);
+        for campaign_id in campaigns.iter() {
+            client.finalize_campaign(&campaign_id);
+        }
+
+        let mixed_score = client.get_trust_score(&mixed).unwrap();
+        assert_eq!((mixed_score.campaigns_completed, mixed_score.campaigns_failed), (1, 1));
+        let reliable_score = client.get_trust_score(&reliable).unwrap();
+        assert_eq!((reliable_score.campaigns_completed, reliable_score.campaigns_failed), (2, 0));
+        assert!(reliable_score.score > mixed_score.score);
+        assert_eq!(client.explain_trust_score(&reliable).unwrap().campaign_points, 2 * CAMPAIGN_COMPLETED_POINTS);
+    }
+
+    /// Assert a call was rejected by the host for missing authorization
+    /// rather than by one of the contract's own checks
+    #[track_caller]
+    fn assert_unauthorized<T: core::fmt::Debug, E: core::fmt::Debug>(result: Result<T, Result<E, InvokeError>>) {
+        assert!(matches!(result, Err(Err(InvokeError::Abort))), "expected an auth failure, got {:?}", result);
+    }
+
+    /// `assert_unauthorized` for entrypoints that don't return a `Result`,
+    /// where host failures surface as a non-contract `Error`
+    #[track_caller]
+    fn assert_unauthorized_query<T: core::fmt::Debug, C: core::fmt::Debug>(
+        result: Result<Result<T, C>, Result<soroban_sdk::Error, InvokeError>>,
+    ) {
+        assert!(
+            matches!(&result, Err(Ok(error)) if !error.is_type(soroban_sdk::xdr::ScErrorType::Contract)),
+            "expected an auth failure, got {:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn test_admin_entrypoints_require_admin() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 2000);
+        client.donate(&campaign_id, &donor, &2000, &false, &true, &String::from_str(&env, ""));
+        let nft_id = client.get_nfts_by_owner(&donor, &None, &1).0.get(0).unwrap().id;
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &100, &0);
+
+        let unsigned = client.mock_auths(&[]);
+        let someone = Address::generate(&env);
+        let text = String::from_str(&env, "x");
+        let hash = BytesN::from_array(&env, &[1; 32]);
+
+        assert_unauthorized(unsigned.try_upgrade(&hash));
+        assert_unauthorized(unsigned.try_pause());
+        assert_unauthorized(unsigned.try_unpause());
+        assert_unauthorized(unsigned.try_set_blacklist(&someone, &true));
+        assert_unauthorized(unsigned.try_transfer_admin(&someone));
+        assert_unauthorized(unsigned.try_cancel_admin_transfer());
+        assert_unauthorized(unsigned.try_migrate());
+        assert_unauthorized(unsigned.try_migrate_amounts(&soroban_sdk::Vec::new(&env)));
+        assert_unauthorized(unsigned.try_suspend_campaign(&campaign_id, &text));
+        assert_unauthorized(unsigned.try_register_hook(&campaign_id, &someone));
+        assert_unauthorized(unsigned.try_verify_campaign(&campaign_id, &80));
+        assert_unauthorized(unsigned.try_set_risk_weights(&client.get_risk_weights()));
+        assert_unauthorized(unsigned.try_set_risk_threshold(&50));
+        assert_unauthorized(unsigned.try_set_platform_fee(&100));
+        assert_unauthorized(unsigned.try_set_campaign_fee(&campaign_id, &100));
+        assert_unauthorized(unsigned.try_clear_campaign_fee(&campaign_id));
+        assert_unauthorized(unsigned.try_set_verified_fee_waiver(&true));
+        assert_unauthorized(unsigned.try_schedule_fee_holiday(&100, &200, &0));
+        assert_unauthorized(unsigned.try_set_event_verbosity(&EventVerbosity::Minimal));
+        assert_unauthorized(unsigned.try_withdraw_fees(&someone, &1));
+        assert_unauthorized(unsigned.try_set_fee_recipients(&soroban_sdk::vec![&env, (someone.clone(), 10000)]));
+        assert_unauthorized(unsigned.try_set_kyc_disclosure_threshold(&Some(100)));
+        assert_unauthorized(unsigned.try_adjust_trust_score(&someone, &5, &text));
+        assert_unauthorized(unsigned.try_set_verification_level(&someone, &1, &hash));
+        assert_unauthorized(unsigned.try_set_min_creator_level(&1));
+        assert_unauthorized(unsigned.try_set_suspension_penalty(&10));
+        assert_unauthorized(unsigned.try_set_trust_decay(&client.get_trust_decay()));
+        assert_unauthorized(unsigned.try_import_trust_scores(&soroban_sdk::Vec::new(&env)));
+        assert_unauthorized(unsigned.try_set_bootstrap_window(&60));
+        assert_unauthorized(unsigned.try_set_endgame_window(&60));
+        assert_unauthorized(unsigned.try_set_disbursement_validity(&60));
+        assert_unauthorized(unsigned.try_set_ttl_config(&100, &200));
+        assert_unauthorized(unsigned.try_add_category(&Category::Education));
+        assert_unauthorized(unsigned.try_remove_category(&Category::Health));
+        assert_unauthorized(unsigned.try_set_category_label(&Category::Health, &Symbol::new(&env, "es"), &text));
+        assert_unauthorized(unsigned.try_admin_burn_nft(&nft_id));
+        assert_unauthorized(unsigned.try_set_badge_transferable(&text, &true));
+        assert_unauthorized(unsigned.try_set_badge_metadata(&text, &text));
+        assert_unauthorized(unsigned.try_approve_disbursement(&disbursement_id, &admin_of(&env, &client)));
+        assert_unauthorized(unsigned.try_set_approvers(&soroban_sdk::vec![&env, someone.clone()], &1));
+        assert_unauthorized(unsigned.try_reject_disbursement(&disbursement_id, &text));
+        assert_unauthorized(unsigned.try_allow_token(&someone, &true));
+        assert_unauthorized(unsigned.try_withdraw_token_fees(&someone, &someone, &1));
+        assert_unauthorized(unsigned.try_set_disbursement_limits(&soroban_sdk::vec![&env, (0, 500)]));
+
+        client.transfer_admin(&someone);
+        assert_unauthorized(unsigned.try_accept_admin());
+    }
+
+    #[test]
+    fn test_beneficiary_entrypoints_require_beneficiary() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 2000), &2000, &false, &false, &String::from_str(&env, ""));
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &100, &0);
+        client.register_hook(&campaign_id, &Address::generate(&env));
+
+        let unsigned = client.mock_auths(&[]);
+        let someone = Address::generate(&env);
+        let text = String::from_str(&env, "x");
+        let donors = soroban_sdk::vec![&env, someone.clone()];
+
+        assert_unauthorized(unsigned.try_create_campaign(
+            &someone,
+            &String::from_str(&env, "Not Mine"),
+            &text,
+            &1000,
+            &30,
+            &Category::Health,
+            &text,
+            &false,
+            &FundingMode::KeepItAll,
+        ));
+        assert_unauthorized(unsigned.try_close_campaign(&campaign_id));
+        assert_unauthorized(unsigned.try_withdraw_remaining(&campaign_id, &someone));
+        assert_unauthorized(unsigned.try_acknowledge_at_risk(&campaign_id));
+        assert_unauthorized(unsigned.try_unregister_hook(&campaign_id));
+        assert_unauthorized(unsigned.try_set_milestones(&campaign_id, &soroban_sdk::Vec::new(&env)));
+        assert_unauthorized(unsigned.try_set_donation_limits(&campaign_id, &10, &5000));
+        assert_unauthorized(unsigned.try_update_campaign(&campaign_id, &Some(String::from_str(&env, "Renamed")), &None, &None, &None));
+        assert_unauthorized(unsigned.try_post_update(&campaign_id, &text, &text));
+        assert_unauthorized(unsigned.try_set_donor_whitelist(&campaign_id, &donors, &true));
+        assert_unauthorized(unsigned.try_add_to_whitelist(&campaign_id, &donors));
+        assert_unauthorized(unsigned.try_remove_from_whitelist(&campaign_id, &donors));
+        assert_unauthorized(unsigned.try_set_close_on_goal(&campaign_id, &true));
+        assert_unauthorized(unsigned.try_set_overfunding_limit(&campaign_id, &1000));
+        assert_unauthorized(unsigned.try_set_campaign_token(&campaign_id, &someone));
+        assert_unauthorized(unsigned.try_create_disbursement(&campaign_id, &beneficiary, &100, &0));
+        assert_unauthorized(unsigned.try_enable_donor_voting(&campaign_id, &3600));
+        assert_unauthorized(unsigned.try_cancel_disbursement(&disbursement_id));
+    }
+
+    #[test]
+    fn test_donor_entrypoints_require_donor() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 5000);
+        let no_message = String::from_str(&env, "");
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &true, &no_message);
+        let nft_id = client.get_nfts_by_owner(&donor, &None, &1).0.get(0).unwrap().id;
+        let recurring_id = client.create_recurring(&campaign_id, &donor, &100, &60, &5);
+        let challenge_id = client.create_challenge(&donor, &campaign_id, &500, &Address::generate(&env), &100, &1000);
+        client.create_match(&campaign_id, &donor, &10000, &500);
+
+        let unsigned = client.mock_auths(&[]);
+        let someone = Address::generate(&env);
+
+        assert_unauthorized(unsigned.try_donate(&campaign_id, &donor, &100, &false, &false, &no_message));
+        assert_unauthorized(unsigned.try_donate_with_privacy(&campaign_id, &donor, &100, &Privacy::HideBoth, &false, &no_message));
+        assert_unauthorized(unsigned.try_donate_as_gift(&campaign_id, &donor, &someone, &100, &false, &no_message));
+        assert_unauthorized(unsigned.try_donate_with_referral(&campaign_id, &donor, &100, &false, &false, &no_message, &someone));
+        assert_unauthorized(unsigned.try_donate_with_ref(&campaign_id, &donor, &100, &false, &false, &no_message, &BytesN::from_array(&env, &[7; 32])));
+        assert_unauthorized(unsigned.try_donate_batch(&donor, &soroban_sdk::vec![&env, (campaign_id.clone(), 100)], &false, &false));
+        assert_unauthorized(unsigned.try_create_recurring(&campaign_id, &donor, &100, &60, &5));
+        assert_unauthorized(unsigned.try_cancel_recurring(&recurring_id));
+        assert_unauthorized(unsigned.try_create_match(&campaign_id, &someone, &10000, &500));
+        assert_unauthorized(unsigned.try_close_match(&campaign_id));
+        assert_unauthorized_query(unsigned.try_get_donations_by_donor_as(&donor, &0, &10));
+        assert_unauthorized(unsigned.try_get_my_donations(&donor, &None, &10));
+        assert_unauthorized(unsigned.try_get_donation_as(&donation_id, &donor));
+        assert_unauthorized(unsigned.try_claim_refund(&donation_id));
+        assert_unauthorized(unsigned.try_create_challenge(&donor, &campaign_id, &500, &someone, &100, &1000));
+        assert_unauthorized(unsigned.try_reclaim_challenge(&challenge_id));
+        let hidden_id = client.donate_with_privacy(&campaign_id, &donor, &100, &Privacy::HideDonor, &false, &no_message);
+        assert_unauthorized(unsigned.try_get_attestation_payload(&hidden_id));
+        assert_unauthorized(unsigned.try_get_receipt(&hidden_id));
+        assert_unauthorized(unsigned.try_report_campaign(&donor, &campaign_id));
+        assert_unauthorized(unsigned.try_transfer_nft(&nft_id, &donor, &someone));
+        assert_unauthorized(unsigned.try_burn_nft(&nft_id));
+        assert_unauthorized(unsigned.try_claim_fee_share(&someone));
+    }
+
+    #[test]
+    fn test_voting_entrypoints_require_voter() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 4000);
+        client.donate(&campaign_id, &donor, &2000, &false, &false, &String::from_str(&env, ""));
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &100, &0);
+        let approver = Address::generate(&env);
+        client.set_approvers(&soroban_sdk::vec![&env, approver.clone()], &1);
+
+        let unsigned = client.mock_auths(&[]);
+        assert_unauthorized(unsigned.try_approve_disbursement(&disbursement_id, &approver));
+        assert_unauthorized(unsigned.try_approve_disbursements(&soroban_sdk::vec![&env, disbursement_id.clone()], &approver));
+
+        let voting_campaign = create_test_campaign(&env, &client, &beneficiary);
+        client.enable_donor_voting(&voting_campaign, &3600);
+        client.donate(&voting_campaign, &donor, &2000, &false, &false, &String::from_str(&env, ""));
+        let voting_id = client.create_disbursement(&voting_campaign, &beneficiary, &100, &0);
+        assert_unauthorized(unsigned.try_vote_disbursement(&voting_id, &donor, &true));
+    }
+
+    #[test]
+    fn test_donating_on_behalf_of_another_donor_fails() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1000);
+        let stranger = Address::generate(&env);
+        let no_message = String::from_str(&env, "");
+
+        let args = (campaign_id.clone(), donor.clone(), 1000_i128, false, false, no_message.clone()).into_val(&env);
+        assert_unauthorized(client.mock_auths(&[mock_auth_for(&stranger, &client.address, "donate", args)])
+            .try_donate(&campaign_id, &donor, &1000, &false, &false, &no_message));
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 0);
+    }
+
+    #[test]
+    fn test_create_disbursement_requires_recipient_signature() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 2000), &2000, &false, &false, &String::from_str(&env, ""));
+        let recipient = Address::generate(&env);
+
+        // The beneficiary alone can't assign a payout to someone else
+        let args: soroban_sdk::Vec<Val> = (campaign_id.clone(), recipient.clone(), 100_i128, 0_u32).into_val(&env);
+        assert_unauthorized(client.mock_auths(&[mock_auth_for(&beneficiary, &client.address, "create_disbursement", args.clone())])
+            .try_create_disbursement(&campaign_id, &recipient, &100, &0));
+
+        // Nor can the recipient claim one without the beneficiary
+        assert_unauthorized(client.mock_auths(&[mock_auth_for(&recipient, &client.address, "create_disbursement", args.clone())])
+            .try_create_disbursement(&campaign_id, &recipient, &100, &0));
+
+        client
+            .mock_auths(&[
+                mock_auth_for(&beneficiary, &client.address, "create_disbursement", args.clone()),
+                mock_auth_for(&recipient, &client.address, "create_disbursement", args),
+            ])
+            .create_disbursement(&campaign_id, &recipient, &100, &0);
+
+        // Paying the beneficiary needs only their own signature
+        let args = (campaign_id.clone(), beneficiary.clone(), 100_i128, 0_u32).into_val(&env);
+        client.mock_auths(&[mock_auth_for(&beneficiary, &client.address, "create_disbursement", args)])
+            .create_disbursement(&campaign_id, &beneficiary, &100, &0);
+    }
+
+    #[test]
+    fn test_donate_with_ref_is_idempotent() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 5000);
+        let no_message = String::from_str(&env, "");
+        let first_ref = BytesN::from_array(&env, &[1; 32]);
+
+        let donation_id = client.donate_with_ref(&campaign_id, &donor, &1000, &false, &false, &no_message, &first_ref);
+        let retried = client.donate_with_ref(&campaign_id, &donor, &1000, &false, &false, &no_message, &first_ref);
+        assert_eq!(retried, donation_id);
+        assert_eq!(client.get_campaign_stats(&campaign_id).donation_count, 1);
+        assert_eq!(client.get_platform_stats().total_donations, 1);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 980);
+
+        let second_ref = BytesN::from_array(&env, &[2; 32]);
+        let other = client.donate_with_ref(&campaign_id, &donor, &1000, &false, &false, &no_message, &second_ref);
+        assert_ne!(other, donation_id);
+        assert_eq!(client.get_campaign_stats(&campaign_id).donation_count, 2);
+
+        // Refs are per donor
+        let someone_else = funded_donor(&env, &client, 1000);
+        assert_ne!(client.donate_with_ref(&campaign_id, &someone_else, &1000, &false, &false, &no_message, &first_ref), donation_id);
+    }
+
+    #[test]
+    fn test_receipt_hash_verifies() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_all_or_nothing_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+
+        let receipt = client.get_receipt(&donation_id);
+        assert_eq!((receipt.gross_amount, receipt.fee, receipt.net_amount), (1000, 20, 980));
+        assert_eq!((receipt.beneficiary.clone(), receipt.donor.clone()), (beneficiary, donor));
+        assert_eq!(client.get_receipt(&donation_id), receipt);
+        assert!(client.verify_receipt(&donation_id, &receipt.receipt_hash));
+        assert!(!client.verify_receipt(&BytesN::from_array(&env, &[9; 32]), &receipt.receipt_hash));
+
+        // A receipt with one field altered hashes differently and fails
+        let mut tampered = receipt.clone();
+        tampered.net_amount = 1000;
+        let tampered_hash = env.as_contract(&client.address, || SaviaContract::receipt_hash(&env, &tampered));
+        assert_ne!(tampered_hash, receipt.receipt_hash);
+        assert!(!client.verify_receipt(&donation_id, &tampered_hash));
+
+        // Refunding changes the receipt, so the earlier one stops verifying
+        env.ledger().with_mut(|l| l.timestamp = 31 * 24 * 60 * 60);
+        client.claim_refund(&donation_id);
+        assert!(!client.verify_receipt(&donation_id, &receipt.receipt_hash));
+        assert!(client.get_receipt(&donation_id).refunded);
+    }
+
+    #[test]
+    fn test_campaigns_in_different_tokens() {
+        let env = Env::default();
+        let client = setup(&env);
+        let platform_token = token_address(&env, &client);
+        let other_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+        let no_message = String::from_str(&env, "");
+
+        let beneficiary = Address::generate(&env);
+        let platform_campaign = create_test_campaign(&env, &client, &beneficiary);
+        let other_campaign = create_test_campaign(&env, &client, &Address::generate(&env));
+        assert_eq!(client.get_campaign(&platform_campaign).unwrap().token, platform_token);
+
+        // Only allowed tokens may be chosen
+        assert_eq!(client.try_set_campaign_token(&other_campaign, &other_token), Err(Ok(SaviaError::WrongToken)));
+        client.allow_token(&other_token, &true);
+        assert!(client.is_token_allowed(&other_token));
+        client.set_campaign_token(&other_campaign, &other_token);
+        assert_eq!(client.get_campaign(&other_campaign).unwrap().token, other_token);
+
+        let donor = funded_donor(&env, &client, 1000);
+        token::StellarAssetClient::new(&env, &other_token).mint(&donor, &2000);
+
+        // Naming the wrong token is rejected before anything moves
+        assert_eq!(
+            client.try_donate_with_token(&other_campaign, &donor, &platform_token, &1000, &false, &false, &no_message),
+            Err(Ok(SaviaError::WrongToken))
+        );
+        client.donate_with_token(&other_campaign, &donor, &other_token, &2000, &false, &false, &no_message);
+        client.donate(&platform_campaign, &donor, &1000, &false, &false, &no_message);
+
+        let other_client = token::Client::new(&env, &other_token);
+        assert_eq!(other_client.balance(&donor), 0);
+        assert_eq!(other_client.balance(&client.address), 2000);
+        assert_eq!(client.get_campaign(&other_campaign).unwrap().current_amount, 1960);
+        assert_eq!(client.get_token_stats(&other_token).total_raised, 1960);
+        assert_eq!(client.get_token_stats(&platform_token).total_raised, 980);
+
+        // Platform totals and fees stay in the platform token
+        assert_eq!(client.get_platform_stats().total_raised, 980);
+        assert_eq!(client.get_accrued_fees(), 20);
+        assert_eq!(client.get_token_fees(&other_token), 40);
+        let treasury = Address::generate(&env);
+        client.withdraw_token_fees(&other_token, &treasury, &40);
+        assert_eq!(other_client.balance(&treasury), 40);
+
+        // Payouts go out in the campaign's token, and the token is fixed now
+        let other_beneficiary = client.get_campaign(&other_campaign).unwrap().beneficiary;
+        let disbursement_id = client.create_disbursement(&other_campaign, &other_beneficiary, &500, &0);
+        client.approve_disbursement(&disbursement_id, &admin_of(&env, &client));
+        client.execute_disbursement(&disbursement_id, &0);
+        assert_eq!(other_client.balance(&other_beneficiary), 500);
+        assert_eq!(client.try_set_campaign_token(&other_campaign, &platform_token), Err(Ok(SaviaError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_campaign_progress() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let no_message = String::from_str(&env, "");
+        assert_eq!(
+            client.try_get_campaign_progress(&BytesN::from_array(&env, &[0; 32])),
+            Err(Ok(SaviaError::CampaignNotFound))
+        );
+
+        client.donate(&campaign_id, &funded_donor(&env, &client, 5000), &5000, &false, &false, &no_message);
+        let progress = client.get_campaign_progress(&campaign_id);
+        assert_eq!((progress.percent_funded, progress.is_goal_met, progress.donor_count), (4900, false, 1));
+        assert_eq!(progress.seconds_remaining, 30 * 24 * 60 * 60);
+
+        // Over-funded well past the goal saturates the percentage
+        client.donate(&campaign_id, &funded_donor(&env, &client, 60_000), &60_000, &false, &false, &no_message);
+        let progress = client.get_campaign_progress(&campaign_id);
+        assert_eq!((progress.percent_funded, progress.is_goal_met, progress.donor_count), (50_000, true, 2));
+
+        // At the end timestamp itself the campaign is still open
+        let end_time = client.get_campaign(&campaign_id).unwrap().end_time;
+        env.ledger().with_mut(|l| l.timestamp = end_time);
+        let progress = client.get_campaign_progress(&campaign_id);
+        assert_eq!((progress.seconds_remaining, progress.is_ended), (0, false));
+
+        env.ledger().with_mut(|l| l.timestamp = end_time + 1);
+        let progress = client.get_campaign_progress(&campaign_id);
+        assert_eq!((progress.seconds_remaining, progress.is_ended), (0, true));
+    }
+
+    /// Full topic vectors of every event published under `(subject, action)`
+    fn topics_for(env: &Env, subject: &str, action: &str) -> soroban_sdk::Vec<soroban_sdk::Vec<Val>> {
+        let expected: soroban_sdk::Vec<Val> = (Symbol::new(env, subject), Symbol::new(env, action)).into_val(env);
+        let mut found = soroban_sdk::Vec::new(env);
+        for (_, event_topics, _) in env.events().all().iter() {
+            if event_topics.len() >= 2 && event_topics.slice(0..2) == expected {
+                found.push_back(event_topics);
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn test_event_topics_carry_ids() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let sym = |s: &str| Symbol::new(&env, s);
+
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let expected: soroban_sdk::Vec<Val> = (sym("campaign"), sym("created"), campaign_id.clone()).into_val(&env);
+        assert_eq!(topics_for(&env, topics::CAMPAIGN, topics::CREATED), soroban_sdk::vec![&env, expected]);
+
+        let donor = funded_donor(&env, &client, 1000);
+        client.donate(&campaign_id, &donor, &1000, &false, &true, &String::from_str(&env, ""));
+        let expected: soroban_sdk::Vec<Val> = (sym("donation"), sym("made"), campaign_id.clone(), donor.clone()).into_val(&env);
+        assert_eq!(topics_for(&env, topics::DONATION, topics::MADE), soroban_sdk::vec![&env, expected]);
+        let expected: soroban_sdk::Vec<Val> = (sym("nft"), sym("minted"), campaign_id.clone(), donor.clone()).into_val(&env);
+        assert_eq!(topics_for(&env, topics::NFT, topics::MINTED), soroban_sdk::vec![&env, expected]);
+        let expected: soroban_sdk::Vec<Val> = (sym("trust"), sym("updated"), donor.clone()).into_val(&env);
+        assert_eq!(topics_for(&env, topics::TRUST, topics::UPDATED).last(), Some(expected));
+
+        // A hidden donor is filed under the contract, never their own address
+        let hidden = funded_donor(&env, &client, 100);
+        client.donate(&campaign_id, &hidden, &100, &true, &false, &String::from_str(&env, ""));
+        let expected: soroban_sdk::Vec<Val> = (sym("donation"), sym("made"), campaign_id.clone(), client.address.clone()).into_val(&env);
+        assert_eq!(topics_for(&env, topics::DONATION, topics::MADE).last(), Some(expected));
+
+        let disbursement_id = client.create_disbursement(&campaign_id, &beneficiary, &500, &0);
+        let expected: soroban_sdk::Vec<Val> = (sym("disbursement"), sym("created"), campaign_id, disbursement_id).into_val(&env);
+        assert_eq!(topics_for(&env, topics::DISBURSEMENT, topics::CREATED), soroban_sdk::vec![&env, expected]);
+    }
+
+    #[test]
+    fn test_trust_changes_emit_updated_events() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let last_change = |env: &Env| {
+            let event = events_for(env, topics::TRUST, topics::UPDATED).last().unwrap();
+            <(u32, u32, TrustChangeReason)>::try_from_val(env, &event).unwrap()
+        };
+        assert_eq!(last_change(&env), (NEUTRAL_TRUST_SCORE, NEUTRAL_TRUST_SCORE, TrustChangeReason::CampaignCreated));
+
+        // One donate call carries both the donation and the donor's trust change
+        let donor = funded_donor(&env, &client, 1000);
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let made = events_for(&env, topics::DONATION, topics::MADE).last().unwrap();
+        let (made_id, made_campaign, made_donor, _) =
+            <(BytesN<32>, BytesN<32>, Option<Address>, Option<i128>)>::try_from_val(&env, &made).unwrap();
+        assert_eq!((made_id, made_campaign, made_donor), (donation_id, campaign_id, Some(donor.clone())));
+        let score = client.get_trust_score(&donor).unwrap().score;
+        assert_eq!(last_change(&env), (NEUTRAL_TRUST_SCORE, score, TrustChangeReason::Donation));
+
+        client.adjust_trust_score(&donor, &-10, &String::from_str(&env, "Review"));
+        assert_eq!(last_change(&env), (score, score - 10, TrustChangeReason::AdminAdjustment));
+
+        let entity = Address::generate(&env);
+        client.initialize_trust_score(&entity);
+        let created = events_for(&env, topics::TRUST, topics::CREATED).last().unwrap();
+        assert_eq!(
+            <(u32, u32, TrustChangeReason)>::try_from_val(&env, &created).unwrap(),
+            (NEUTRAL_TRUST_SCORE, NEUTRAL_TRUST_SCORE, TrustChangeReason::Created)
+        );
+
+        client.adjust_trust_score(&entity, &30, &String::from_str(&env, "Long-time donor"));
+        client.set_trust_decay(&TrustDecay { grace: 0, period: 30 * 24 * 60 * 60, points: 2 });
+        env.ledger().with_mut(|li| li.timestamp += 60 * 24 * 60 * 60);
+        assert_eq!(client.refresh_trust_score(&entity), 76);
+        assert_eq!(last_change(&env), (80, 76, TrustChangeReason::Decay));
+    }
+
+    #[test]
+    fn test_get_donations_in_range() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 10_000);
+        let day_start = |day: u64| day * DAY;
+        let donate_at = |timestamp: u64| {
+            env.ledger().with_mut(|li| li.timestamp = timestamp);
+            client.donate(&campaign_id, &donor, &100, &false, &false, &String::from_str(&env, ""))
+        };
+
+        // One donation late on day 1, two on day 2, one early and one late on day 3
+        let base = env.ledger().timestamp() / DAY + 1;
+        donate_at(day_start(base) + DAY - 1);
+        let middle = [donate_at(day_start(base + 1) + 10), donate_at(day_start(base + 1) + 5000)];
+        let boundary = donate_at(day_start(base + 2));
+        donate_at(day_start(base + 2) + 1000);
+
+        // From the start of day 2 up to the first second of day 3
+        let found = client.get_donations_in_range(&day_start(base + 1), &day_start(base + 2), &0, &10);
+        let mut ids = soroban_sdk::Vec::new(&env);
+        for donation in found.iter() {
+            ids.push_back(donation.id);
+        }
+        assert_eq!(ids, soroban_sdk::vec![&env, middle[0].clone(), middle[1].clone(), boundary]);
+
+        // Paging counts only donations inside the window
+        let page = client.get_donations_in_range(&day_start(base + 1), &day_start(base + 2), &1, &1);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page.get(0).unwrap().id, middle[1]);
+
+        assert_eq!(
+            client.try_get_donations_in_range(&0, &(MAX_RANGE_SECONDS + 1), &0, &10),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+        assert_eq!(
+            client.try_get_donations_in_range(&day_start(base + 2), &day_start(base + 1), &0, &10),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+    }
+
+    #[test]
+    fn test_disbursement_after_early_close_of_all_or_nothing_fails() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_all_or_nothing_campaign(&env, &client, &beneficiary);
+        client.donate(&campaign_id, &funded_donor(&env, &client, 1000), &1000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().outcome, CampaignOutcome::Ongoing);
+
+        client.close_campaign(&campaign_id);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().outcome, CampaignOutcome::ClosedEarly);
+        assert_eq!(
+            client.try_create_disbursement(&campaign_id, &beneficiary, &500, &0),
+            Err(Ok(SaviaError::GoalNotReached))
+        );
+    }
+
+    #[test]
+    fn test_campaign_outcomes() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_creation_limits(&CreationLimits { max_active: 0, max_per_day: 0 });
+        let beneficiary = Address::generate(&env);
+        let donor = funded_donor(&env, &client, 100_000);
+        let empty = String::from_str(&env, "");
+
+        // A keep-it-all campaign closed early still pays out what it raised
+        let kept = create_test_campaign(&env, &client, &beneficiary);
+        client.donate(&kept, &donor, &1000, &false, &false, &empty);
+        client.close_campaign(&kept);
+        assert_eq!(client.get_campaign(&kept).unwrap().outcome, CampaignOutcome::ClosedEarly);
+        client.create_disbursement(&kept, &beneficiary, &500, &0);
+
+        let funded = create_all_or_nothing_campaign(&env, &client, &beneficiary);
+        client.donate(&funded, &donor, &20_000, &false, &false, &empty);
+        let missed = create_all_or_nothing_campaign(&env, &client, &beneficiary);
+        client.donate(&missed, &donor, &1000, &false, &false, &empty);
+        let suspended = create_test_campaign(&env, &client, &beneficiary);
+        client.suspend_campaign(&suspended, &String::from_str(&env, "Fraud"));
+        assert_eq!(client.get_campaign(&suspended).unwrap().outcome, CampaignOutcome::Suspended);
+
+        env.ledger().with_mut(|li| li.timestamp += 31 * 24 * 60 * 60);
+        assert!(client.finalize_campaign(&funded));
+        assert_eq!(client.get_campaign(&funded).unwrap().outcome, CampaignOutcome::Successful);
+        client.create_disbursement(&funded, &beneficiary, &500, &0);
+        assert!(!client.finalize_campaign(&missed));
+        assert_eq!(client.get_campaign(&missed).unwrap().outcome, CampaignOutcome::Unsuccessful);
+        assert_eq!(
+            client.try_create_disbursement(&missed, &beneficiary, &500, &0),
+            Err(Ok(SaviaError::GoalNotReached))
+        );
+
+        // Suspending a campaign after it ended overrides its outcome
+        client.suspend_campaign(&funded, &String::from_str(&env, "Fraud"));
+        assert_eq!(client.get_campaign(&funded).unwrap().outcome, CampaignOutcome::Suspended);
+    }
+
+    #[test]
+    fn test_reopen_campaign_within_window() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 2000);
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        let end_time = client.get_campaign(&campaign_id).unwrap().end_time;
+        let active_before = client.get_platform_stats().active_campaigns;
+
+        client.close_campaign(&campaign_id);
+        // The admin can only confirm a request the beneficiary made
+        assert_eq!(client.try_reopen_campaign(&campaign_id), Err(Ok(SaviaError::InvalidInput)));
+
+        env.ledger().with_mut(|li| li.timestamp += 6 * 24 * 60 * 60);
+        client.compact_active_campaigns();
+        client.request_reopen(&campaign_id);
+        client.reopen_campaign(&campaign_id);
+
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert!(campaign.active);
+        assert_eq!(campaign.outcome, CampaignOutcome::Ongoing);
+        assert_eq!(campaign.end_time, end_time);
+        assert_eq!(client.get_platform_stats().active_campaigns, active_before);
+        assert_eq!(client.list_active_campaigns_at(&0, &10).len(), 1);
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+        assert_eq!(client.get_donations_by_campaign_at(&campaign_id, &0, &10).len(), 2);
+
+        // The request was consumed
+        assert_eq!(client.try_reopen_campaign(&campaign_id), Err(Ok(SaviaError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_reopen_campaign_after_window_fails() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        client.close_campaign(&campaign_id);
+        client.request_reopen(&campaign_id);
+
+        env.ledger().with_mut(|li| li.timestamp += 7 * 24 * 60 * 60 + 1);
+        assert_eq!(client.try_reopen_campaign(&campaign_id), Err(Ok(SaviaError::CampaignLocked)));
+        assert_eq!(client.try_request_reopen(&campaign_id), Err(Ok(SaviaError::CampaignLocked)));
+        assert!(!client.get_campaign(&campaign_id).unwrap().active);
+    }
+
+    #[test]
+    fn test_suspended_or_expired_campaign_cannot_reopen() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+
+        let suspended = create_test_campaign(&env, &client, &beneficiary);
+        client.close_campaign(&suspended);
+        client.request_reopen(&suspended);
+        client.suspend_campaign(&suspended, &String::from_str(&env, "Fraud"));
+        assert_eq!(client.try_reopen_campaign(&suspended), Err(Ok(SaviaError::CampaignSuspended)));
+        assert_eq!(client.try_request_reopen(&suspended), Err(Ok(SaviaError::CampaignSuspended)));
+
+        let expired = create_test_campaign(&env, &client, &beneficiary);
+        env.ledger().with_mut(|li| li.timestamp += 31 * 24 * 60 * 60);
+        client.finalize_campaign(&expired);
+        assert_eq!(client.try_request_reopen(&expired), Err(Ok(SaviaError::CampaignEnded)));
+
+        let active = create_test_campaign(&env, &client, &beneficiary);
+        assert_eq!(client.try_request_reopen(&active), Err(Ok(SaviaError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_creation_rate_limit() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        for _ in 0..3 {
+            create_test_campaign(&env, &client, &beneficiary);
+        }
+        let create = |client: &SaviaContractClient| client.try_create_campaign(
+            &beneficiary,
+            &String::from_str(&env, "Test Campaign"),
+            &String::from_str(&env, "A test campaign"),
+            &10000,
+            &30,
+            &Category::Health,
+            &String::from_str(&env, "Test City"),
+            &false,
+            &FundingMode::KeepItAll,
+        );
+        assert_eq!(create(&client), Err(Ok(SaviaError::RateLimited)));
+
+        // The window rolls: a day after the first creation there is room again
+        env.ledger().with_mut(|li| li.timestamp += 24 * 60 * 60);
+        assert!(create(&client).is_ok());
+        create_test_campaign(&env, &client, &beneficiary);
+
+        // Five campaigns are now active, the default cap
+        env.ledger().with_mut(|li| li.timestamp += 24 * 60 * 60);
+        assert_eq!(create(&client), Err(Ok(SaviaError::RateLimited)));
+
+        // A global override lifts it
+        client.set_creation_limits(&CreationLimits { max_active: 6, max_per_day: 3 });
+        assert_eq!(client.get_creation_limits(), CreationLimits { max_active: 6, max_per_day: 3 });
+        assert!(create(&client).is_ok());
+    }
+
+    #[test]
+    fn test_creation_exemption_bypasses_limits() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+
+        // Only verified addresses can be exempted
+        assert_eq!(
+            client.try_set_creation_exempt(&beneficiary, &true),
+            Err(Ok(SaviaError::VerificationRequired))
+        );
+        set_verification_level(&env, &client, &beneficiary, 1);
+        client.set_creation_exempt(&beneficiary, &true);
+        assert!(client.is_creation_exempt(&beneficiary));
+        for _ in 0..6 {
+            create_test_campaign(&env, &client, &beneficiary);
+        }
+
+        client.set_creation_exempt(&beneficiary, &false);
+        assert_eq!(
+            client.try_create_campaign(
+                &beneficiary,
+                &String::from_str(&env, "Test Campaign"),
+                &String::from_str(&env, "A test campaign"),
+                &10000,
+                &30,
+                &Category::Health,
+                &String::from_str(&env, "Test City"),
+                &false,
+                &FundingMode::KeepItAll,
+            ),
+            Err(Ok(SaviaError::RateLimited))
+        );
+
+        let outsider = Address::generate(&env);
+        let args = (beneficiary.clone(), true).into_val(&env);
+        env.mock_auths(&[mock_auth_for(&outsider, &client.address, "set_creation_exempt", args)]);
+        assert_unauthorized(client.try_set_creation_exempt(&beneficiary, &true));
+    }
+
+    #[test]
+    fn test_get_campaigns_ending_between() {
+        let env = Env::default();
+        let client = setup(&env);
+        let hour = 60 * 60;
+        let create = |duration_days: u64| client.create_campaign(
+            &Address::generate(&env),
+            &String::from_str(&env, "Ending soon"),
+            &String::from_str(&env, "A test campaign"),
+            &10000,
+            &duration_days,
+            &Category::Health,
+            &String::from_str(&env, "Test City"),
+            &false,
+            &FundingMode::KeepItAll,
+        );
+
+        env.ledger().with_mut(|li| li.timestamp = 12 * hour);
+        let two_days = create(2);
+        // Created later but ending an hour earlier on the same day
+        env.ledger().with_mut(|li| li.timestamp = 35 * hour);
+        let one_day = create(1);
+        let three_days = create(3);
+        create(10);
+        let closed = create(2);
+        client.close_campaign(&closed);
+
+        let now = env.ledger().timestamp();
+        let ids = |campaigns: soroban_sdk::Vec<Campaign>| {
+            let mut ids = soroban_sdk::Vec::new(&env);
+            for campaign in campaigns.iter() {
+                ids.push_back(campaign.id);
+            }
+            ids
+        };
+        let ending = client.get_campaigns_ending_between(&now, &(now + 72 * hour), &10);
+        assert_eq!(ids(ending), soroban_sdk::vec![&env, one_day.clone(), two_days.clone(), three_days]);
+        let ending = client.get_campaigns_ending_between(&now, &(now + 72 * hour), &2);
+        assert_eq!(ids(ending), soroban_sdk::vec![&env, one_day, two_days]);
+
+        assert_eq!(
+            client.try_get_campaigns_ending_between(&now, &(now + MAX_RANGE_SECONDS + 1), &10).err(),
+            Some(Ok(SaviaError::InvalidInput))
+        );
+    }
+
+    #[test]
+    fn test_get_recently_funded() {
+        let env = Env::default();
+        let client = setup(&env);
+        let first = create_test_campaign(&env, &client, &Address::generate(&env));
+        let second = create_test_campaign(&env, &client, &Address::generate(&env));
+        let unfunded = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 100_000);
+        for campaign_id in [&first, &second] {
+            client.donate(campaign_id, &donor, &20_000, &false, &false, &String::from_str(&env, ""));
+        }
+        client.donate(&unfunded, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+
+        let funded = client.get_recently_funded(&10);
+        assert_eq!(funded.len(), 2);
+        assert_eq!((funded.get(0).unwrap().id, funded.get(1).unwrap().id), (second.clone(), first.clone()));
+        assert_eq!(client.get_recently_funded(&1).get(0).unwrap().id, second);
+
+        client.suspend_campaign(&second, &String::from_str(&env, "Fraud"));
+        let funded = client.get_recently_funded(&10);
+        assert_eq!(funded.len(), 1);
+        assert_eq!(funded.get(0).unwrap().id, first);
+    }
+
+    #[test]
+    fn test_profile_verified_name_resets_on_update() {
+        let env = Env::default();
+        let client = setup(&env);
+        let entity = Address::generate(&env);
+        let name = String::from_str(&env, "Clinica Esperanza");
+        let bio = String::from_str(&env, "ipfs://bio");
+        let website = String::from_str(&env, "https://example.org");
+        assert_eq!(client.get_profile(&entity), None);
+
+        client.register_profile(&entity, &name, &bio, &website);
+        let profile = client.get_profile(&entity).unwrap();
+        assert_eq!((profile.display_name, profile.bio_uri, profile.website), (name.clone(), bio.clone(), website.clone()));
+        assert!(!profile.name_verified);
+
+        // Email verification doesn't cover the name; identity does
+        set_verification_level(&env, &client, &entity, 1);
+        assert!(!client.get_profile(&entity).unwrap().name_verified);
+        set_verification_level(&env, &client, &entity, 2);
+        assert!(client.get_profile(&entity).unwrap().name_verified);
+
+        client.register_profile(&entity, &String::from_str(&env, "Someone Else"), &bio, &website);
+        let profile = client.get_profile(&entity).unwrap();
+        assert_eq!(profile.display_name, String::from_str(&env, "Someone Else"));
+        assert!(!profile.name_verified);
+        // The verification level itself is untouched
+        assert_eq!(client.get_trust_score(&entity).unwrap().verification_level, 2);
+    }
+
+    #[test]
+    fn test_register_profile_validates_and_requires_entity() {
+        let env = Env::default();
+        let client = setup(&env);
+        let entity = Address::generate(&env);
+        let empty = String::from_str(&env, "");
+        let too_long_name = String::from_bytes(&env, &[b'a'; MAX_DISPLAY_NAME_LEN as usize + 1]);
+        let too_long_uri = String::from_bytes(&env, &[b'a'; MAX_PROFILE_URI_LEN as usize + 1]);
+        let name = String::from_str(&env, "Name");
+
+        assert_eq!(client.try_register_profile(&entity, &empty, &empty, &empty), Err(Ok(SaviaError::InvalidInput)));
+        assert_eq!(client.try_register_profile(&entity, &too_long_name, &empty, &empty), Err(Ok(SaviaError::InvalidInput)));
+        assert_eq!(client.try_register_profile(&entity, &name, &too_long_uri, &empty), Err(Ok(SaviaError::InvalidInput)));
+        assert_eq!(client.try_register_profile(&entity, &name, &empty, &too_long_uri), Err(Ok(SaviaError::InvalidInput)));
+        client.register_profile(&entity, &name, &empty, &empty);
+
+        let outsider = Address::generate(&env);
+        let args = (entity.clone(), name.clone(), empty.clone(), empty.clone()).into_val(&env);
+        env.mock_auths(&[mock_auth_for(&outsider, &client.address, "register_profile", args)]);
+        assert_unauthorized(client.try_register_profile(&entity, &name, &empty, &empty));
+    }
+
+    #[test]
+    fn test_unverified_raise_cap() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1_000_000);
+        let empty = String::from_str(&env, "");
+        assert_eq!(client.get_unverified_raise_cap(), 100_000);
+        client.set_unverified_raise_cap(&9800);
+
+        // 10000 gross is 9800 net at the 2% fee, exactly the cap
+        assert_eq!(client.preview_donation(&campaign_id, &10000).unverified_headroom, Some(9800));
+        client.donate(&campaign_id, &donor, &10000, &false, &false, &empty);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 9800);
+
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &100, &false, &false, &empty),
+            Err(Ok(SaviaError::VerificationRequired))
+        );
+        assert_eq!(
+            client.try_preview_donation(&campaign_id, &100),
+            Err(Ok(SaviaError::VerificationRequired))
+        );
+
+        client.verify_campaign(&campaign_id, &80);
+        assert_eq!(client.preview_donation(&campaign_id, &100).unverified_headroom, None);
+        client.donate(&campaign_id, &donor, &100, &false, &false, &empty);
+        assert_eq!(client.get_campaign(&campaign_id).unwrap().current_amount, 9898);
+
+        // 0 disables the cap for every campaign
+        let other = create_test_campaign(&env, &client, &Address::generate(&env));
+        client.set_unverified_raise_cap(&0);
+        client.donate(&other, &donor, &20_000, &false, &false, &empty);
+        assert_eq!(client.try_set_unverified_raise_cap(&-1), Err(Ok(SaviaError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_admin_entrypoints_before_initialize() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = SaviaContractClient::new(&env, &env.register_contract(None, SaviaContract));
+        let addr = Address::generate(&env);
+        let id = BytesN::from_array(&env, &[1; 32]);
+        let text = String::from_str(&env, "x");
+        let not_initialized = Err(Ok(SaviaError::NotInitialized));
 
-        // Create donation record
-        let donation = Donation {
-            id: donation_id.clone(),
-            campaign_id: campaign_id.clone(),
-            donor: donor.clone(),
-            amount: net_amount,
-            timestamp: current_time,
-            nft_minted: mint_nft,
-            anonymous,
-        };
+        // Admin functions
+        assert_eq!(client.try_upgrade(&id), not_initialized);
+        assert_eq!(client.try_pause(), not_initialized);
+        assert_eq!(client.try_unpause(), not_initialized);
+        assert_eq!(client.try_set_blacklist(&addr, &true), not_initialized);
+        assert_eq!(client.try_transfer_admin(&addr), not_initialized);
+        assert_eq!(client.try_set_platform_fee(&100), not_initialized);
+        assert_eq!(client.try_schedule_fee_holiday(&10, &20, &0), not_initialized);
+        assert_eq!(client.try_set_event_verbosity(&EventVerbosity::Verbose), not_initialized);
+        assert_eq!(client.try_allow_token(&addr, &true), not_initialized);
+        assert_eq!(client.try_withdraw_fees(&addr, &1), not_initialized);
+        assert_eq!(client.try_set_fee_recipients(&soroban_sdk::vec![&env, (addr.clone(), 10000u32)]), not_initialized);
+        assert_eq!(client.try_verify_campaign(&id, &80), not_initialized);
+        assert_eq!(client.try_suspend_campaign(&id, &text), not_initialized);
+        assert_eq!(client.try_adjust_trust_score(&addr, &5, &text).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_set_verification_level(&addr, &1, &id), not_initialized);
+        assert_eq!(client.try_add_category(&Category::Health), not_initialized);
+        assert_eq!(client.try_set_approvers(&soroban_sdk::vec![&env, addr.clone()], &1), not_initialized);
+        assert_eq!(client.try_set_creation_limits(&CreationLimits { max_active: 1, max_per_day: 1 }), not_initialized);
+        assert_eq!(client.try_set_unverified_raise_cap(&1), not_initialized);
+        assert_eq!(client.try_reopen_campaign(&id), not_initialized);
+        assert_eq!(client.try_approve_disbursement(&id, &addr), not_initialized);
+        assert_eq!(client.try_cancel_admin_transfer(), not_initialized);
+        assert_eq!(client.try_migrate(), not_initialized);
+        assert_eq!(client.try_migrate_amounts(&Vec::new(&env)).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_freeze_campaign(&id, &true), not_initialized);
+        assert_eq!(client.try_set_risk_weights(&RiskWeights { new_beneficiary: 1, unverified: 1, goal_over_cap: 1, duplicate_title: 1, self_donations: 1, reports: 1 }), not_initialized);
+        assert_eq!(client.try_set_risk_threshold(&50), not_initialized);
+        assert_eq!(client.try_resolve_dispute(&id, &true), not_initialized);
+        assert_eq!(client.try_set_dispute_threshold(&DisputeThreshold { max_open: 1, max_open_bps: 0 }), not_initialized);
+        assert_eq!(client.try_set_ambassador_thresholds(&AmbassadorThresholds { min_raised: 1, min_donations: 1 }), not_initialized);
+        assert_eq!(client.try_create_bundle(&text, &soroban_sdk::vec![&env, (id.clone(), 10000u32)]).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_set_campaign_fee(&id, &100), not_initialized);
+        assert_eq!(client.try_clear_campaign_fee(&id), not_initialized);
+        assert_eq!(client.try_set_verified_fee_waiver(&true), not_initialized);
+        assert_eq!(client.try_withdraw_token_fees(&addr, &addr, &1), not_initialized);
+        assert_eq!(client.try_set_kyc_disclosure_threshold(&Some(1000)), not_initialized);
+        assert_eq!(client.try_set_archive_grace_period(&60), not_initialized);
+        assert_eq!(client.try_set_min_creator_level(&1), not_initialized);
+        assert_eq!(client.try_set_creation_exempt(&addr, &true), not_initialized);
+        assert_eq!(client.try_set_suspension_penalty(&5), not_initialized);
+        assert_eq!(client.try_set_trust_decay(&TrustDecay { grace: 1, period: 1, points: 1 }), not_initialized);
+        assert_eq!(client.try_import_trust_scores(&Vec::new(&env)).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_set_bootstrap_window(&60), not_initialized);
+        assert_eq!(client.try_set_endgame_window(&60), not_initialized);
+        assert_eq!(client.try_set_disbursement_validity(&60), not_initialized);
+        assert_eq!(client.try_set_ttl_config(&100, &200), not_initialized);
+        assert_eq!(client.try_remove_category(&Category::Health), not_initialized);
+        assert_eq!(client.try_set_category_label(&Category::Health, &Symbol::new(&env, "es"), &text), not_initialized);
+        assert_eq!(client.try_admin_burn_nft(&id), not_initialized);
+        assert_eq!(client.try_set_badge_transferable(&text, &true), not_initialized);
+        assert_eq!(client.try_set_badge_metadata(&text, &text), not_initialized);
+        assert_eq!(client.try_set_metadata_base_uri(&text), not_initialized);
+        assert_eq!(client.try_approve_disbursements(&soroban_sdk::vec![&env, id.clone()], &addr).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_reject_disbursement(&id, &text), not_initialized);
+        assert_eq!(client.try_set_disbursement_limits(&Vec::new(&env)), not_initialized);
+    }
 
-        // Update campaign progress
-        campaign.current_amount += net_amount;
-        env.storage().persistent().set(&DataKey::Campaign(campaign_id.clone()), &campaign);
+    #[test]
+    fn test_entrypoints_before_initialize() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = SaviaContractClient::new(&env, &env.register_contract(None, SaviaContract));
+        let addr = Address::generate(&env);
+        let other = Address::generate(&env);
+        let id = BytesN::from_array(&env, &[1; 32]);
+        let text = String::from_str(&env, "x");
+        let not_initialized = Err(Ok(SaviaError::NotInitialized));
 
-        // Store donation
-        env.storage().persistent().set(&DataKey::Donation(donation_id.clone()), &donation);
+        // Calls that would otherwise fall back to defaults
+        assert_eq!(
+            client.try_create_campaign(&addr, &text, &text, &10000, &30, &Category::Health, &text, &false, &FundingMode::KeepItAll).err(),
+            Some(Ok(SaviaError::NotInitialized))
+        );
+        assert_eq!(client.try_donate(&id, &addr, &100, &false, &false, &text).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_donate_with_privacy(&id, &addr, &100, &Privacy::Public, &false, &text).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_initialize_trust_score(&addr), not_initialized);
+        assert_eq!(client.try_register_profile(&addr, &text, &text, &text), not_initialized);
+        assert_eq!(client.try_get_config().err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_get_platform_fee().err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.quote_donation(&1000).fee, 0);
+        assert_eq!(client.try_accept_admin(), not_initialized);
+        assert_eq!(client.try_report_campaign(&addr, &id).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_claim_fee_share(&addr).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_refresh_trust_score(&addr).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_donate_to_bundle(&id, &addr, &100, &false, &false).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_file_dispute(&id, &addr, &text).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_donate_as_gift(&id, &addr, &other, &100, &false, &text).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_donate_with_referral(&id, &addr, &100, &false, &false, &text, &other).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_donate_with_ref(&id, &addr, &100, &false, &false, &text, &id).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_donate_with_token(&id, &addr, &other, &100, &false, &false, &text).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_donate_with_fundraiser(&id, &addr, &100, &false, &false, &text, &None).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_donate_batch(&addr, &soroban_sdk::vec![&env, (id.clone(), 100)], &false, &false).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_preview_donation(&id, &100).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_register_fundraiser(&id, &addr), not_initialized);
+        assert_eq!(client.try_create_recurring(&id, &addr, &100, &60, &5).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_create_match(&id, &addr, &10000, &500), not_initialized);
+        assert_eq!(client.try_create_challenge(&addr, &id, &500, &other, &100, &1000).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_withdraw_remaining(&id, &addr).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_create_disbursement(&id, &addr, &100, &0).err(), Some(Ok(SaviaError::NotInitialized)));
+        assert_eq!(client.try_execute_disbursement(&id, &0), not_initialized);
+    }
 
-        // Update trust score
-        Self::update_donor_trust_score(env.clone(), donor.clone(), net_amount)?;
+    #[test]
+    fn test_record_entrypoints_before_initialize() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = SaviaContractClient::new(&env, &env.register_contract(None, SaviaContract));
+        let addr = Address::generate(&env);
+        let other = Address::generate(&env);
+        let id = BytesN::from_array(&env, &[1; 32]);
+        let text = String::from_str(&env, "x");
 
-        // Mint NFT if requested
-        if mint_nft {
-            Self::mint_donation_nft(env.clone(), donor, campaign_id, donation_id.clone(), net_amount)?;
-        }
+        // Calls on records fail as missing, since none can exist yet
+        assert_eq!(client.try_close_campaign(&id), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_claim_refund(&id), Err(Ok(SaviaError::DonationNotFound)));
+        assert_eq!(client.try_bump_campaign(&id), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.compact_active_campaigns(), 0);
+        assert_eq!(client.try_request_reopen(&id), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_finalize_campaign(&id).err(), Some(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_archive_campaign(&id, &addr).err(), Some(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_check_campaign_health(&id).err(), Some(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_acknowledge_at_risk(&id), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_register_hook(&id, &addr), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_unregister_hook(&id), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_set_milestones(&id, &Vec::new(&env)), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_set_donation_limits(&id, &1, &0), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_update_campaign(&id, &None, &None, &None, &None), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_post_update(&id, &text, &text).err(), Some(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_set_donor_whitelist(&id, &Vec::new(&env), &true), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_add_to_whitelist(&id, &Vec::new(&env)), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_remove_from_whitelist(&id, &Vec::new(&env)), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_set_close_on_goal(&id, &true), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_set_campaign_token(&id, &addr), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_set_overfunding_limit(&id, &12000), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_enable_donor_voting(&id, &60), Err(Ok(SaviaError::CampaignNotFound)));
+        assert_eq!(client.try_execute_recurring(&id).err(), Some(Ok(SaviaError::RecurringNotFound)));
+        assert_eq!(client.try_cancel_recurring(&id), Err(Ok(SaviaError::RecurringNotFound)));
+        assert_eq!(client.try_reclaim_challenge(&id).err(), Some(Ok(SaviaError::ChallengeNotFound)));
+        assert_eq!(client.try_transfer_nft(&id, &addr, &other), Err(Ok(SaviaError::NFTNotFound)));
+        assert_eq!(client.try_burn_nft(&id), Err(Ok(SaviaError::NFTNotFound)));
+        assert_eq!(client.try_vote_disbursement(&id, &addr, &true), Err(Ok(SaviaError::DisbursementNotFound)));
+        assert_eq!(client.try_tally_disbursement(&id).err(), Some(Ok(SaviaError::DisbursementNotFound)));
+        assert_eq!(client.try_cancel_disbursement(&id), Err(Ok(SaviaError::DisbursementNotFound)));
+        assert_eq!(client.try_expire_disbursement(&id), Err(Ok(SaviaError::DisbursementNotFound)));
+        assert_eq!(client.try_close_match(&id).err(), Some(Ok(SaviaError::InvalidInput)));
+    }
 
-        Ok(donation_id)
+    #[test]
+    fn test_initialize_only_once() {
+        let env = Env::default();
+        let client = setup(&env);
+        let admin = admin_of(&env, &client);
+        let token = token_address(&env, &client);
+
+        assert_eq!(
+            client.try_initialize(&Address::generate(&env), &token, &0),
+            Err(Ok(SaviaError::Unauthorized))
+        );
+        assert_eq!(admin_of(&env, &client), admin);
+        assert_eq!(client.get_platform_fee(), 200);
     }
 
-    /// Get donation details
-    pub fn get_donation(env: Env, donation_id: BytesN<32>) -> Option<Donation> {
-        env.storage().persistent().get(&DataKey::Donation(donation_id))
+    #[test]
+    fn test_extreme_values_fail_without_trapping() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_unverified_raise_cap(&0);
+        let beneficiary = Address::generate(&env);
+        let empty = String::from_str(&env, "");
+
+        // A huge goal with an overfunding limit saturates the cap
+        let campaign_id = client.create_campaign(
+            &beneficiary, &empty, &empty, &i128::MAX, &30, &Category::Health, &empty, &false, &FundingMode::KeepItAll,
+        );
+        client.set_overfunding_limit(&campaign_id, &12000);
+        assert!(client.try_preview_donation(&campaign_id, &1000).is_ok());
+        let donor = funded_donor(&env, &client, 1_000_000);
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &empty);
+
+        // Finalizing it compares against a quarter of the goal without overflowing
+        env.ledger().with_mut(|li| li.timestamp += 31 * 24 * 60 * 60);
+        assert_eq!(client.try_finalize_campaign(&campaign_id), Ok(Ok(false)));
+        assert_eq!(client.get_trust_score(&beneficiary).unwrap().campaigns_failed, 1);
+
+        // A recurring interval that would run past the end of time
+        let other = create_test_campaign(&env, &client, &Address::generate(&env));
+        let recurring_id = client.create_recurring(&other, &donor, &100, &u64::MAX, &2);
+        client.execute_recurring(&recurring_id);
+        assert_eq!(client.get_recurring(&recurring_id).unwrap().next_execution, u64::MAX);
+        assert_eq!(client.try_execute_recurring(&recurring_id), Err(Ok(SaviaError::InvalidInput)));
+
+        // A bootstrap window of u64::MAX seconds never closes
+        client.set_bootstrap_window(&u64::MAX);
+        assert_eq!(client.import_trust_scores(&soroban_sdk::vec![&env]), 0);
     }
 
-    /// Initialize trust score for new user
-    pub fn initialize_trust_score(env: Env, entity: Address) -> Result<(), soroban_sdk::Error> {
-        if env.storage().persistent().has(&DataKey::TrustScore(entity.clone())) {
-            return Err(soroban_sdk::Error::from_contract_error(SaviaError::ScoreExists as u32));
+    /// Every donation `get_donations_by_campaign` lists for a campaign
+    fn all_campaign_donations(env: &Env, client: &SaviaContractClient, campaign_id: &BytesN<32>) -> Vec<Donation> {
+        let mut donations = Vec::new(env);
+        let mut cursor = None;
+        loop {
+            let (page, next) = client.get_donations_by_campaign(campaign_id, &cursor, &50);
+            donations.append(&page);
+            match next {
+                Some(next) => cursor = Some(next),
+                None => return donations,
+            }
         }
+    }
 
-        let trust_score = TrustScore {
-            entity: entity.clone(),
-            score: 50, // Start with neutral score
-            verification_level: 0,
-            donation_count: 0,
-            total_donated: 0,
-            campaigns_created: 0,
-            last_updated: env.ledger().timestamp(),
-        };
+    #[test]
+    fn test_archive_campaign_after_grace_period() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let empty = String::from_str(&env, "");
 
-        env.storage().persistent().set(&DataKey::TrustScore(entity), &trust_score);
-        Ok(())
-    }
+        let donor = funded_donor(&env, &client, 10_000);
+        let hidden = funded_donor(&env, &client, 10_000);
+        let donated_at = env.ledger().timestamp();
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &empty);
+        client.donate(&campaign_id, &donor, &500, &false, &false, &empty);
+        client.donate_with_privacy(&campaign_id, &hidden, &700, &Privacy::HideBoth, &false, &empty);
 
-    /// Update donor trust score
-    fn update_donor_trust_score(env: Env, donor: Address, amount: u64) -> Result<(), soroban_sdk::Error> {
-        let mut trust_score: TrustScore = env.storage().persistent().get(&DataKey::TrustScore(donor.clone()))
-            .unwrap_or(TrustScore {
-                entity: donor.clone(),
-                score: 50,
-                verification_level: 0,
-                donation_count: 0,
-                total_donated: 0,
-                campaigns_created: 0,
-                last_updated: env.ledger().timestamp(),
-            });
+        // Still running
+        assert_eq!(
+            client.try_archive_campaign(&campaign_id, &beneficiary),
+            Err(Ok(SaviaError::CampaignNotEnded))
+        );
+        env.ledger().with_mut(|li| li.timestamp += 31 * DAY);
+        client.finalize_campaign(&campaign_id);
 
-        trust_score.donation_count += 1;
-        trust_score.total_donated += amount;
-        trust_score.last_updated = env.ledger().timestamp();
+        // Within the grace period
+        env.ledger().with_mut(|li| li.timestamp += 89 * DAY);
+        assert_eq!(
+            client.try_archive_campaign(&campaign_id, &beneficiary),
+            Err(Ok(SaviaError::CampaignNotEnded))
+        );
+
+        env.ledger().with_mut(|li| li.timestamp += DAY);
+        assert_eq!(
+            client.try_archive_campaign(&campaign_id, &Address::generate(&env)),
+            Err(Ok(SaviaError::Unauthorized))
+        );
 
-        // Calculate new score based on donation history
-        let donation_factor = if trust_score.donation_count > 100 { 100 } else { trust_score.donation_count };
-        let amount_factor = if trust_score.total_donated > 100000 { 100000 } else { trust_score.total_donated };
-        let consistency_factor = if trust_score.donation_count > 1 { 120u64 } else { 100u64 };
+        let before = all_campaign_donations(&env, &client, &campaign_id);
+        assert_eq!(before.len(), 3);
+        let expected: BytesN<32> = env.crypto().sha256(&before.clone().to_xdr(&env)).into();
 
-        // Fixed arithmetic types
-        let new_score = 50u64 + (25u64 * donation_factor as u64 / 100u64) + (20u64 * amount_factor / 100000u64) * consistency_factor / 100u64;
-        trust_score.score = if new_score > 100 { 100 } else { new_score as u32 };
+        let digest = client.archive_campaign(&campaign_id, &beneficiary);
+        assert_eq!(digest, expected);
+        assert_eq!(client.get_archive_digest(&campaign_id), Some(expected.clone()));
+        let data = events_for(&env, topics::CAMPAIGN, topics::ARCHIVED).last().unwrap();
+        let (_, count, published) = <(BytesN<32>, u32, BytesN<32>)>::try_from_val(&env, &data).unwrap();
+        assert_eq!((count, published), (3, expected));
 
-        env.storage().persistent().set(&DataKey::TrustScore(donor), &trust_score);
-        Ok(())
+        // Donation records and every index entry pointing at them are gone
+        assert!(client.get_donation(&donation_id).is_none());
+        assert_eq!(all_campaign_donations(&env, &client, &campaign_id).len(), 0);
+        assert_eq!(client.get_donations_by_donor(&donor, &0, &10).len(), 0);
+        assert_eq!(client.get_donations_by_donor_as(&hidden, &0, &10).len(), 0);
+        assert_eq!(client.get_donations_in_range(&donated_at, &(donated_at + DAY), &0, &10).len(), 0);
+
+        // The campaign itself is kept
+        assert!(client.get_campaign(&campaign_id).is_some());
+        assert_eq!(
+            client.try_archive_campaign(&campaign_id, &beneficiary),
+            Err(Ok(SaviaError::InvalidInput))
+        );
     }
 
-    /// Get trust score
-    pub fn get_trust_score(env: Env, entity: Address) -> Option<TrustScore> {
-        env.storage().persistent().get(&DataKey::TrustScore(entity))
+    #[test]
+    fn test_archive_blocked_by_refundable_donations() {
+        let env = Env::default();
+        let client = setup(&env);
+        let admin = admin_of(&env, &client);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_all_or_nothing_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 10_000);
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &String::from_str(&env, ""));
+
+        env.ledger().with_mut(|li| li.timestamp += 31 * DAY);
+        client.finalize_campaign(&campaign_id);
+        client.set_archive_grace_period(&DAY);
+        assert_eq!(client.get_archive_grace_period(), DAY);
+        env.ledger().with_mut(|li| li.timestamp += DAY);
+
+        assert_eq!(
+            client.try_archive_campaign(&campaign_id, &admin),
+            Err(Ok(SaviaError::CampaignLocked))
+        );
+
+        client.claim_refund(&donation_id);
+        client.archive_campaign(&campaign_id, &admin);
+        assert!(client.get_donation(&donation_id).is_none());
     }
 
-    /// Mint donation NFT
-    fn mint_donation_nft(
-        env: Env,
-        owner: Address,
-        campaign_id: BytesN<32>,
-        donation_id: BytesN<32>,
-        amount: u64,
-    ) -> Result<BytesN<32>, soroban_sdk::Error> {
-        // Get and increment NFT counter
-        let counter: u64 = env.storage().instance().get(&DataKey::NFTCounter).unwrap_or(0);
-        let new_counter = counter + 1;
-        env.storage().instance().set(&DataKey::NFTCounter, &new_counter);
+    #[test]
+    fn test_hidden_donations_count_privately() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let donor = funded_donor(&env, &client, 1_000_000);
+        let empty = String::from_str(&env, "");
 
-        // Generate NFT ID
-        let mut hash_input = Bytes::new(&env);
-        
-        let owner_bytes = owner.clone().to_xdr(&env);
-        let campaign_bytes = Bytes::from_slice(&env, campaign_id.to_array().as_slice());
-        let donation_bytes = Bytes::from_slice(&env, donation_id.to_array().as_slice());
-        
-        hash_input.append(&owner_bytes);
-        hash_input.append(&campaign_bytes);
-        hash_input.append(&donation_bytes);
-        hash_input.append(&Bytes::from_slice(&env, &amount.to_be_bytes()));
-        hash_input.append(&Bytes::from_slice(&env, &new_counter.to_be_bytes()));
-        
-        let nft_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+        let trust_events = topics_for(&env, topics::TRUST, topics::UPDATED).len();
+        let nft_events = events_for(&env, topics::NFT, topics::MINTED).len();
+        let hidden_id = client.donate_with_privacy(&campaign_id, &donor, &5000, &Privacy::HideDonor, &true, &empty);
 
-        // Determine badge type based on amount
-        let badge_type = Self::get_badge_type(&env, amount);
+        // Nothing public names the donor
+        assert!(client.get_leaderboard(&campaign_id).is_empty());
+        assert!(client.get_donations_by_donor(&donor, &0, &10).is_empty());
+        assert_eq!(client.get_donation(&hidden_id).unwrap().donor, client.address);
+        assert!(client.get_nfts_by_owner_at(&donor, &0, &10).is_empty());
+        assert!(!client.get_donation_as(&hidden_id, &donor).nft_minted);
+        assert_eq!(topics_for(&env, topics::TRUST, topics::UPDATED).len(), trust_events);
+        assert_eq!(events_for(&env, topics::NFT, topics::MINTED).len(), nft_events);
 
-        let nft_badge = NFTBadge {
-            id: nft_id.clone(),
-            owner,
-            badge_type,
-            campaign_id: Some(campaign_id),
-            minted_at: env.ledger().timestamp(),
-            metadata_uri: String::from_str(&env, "https://savia.org/nft/metadata"),
-        };
+        // but the gift still counts toward the donor's score
+        assert_eq!(client.get_trust_score(&donor).unwrap().total_donated, 4900);
 
-        env.storage().persistent().set(&DataKey::NFTBadge(nft_id.clone()), &nft_badge);
-        Ok(nft_id)
+        // and toward the tier of their next public badge
+        let public_id = client.donate(&campaign_id, &donor, &1000, &false, &true, &empty);
+        let badges = client.get_nfts_by_owner_at(&donor, &0, &10);
+        assert_eq!(badges.len(), 1);
+        assert_eq!(badges.get(0).unwrap().tier, BadgeTier::Gold);
+        assert_eq!(client.get_leaderboard(&campaign_id), soroban_sdk::vec![&env, (donor.clone(), 980)]);
+
+        // The donor sees both gifts, unredacted
+        let (first, cursor) = client.get_my_donations(&donor, &None, &1);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first.get(0).unwrap().id, hidden_id);
+        assert_eq!(first.get(0).unwrap().donor, donor);
+        let (rest, cursor) = client.get_my_donations(&donor, &cursor, &10);
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest.get(0).unwrap().id, public_id);
+        assert_eq!(cursor, None);
     }
 
-    /// Get NFT details
-    pub fn get_nft(env: Env, nft_id: BytesN<32>) -> Option<NFTBadge> {
-        env.storage().persistent().get(&DataKey::NFTBadge(nft_id))
+    /// `<base>/<nft id in hex>`, encoded independently of the contract
+    fn expected_token_uri(env: &Env, base: &str, nft_id: &BytesN<32>) -> String {
+        let hex: std::string::String = nft_id.to_array().iter().map(|byte| std::format!("{:02x}", byte)).collect();
+        String::from_str(env, &std::format!("{}/{}", base, hex))
     }
 
-    /// Create disbursement request
-    pub fn create_disbursement(
-        env: Env,
-        campaign_id: BytesN<32>,
-        recipient: Address,
-        amount: u64,
-        milestone: String,
-    ) -> Result<BytesN<32>, soroban_sdk::Error> {
-        let campaign: Campaign = env.storage().persistent().get(&DataKey::Campaign(campaign_id.clone()))
-            .ok_or(soroban_sdk::Error::from_contract_error(SaviaError::CampaignNotFound as u32))?;
+    #[test]
+    fn test_badges_get_per_nft_metadata_uris() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let base = "https://badges.example.org/meta";
+        assert_eq!(client.get_metadata_base_uri(), String::from_str(&env, DEFAULT_METADATA_BASE_URI));
 
-        if amount > campaign.current_amount {
-            return Err(soroban_sdk::Error::from_contract_error(SaviaError::InsufficientFunds as u32));
-        }
+        client.set_metadata_base_uri(&String::from_str(&env, base));
+        assert_eq!(client.get_metadata_base_uri(), String::from_str(&env, base));
 
-        // Get and increment disbursement counter
-        let counter: u64 = env.storage().instance().get(&DataKey::DisbursementCounter).unwrap_or(0);
-        let new_counter = counter + 1;
-        env.storage().instance().set(&DataKey::DisbursementCounter, &new_counter);
+        let first = funded_donor(&env, &client, 10_000);
+        let second = funded_donor(&env, &client, 10_000);
+        client.donate(&campaign_id, &first, &500, &false, &true, &String::from_str(&env, ""));
+        client.donate(&campaign_id, &second, &500, &false, &true, &String::from_str(&env, ""));
+        let first_id = client.get_nfts_by_owner_at(&first, &0, &1).get(0).unwrap().id;
+        let second_id = client.get_nfts_by_owner_at(&second, &0, &1).get(0).unwrap().id;
 
-        // Generate disbursement ID
-        let mut hash_input = Bytes::new(&env);
-        
-        let campaign_bytes = Bytes::from_slice(&env, campaign_id.to_array().as_slice());
-        let recipient_bytes = recipient.clone().to_xdr(&env);
-        let milestone_bytes = milestone.to_string().into_bytes();
-        
-        hash_input.append(&campaign_bytes);
-        hash_input.append(&recipient_bytes);
-        hash_input.append(&Bytes::from_slice(&env, &amount.to_be_bytes()));
-        hash_input.append(&Bytes::from_slice(&env, &milestone_bytes));
-        hash_input.append(&Bytes::from_slice(&env, &new_counter.to_be_bytes()));
-        
-        let disbursement_id: BytesN<32> = env.crypto().sha256(&hash_input).into();
+        let first_uri = client.token_uri(&first_id);
+        let second_uri = client.token_uri(&second_id);
+        assert_ne!(first_uri, second_uri);
+        assert_eq!(first_uri, expected_token_uri(&env, base, &first_id));
+        assert_eq!(second_uri, expected_token_uri(&env, base, &second_id));
+        assert_eq!(client.get_nft(&first_id).unwrap().metadata_uri, first_uri);
 
-        let disbursement = Disbursement {
-            id: disbursement_id.clone(),
-            campaign_id,
-            recipient,
-            amount,
-            milestone,
-            status: DisbursementStatus::Pending,
-            created_at: env.ledger().timestamp(),
-            executed_at: None,
-        };
+        // A trailing slash is not doubled
+        client.set_metadata_base_uri(&String::from_str(&env, "https://badges.example.org/"));
+        let third = funded_donor(&env, &client, 10_000);
+        client.donate(&campaign_id, &third, &500, &false, &true, &String::from_str(&env, ""));
+        let third_id = client.get_nfts_by_owner_at(&third, &0, &1).get(0).unwrap().id;
+        assert_eq!(client.token_uri(&third_id), expected_token_uri(&env, "https://badges.example.org", &third_id));
 
-        env.storage().persistent().set(&DataKey::Disbursement(disbursement_id.clone()), &disbursement);
-        Ok(disbursement_id)
+        assert_eq!(client.try_token_uri(&BytesN::from_array(&env, &[0; 32])), Err(Ok(SaviaError::NFTNotFound)));
+        assert_eq!(client.try_set_metadata_base_uri(&String::from_str(&env, "")), Err(Ok(SaviaError::InvalidInput)));
+        let too_long = String::from_bytes(&env, &[b'a'; MAX_METADATA_BASE_URI_LEN as usize + 1]);
+        assert_eq!(client.try_set_metadata_base_uri(&too_long), Err(Ok(SaviaError::InvalidInput)));
     }
 
-    /// Execute approved disbursement
-    pub fn execute_disbursement(
-        env: Env,
-        disbursement_id: BytesN<32>,
-    ) -> Result<(), soroban_sdk::Error> {
-        let mut disbursement: Disbursement = env.storage().persistent().get(&DataKey::Disbursement(disbursement_id.clone()))
-            .ok_or(soroban_sdk::Error::from_contract_error(SaviaError::DisbursementNotFound as u32))?;
+    #[test]
+    fn test_disputes_hold_donations_until_dismissed() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let empty = String::from_str(&env, "");
+        let reason = String::from_str(&env, "ipfs://dispute");
+        client.set_dispute_threshold(&DisputeThreshold { max_open: 1, max_open_bps: 0 });
 
-        if disbursement.status != DisbursementStatus::Approved {
-            return Err(soroban_sdk::Error::from_contract_error(SaviaError::NotApproved as u32));
-        }
+        let first = funded_donor(&env, &client, 10_000);
+        let second = funded_donor(&env, &client, 10_000);
+        client.donate(&campaign_id, &first, &1000, &false, &false, &empty);
+        client.donate(&campaign_id, &second, &1000, &false, &true, &empty);
 
-        disbursement.status = DisbursementStatus::Executed;
-        disbursement.executed_at = Some(env.ledger().timestamp());
+        // Only donors may file, once at a time
+        assert_eq!(
+            client.try_file_dispute(&campaign_id, &Address::generate(&env), &reason),
+            Err(Ok(SaviaError::Unauthorized))
+        );
+        let first_dispute = client.file_dispute(&campaign_id, &first, &reason);
+        assert_eq!(client.try_file_dispute(&campaign_id, &first, &reason), Err(Ok(SaviaError::AlreadyReported)));
+        assert!(!client.is_dispute_hold(&campaign_id));
 
-        env.storage().persistent().set(&DataKey::Disbursement(disbursement_id), &disbursement);
-        Ok(())
+        // The second open dispute passes the threshold
+        let second_dispute = client.file_dispute(&campaign_id, &second, &reason);
+        assert!(client.is_dispute_hold(&campaign_id));
+        assert_eq!(client.get_open_disputes(&campaign_id), soroban_sdk::vec![&env, first_dispute.clone(), second_dispute.clone()]);
+        let donor = funded_donor(&env, &client, 10_000);
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &1000, &false, &false, &empty),
+            Err(Ok(SaviaError::CampaignLocked))
+        );
+
+        // Dismissing one brings the campaign back within the threshold
+        client.resolve_dispute(&first_dispute, &false);
+        assert!(!client.is_dispute_hold(&campaign_id));
+        assert_eq!(client.get_dispute(&first_dispute).unwrap().status, DisputeStatus::Dismissed);
+        assert_eq!(client.try_resolve_dispute(&first_dispute, &true), Err(Ok(SaviaError::InvalidInput)));
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &empty);
+        assert!(!client.get_campaign(&campaign_id).unwrap().suspended);
     }
 
-    /// Get disbursement details
-    pub fn get_disbursement(env: Env, disbursement_id: BytesN<32>) -> Option<Disbursement> {
-        env.storage().persistent().get(&DataKey::Disbursement(disbursement_id))
+    #[test]
+    fn test_dispute_hold_blocks_payouts() {
+        let env = Env::default();
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let empty = String::from_str(&env, "");
+        client.set_dispute_threshold(&DisputeThreshold { max_open: 0, max_open_bps: 5000 });
+
+        let reporter = funded_donor(&env, &client, 10_000);
+        client.donate(&campaign_id, &reporter, &1000, &false, &false, &empty);
+        let approved = client.create_disbursement(&campaign_id, &beneficiary, &100, &0);
+        client.approve_disbursement(&approved, &admin_of(&env, &client));
+
+        let dispute_id = client.file_dispute(&campaign_id, &reporter, &String::from_str(&env, "ipfs://dispute"));
+        assert!(client.is_dispute_hold(&campaign_id));
+        client.close_campaign(&campaign_id);
+        let locked = Some(Ok(SaviaError::CampaignLocked));
+        assert_eq!(client.try_create_disbursement(&campaign_id, &beneficiary, &100, &0).err(), locked);
+        assert_eq!(client.try_execute_disbursement(&approved, &0).err(), locked);
+        assert_eq!(client.try_withdraw_remaining(&campaign_id, &beneficiary).err(), locked);
+
+        // Dismissing the dispute releases the escrow
+        client.resolve_dispute(&dispute_id, &false);
+        client.execute_disbursement(&approved, &0);
+        assert_eq!(client.withdraw_remaining(&campaign_id, &beneficiary), 880);
     }
 
-    /// Helper function to determine badge type based on amount
-    fn get_badge_type(env: &Env, amount: u64) -> String {
-        if amount < 1000 {
-            String::from_str(env, "Bronze Supporter")
-        } else if amount < 5000 {
-            String::from_str(env, "Silver Supporter")
-        } else if amount < 10000 {
-            String::from_str(env, "Gold Supporter")
-        } else if amount < 50000 {
-            String::from_str(env, "Platinum Supporter")
-        } else {
-            String::from_str(env, "Diamond Supporter")
-        }
+    #[test]
+    fn test_dispute_after_close_holds_escrow() {
+        let env = Env::default();
+        let client = setup(&env);
+        client.set_platform_fee(&0);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let empty = String::from_str(&env, "");
+        client.set_dispute_threshold(&DisputeThreshold { max_open: 0, max_open_bps: 5000 });
+
+        // The goal closes the campaign, so the payout is open straight away
+        client.set_close_on_goal(&campaign_id, &true);
+        let reporter = funded_donor(&env, &client, 20_000);
+        client.donate(&campaign_id, &reporter, &10_000, &false, &false, &empty);
+        assert!(!client.get_campaign(&campaign_id).unwrap().active);
+
+        client.file_dispute(&campaign_id, &reporter, &String::from_str(&env, "ipfs://dispute"));
+        assert!(client.is_dispute_hold(&campaign_id));
+        assert_eq!(
+            client.try_withdraw_remaining(&campaign_id, &beneficiary),
+            Err(Ok(SaviaError::CampaignLocked))
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use soroban_sdk::{testutils::Address as _, Address, Env};
+    #[test]
+    fn test_upheld_dispute_suspends_campaign() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        let empty = String::from_str(&env, "");
+        // One open dispute among two donors is 50%
+        client.set_dispute_threshold(&DisputeThreshold { max_open: 0, max_open_bps: 4000 });
+
+        let reporter = funded_donor(&env, &client, 10_000);
+        let other = funded_donor(&env, &client, 10_000);
+        let donation_id = client.donate(&campaign_id, &reporter, &1000, &false, &false, &empty);
+        client.donate(&campaign_id, &other, &1000, &false, &false, &empty);
+
+        let dispute_id = client.file_dispute(&campaign_id, &reporter, &String::from_str(&env, "ipfs://dispute"));
+        assert!(client.is_dispute_hold(&campaign_id));
+        let held = events_for(&env, topics::CAMPAIGN, topics::HELD).last().unwrap();
+        assert_eq!(<(BytesN<32>, u32)>::try_from_val(&env, &held).unwrap(), (campaign_id.clone(), 1));
+
+        client.resolve_dispute(&dispute_id, &true);
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert!(campaign.suspended);
+        assert_eq!(campaign.outcome, CampaignOutcome::Suspended);
+        assert_eq!(client.get_dispute(&dispute_id).unwrap().status, DisputeStatus::Upheld);
+        assert!(client.get_open_disputes(&campaign_id).is_empty());
+        assert!(!client.is_dispute_hold(&campaign_id));
+
+        // Donors can take their money back
+        assert_eq!(client.claim_refund(&donation_id), 980);
+        assert_eq!(
+            client.try_file_dispute(&campaign_id, &other, &String::from_str(&env, "ipfs://late")),
+            Err(Ok(SaviaError::CampaignSuspended))
+        );
+    }
 
     #[test]
-    fn test_initialize_contract() {
+    fn test_bundle_splits_one_payment() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, SaviaContract);
-        let client = SaviaContractClient::new(&env, &contract_id);
+        let client = setup(&env);
+        let campaigns = [
+            create_test_campaign(&env, &client, &Address::generate(&env)),
+            create_test_campaign(&env, &client, &Address::generate(&env)),
+            create_test_campaign(&env, &client, &Address::generate(&env)),
+        ];
+        let allocations = soroban_sdk::vec![
+            &env,
+            (campaigns[0].clone(), 5000u32),
+            (campaigns[1].clone(), 3000u32),
+            (campaigns[2].clone(), 2000u32),
+        ];
+        let name = String::from_str(&env, "Hurricane Relief");
+        let bundle_id = client.create_bundle(&name, &allocations);
+        let bundle = client.get_bundle(&bundle_id).unwrap();
+        assert_eq!((bundle.name, bundle.allocations), (name, allocations));
 
-        let result = client.initialize(&200);
-        assert!(result.is_ok());
+        // 1001 splits 500/300/200 with the dust going to the first
+        let donor = funded_donor(&env, &client, 10_000);
+        let token = token::Client::new(&env, &token_address(&env, &client));
+        let donation_ids = client.donate_to_bundle(&bundle_id, &donor, &1001, &false, &true);
+        assert_eq!(token.balance(&donor), 10_000 - 1001);
+
+        let mut net = 0;
+        for (index, donation_id) in donation_ids.iter().enumerate() {
+            let donation = client.get_donation(&donation_id).unwrap();
+            assert_eq!(donation.campaign_id, campaigns[index]);
+            assert_eq!(client.get_campaign(&campaigns[index]).unwrap().current_amount, donation.amount);
+            net += donation.amount;
+        }
+        let gifts: std::vec::Vec<i128> = donation_ids.iter()
+            .map(|id| { let d = client.get_donation(&id).unwrap(); d.amount + d.platform_fee })
+            .collect();
+        assert_eq!(gifts, [501, 300, 200]);
+        assert_eq!(gifts.iter().sum::<i128>(), 1001);
+
+        // One badge for the bundle, at the tier of the whole net gift
+        let badges = client.get_nfts_by_owner_at(&donor, &0, &10);
+        assert_eq!(badges.len(), 1);
+        let badge = badges.get(0).unwrap();
+        assert_eq!((badge.campaign_id, badge.donation_amount), (bundle_id.clone(), net));
     }
 
     #[test]
-    fn test_create_campaign() {
+    fn test_bundle_validation() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, SaviaContract);
-        let client = SaviaContractClient::new(&env, &contract_id);
+        let client = setup(&env);
+        let first = create_test_campaign(&env, &client, &Address::generate(&env));
+        let second = create_test_campaign(&env, &client, &Address::generate(&env));
+        let name = String::from_str(&env, "Relief");
+
+        for allocations in [
+            soroban_sdk::vec![&env, (first.clone(), 5000u32), (second.clone(), 4000u32)],
+            soroban_sdk::vec![&env, (first.clone(), 5000u32), (first.clone(), 5000u32)],
+            soroban_sdk::vec![&env, (first.clone(), 10000u32), (second.clone(), 0u32)],
+            soroban_sdk::vec![&env],
+        ] {
+            assert_eq!(client.try_create_bundle(&name, &allocations), Err(Ok(SaviaError::InvalidInput)));
+        }
+        assert_eq!(
+            client.try_create_bundle(&name, &soroban_sdk::vec![&env, (BytesN::from_array(&env, &[9; 32]), 10000u32)]),
+            Err(Ok(SaviaError::CampaignNotFound))
+        );
 
-        client.initialize(&200);
+        // A closed campaign fails the whole donation
+        let bundle_id = client.create_bundle(&name, &soroban_sdk::vec![&env, (first.clone(), 6000u32), (second.clone(), 4000u32)]);
+        client.close_campaign(&second);
+        let donor = funded_donor(&env, &client, 10_000);
+        assert_eq!(
+            client.try_donate_to_bundle(&bundle_id, &donor, &1000, &false, &false),
+            Err(Ok(SaviaError::CampaignInactive))
+        );
+        assert_eq!(client.get_campaign(&first).unwrap().current_amount, 0);
+        assert_eq!(
+            client.try_donate_to_bundle(&BytesN::from_array(&env, &[9; 32]), &donor, &1000, &false, &false),
+            Err(Ok(SaviaError::InvalidInput))
+        );
+    }
 
+    #[test]
+    fn test_donate_and_create_campaign_need_initialize() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = SaviaContractClient::new(&env, &env.register_contract(None, SaviaContract));
         let beneficiary = Address::generate(&env);
-        let result = client.create_campaign(
-            &beneficiary,
-            &String::from_str(&env, "Test Campaign"),
-            &String::from_str(&env, "A test campaign"),
-            &10000,
-            &30,
-            &String::from_str(&env, "Health"),
-            &String::from_str(&env, "Test City"),
+        let text = String::from_str(&env, "Relief");
+        assert!(!client.is_initialized());
+        assert_eq!(client.version(), 0);
+
+        assert_eq!(
+            client.try_create_campaign(&beneficiary, &text, &text, &10000, &30, &Category::Health, &text, &false, &FundingMode::KeepItAll).err(),
+            Some(Ok(SaviaError::NotInitialized))
+        );
+        assert_eq!(
+            client.try_donate(&BytesN::from_array(&env, &[1; 32]), &Address::generate(&env), &100, &false, &false, &text).err(),
+            Some(Ok(SaviaError::NotInitialized))
         );
 
-        assert!(result.is_ok());
-        let campaign_id = result.unwrap();
-        let campaign = client.get_campaign(&campaign_id);
-        assert!(campaign.is_some());
+        // A zero platform fee is a valid choice
+        let token = env.register_stellar_asset_contract_v2(Address::generate(&env));
+        client.initialize(&Address::generate(&env), &token.address(), &0);
+        assert!(client.is_initialized());
+        assert_eq!(client.version(), CONTRACT_VERSION);
+        assert_eq!(client.get_platform_fee(), 0);
+
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 1000);
+        let donation_id = client.donate(&campaign_id, &donor, &1000, &false, &false, &text);
+        let donation = client.get_donation(&donation_id).unwrap();
+        assert_eq!((donation.amount, donation.platform_fee), (1000, 0));
     }
 
     #[test]
-    fn test_donation_flow() {
+    fn test_fundraiser_tally_and_ambassador_badge() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, SaviaContract);
-        let client = SaviaContractClient::new(&env, &contract_id);
+        let client = setup(&env);
+        let beneficiary = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let fundraiser = Address::generate(&env);
+        let empty = String::from_str(&env, "");
+        client.set_ambassador_thresholds(&AmbassadorThresholds { min_raised: 2000, min_donations: 3 });
+
+        // Only the beneficiary registers fundraisers, once each
+        let unsigned = client.mock_auths(&[]);
+        assert_unauthorized(unsigned.try_register_fundraiser(&campaign_id, &fundraiser));
+        client.register_fundraiser(&campaign_id, &fundraiser);
+        assert_eq!(client.try_register_fundraiser(&campaign_id, &fundraiser), Err(Ok(SaviaError::InvalidInput)));
+        assert_eq!(client.try_register_fundraiser(&campaign_id, &beneficiary), Err(Ok(SaviaError::InvalidInput)));
 
-        client.initialize(&200);
+        // Unregistered fundraisers are ignored
+        let stranger = Address::generate(&env);
+        let donor = funded_donor(&env, &client, 100_000);
+        client.donate_with_fundraiser(&campaign_id, &donor, &1000, &false, &false, &empty, &Some(stranger.clone()));
+        assert_eq!(client.get_fundraiser_stats(&campaign_id, &stranger), None);
+        assert_eq!(client.get_trust_score(&stranger).map(|trust| trust.score), None);
+
+        let before = client.get_trust_score(&fundraiser).map(|trust| trust.score).unwrap_or(50);
+        client.donate_with_fundraiser(&campaign_id, &donor, &1000, &false, &false, &empty, &Some(fundraiser.clone()));
+        client.donate_with_fundraiser(&campaign_id, &donor, &1000, &true, &false, &empty, &Some(fundraiser.clone()));
+        let stats = client.get_fundraiser_stats(&campaign_id, &fundraiser).unwrap();
+        assert_eq!((stats.donation_count, stats.raised, stats.trust_bonus), (2, 1960, 2));
+        assert_eq!(client.get_trust_score(&fundraiser).unwrap().score, before + 2);
+        assert!(!stats.ambassador);
+        assert!(client.get_nfts_by_owner_at(&fundraiser, &0, &10).is_empty());
+
+        // The third donation passes both thresholds
+        client.donate_with_fundraiser(&campaign_id, &donor, &500, &false, &false, &empty, &Some(fundraiser.clone()));
+        let stats = client.get_fundraiser_stats(&campaign_id, &fundraiser).unwrap();
+        assert_eq!((stats.donation_count, stats.raised), (3, 2450));
+        let badges = client.get_nfts_by_owner_at(&fundraiser, &0, &10);
+        assert_eq!(badges.len(), 1);
+        let badge = badges.get(0).unwrap();
+        assert!(stats.ambassador);
+        assert_eq!((badge.tier, badge.badge_type, badge.campaign_id), (BadgeTier::Ambassador, String::from_str(&env, "Ambassador"), campaign_id.clone()));
+
+        // Only one badge per campaign
+        client.donate_with_fundraiser(&campaign_id, &donor, &500, &false, &false, &empty, &Some(fundraiser.clone()));
+        assert_eq!(client.get_nfts_by_owner_at(&fundraiser, &0, &10).len(), 1);
+        assert_eq!(client.get_fundraiser_stats(&campaign_id, &fundraiser).unwrap().donation_count, 4);
+    }
 
+    #[test]
+    fn test_freeze_blocks_donations_until_unfrozen() {
+        let env = Env::default();
+        let client = setup(&env);
         let beneficiary = Address::generate(&env);
-        let donor = Address::generate(&env);
+        let campaign_id = create_test_campaign(&env, &client, &beneficiary);
+        let donor = funded_donor(&env, &client, 100_000);
+        let empty = String::from_str(&env, "");
+        client.donate(&campaign_id, &donor, &5000, &false, &false, &empty);
+        let approved = client.create_disbursement(&campaign_id, &beneficiary, &500, &0);
+        client.approve_disbursement(&approved, &admin_of(&env, &client));
 
-        // Create campaign
-        let campaign_id = client.create_campaign(
-            &beneficiary,
-            &String::from_str(&env, "Test Campaign"),
-            &String::from_str(&env, "A test campaign"),
-            &10000,
-            &30,
-            &String::from_str(&env, "Health"),
-            &String::from_str(&env, "Test City"),
-        ).unwrap();
+        assert_eq!(
+            client.try_freeze_campaign(&BytesN::from_array(&env, &[9; 32]), &true),
+            Err(Ok(SaviaError::CampaignNotFound))
+        );
+        let unsigned = client.mock_auths(&[]);
+        assert_unauthorized(unsigned.try_freeze_campaign(&campaign_id, &true));
 
-        // Make donation
-        let donation_id = client.donate(
-            &campaign_id,
-            &donor,
-            &1000,
-            &false,
-            &true,
-        ).unwrap();
+        client.freeze_campaign(&campaign_id, &true);
+        assert!(client.is_frozen(&campaign_id));
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::FROZEN).len(), 1);
+        assert_eq!(
+            client.try_donate(&campaign_id, &donor, &1000, &false, &false, &empty),
+            Err(Ok(SaviaError::CampaignFrozen))
+        );
+        assert_eq!(
+            client.try_create_disbursement(&campaign_id, &beneficiary, &500, &0),
+            Err(Ok(SaviaError::CampaignFrozen))
+        );
 
-        // Verify donation
-        let donation = client.get_donation(&donation_id);
-        assert!(donation.is_some());
-        assert_eq!(donation.unwrap().amount, 980); // 1000 - 2% fee
+        // Nothing else about the campaign changes
+        let campaign = client.get_campaign(&campaign_id).unwrap();
+        assert!(campaign.active && !campaign.suspended);
+        client.execute_disbursement(&approved, &0);
+
+        client.freeze_campaign(&campaign_id, &false);
+        assert!(!client.is_frozen(&campaign_id));
+        assert_eq!(events_for(&env, topics::CAMPAIGN, topics::UNFROZEN).len(), 1);
+        client.donate(&campaign_id, &donor, &1000, &false, &false, &empty);
+        client.create_disbursement(&campaign_id, &beneficiary, &500, &0);
+    }
+
+    #[test]
+    fn test_frozen_campaign_can_be_finalized() {
+        let env = Env::default();
+        let client = setup(&env);
+        let campaign_id = create_test_campaign(&env, &client, &Address::generate(&env));
+        client.freeze_campaign(&campaign_id, &true);
+
+        env.ledger().with_mut(|li| li.timestamp += 31 * DAY);
+        assert!(!client.finalize_campaign(&campaign_id));
+        assert!(!client.get_campaign(&campaign_id).unwrap().active);
     }
-}
\ No newline at end of file
+}